@@ -5,8 +5,8 @@ use log::info;
 use std::path::PathBuf;
 
 use crate::auth::Token;
-use crate::config::{Config, GitLabConfig, GitHubConfig, OutputFormat};
-use crate::providers::{GitHubProvider, GitLabProvider, JobCache};
+use crate::config::{CacheConfig, Config, CostsConfig, GitLabConfig, GitHubConfig, HttpConfig, LabelRule, OutputFormat, SignatureRule};
+use crate::providers::{BillingModel, Clustering, CostRates, GitHubProvider, GitLabProvider, GitLabProviderOptions, HttpSettings, JobCache, LabelRules, PipelineSource, Sampling, SignatureRules, TestReportCache, TraceCache};
 
 /// Command-line interface for `CILens`.
 ///
@@ -52,6 +52,61 @@ pub struct Cli {
         help = "Output format: summary, json, csv, html"
     )]
     format: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECS",
+        help = "Timeout for each API request in seconds"
+    )]
+    timeout: Option<u64>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECS",
+        help = "Timeout for connecting to the API in seconds"
+    )]
+    connect_timeout: Option<u64>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "URL",
+        help = "Proxy for all API requests (defaults to HTTPS_PROXY/HTTP_PROXY)"
+    )]
+    proxy: Option<String>,
+
+    #[arg(
+        long = "ca-cert",
+        global = true,
+        value_name = "FILE",
+        help = "PEM file with extra CA certificates to trust (repeatable)"
+    )]
+    ca_certs: Vec<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE",
+        help = "PEM client certificate for mutual TLS (may include the private key)"
+    )]
+    client_cert: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE",
+        help = "PEM private key for --client-cert"
+    )]
+    client_key: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Skip TLS certificate verification (insecure, prints a warning)"
+    )]
+    insecure: bool,
 }
 
 /// Configuration for GitHub insights collection.
@@ -114,11 +169,97 @@ enum Commands {
         )]
         min_type_percentage: u8,
 
+        #[arg(
+            long,
+            help = "Merge pipelines whose job sets have at least this Jaccard similarity (0.0-1.0) into one type",
+            value_parser = parse_similarity_threshold,
+        )]
+        similarity_threshold: Option<f64>,
+
+        #[arg(
+            long,
+            help = "How to sample pipelines within --limit: balanced (half success, half failed; default), chronological (most recent of any status) or stratified (per status, weighted by real status ratios)"
+        )]
+        sampling: Option<Sampling>,
+
+        #[arg(
+            long = "environment",
+            help = "Deployment environment to report delivery metrics for (repeatable, default: production)"
+        )]
+        environments: Vec<String>,
+
+        #[arg(
+            long,
+            help = "Fetch JUnit test reports for per-test failure and flakiness metrics (slower)"
+        )]
+        test_reports: bool,
+
+        #[arg(
+            long,
+            help = "Download failed job logs and cluster the failures by signature (slower)"
+        )]
+        failure_signatures: bool,
+
+        #[arg(
+            long,
+            help = "Also show the pipelines that are running or pending right now and how long they have been queued"
+        )]
+        live: bool,
+
+        #[arg(
+            long,
+            help = "Fetch the most recent merge request pipelines for the merge request report, beyond the sample (slower)"
+        )]
+        merge_requests: bool,
+
+        #[arg(
+            long,
+            help = "Fetch the most recent canceled pipelines for the canceled pipelines report, beyond the sample (slower)"
+        )]
+        canceled_pipelines: bool,
+
+        #[arg(
+            long,
+            help = "Fetch the most recent pipelines of --ref in order for the branch health report, unless --sampling chronological already is that timeline (slower)"
+        )]
+        branch_timeline: bool,
+
         #[arg(long, help = "Disable job caching (fetch all data fresh)")]
         no_cache: bool,
 
-        #[arg(long, help = "Clear the job cache before running")]
+        #[arg(
+            long,
+            conflicts_with = "offline",
+            help = "Clear the job cache before running"
+        )]
         clear_cache: bool,
+
+        #[arg(
+            long,
+            help = "Keep cached pipelines of earlier runs with other filters instead of replacing them"
+        )]
+        keep_cache: bool,
+
+        #[arg(
+            long,
+            conflicts_with = "from_raw",
+            help = "Analyze the pipelines cached by a previous run, without any API request"
+        )]
+        offline: bool,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Analyze pipelines from a raw data file written with --export-raw, without any API request"
+        )]
+        from_raw: Option<PathBuf>,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Export the analyzed pipelines with their jobs as raw data (JSON, or NDJSON for .ndjson/.jsonl files)"
+        )]
+        export_raw: Option<PathBuf>,
     },
     /// Collect CI/CD insights from GitHub Actions
     Github {
@@ -149,6 +290,18 @@ enum Commands {
         #[arg(long, name = "ref", help = "Filter workflow runs by git ref (branch/tag)")]
         ref_: Option<String>,
 
+        #[arg(
+            long,
+            help = "Only analyze runs of this workflow (file name like 'ci.yml', path, ID or name)"
+        )]
+        workflow: Option<String>,
+
+        #[arg(
+            long,
+            help = "Only analyze runs triggered by this event (e.g., push, pull_request, schedule)"
+        )]
+        event: Option<String>,
+
         #[arg(long, help = "Fetch workflow runs since this date (YYYY-MM-DD)")]
         since: Option<NaiveDate>,
 
@@ -162,6 +315,55 @@ enum Commands {
             value_parser = value_parser!(u8).range(0..=100),
         )]
         min_type_percentage: u8,
+
+        #[arg(
+            long,
+            help = "Reconcile billable minutes against the workflow run timing endpoint (one extra request per run)"
+        )]
+        reconcile_billing: bool,
+    },
+    /// Inspect and maintain the local GitLab pipeline cache
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheCommands {
+    /// Check cache files for unreadable data and corrupt entries
+    Verify {
+        #[arg(help = "GitLab project path (e.g., 'group/project'); all projects if omitted")]
+        project_path: Option<String>,
+    },
+    /// List cached projects with their size and last hit rate
+    List,
+    /// Show entry ages, disk usage and hit rate of cached projects
+    Stats {
+        #[arg(help = "GitLab project path (e.g., 'group/project'); all projects if omitted")]
+        project_path: Option<String>,
+    },
+    /// Drop cached pipelines older than a maximum age or beyond a maximum count
+    Prune {
+        #[arg(help = "GitLab project path (e.g., 'group/project'); all projects if omitted")]
+        project_path: Option<String>,
+
+        #[arg(
+            long,
+            help = "Drop pipelines cached more than this many days ago (defaults to [cache] max-age-days)"
+        )]
+        max_age_days: Option<u32>,
+
+        #[arg(
+            long,
+            help = "Keep at most this many pipelines per project (defaults to [cache] max-entries)"
+        )]
+        max_entries: Option<usize>,
+    },
+    /// Delete cached pipelines, test reports and job logs
+    Clear {
+        #[arg(help = "GitLab project path (e.g., 'group/project'); all projects if omitted")]
+        project_path: Option<String>,
     },
 }
 
@@ -171,6 +373,11 @@ impl Cli {
     /// # Arguments
     ///
     /// * `config` - GitLab configuration including authentication, project path, and filters
+    /// * `costs` - Per-runner cost rates from the config file
+    /// * `labels` - Pipeline type labelling rules from the config file
+    /// * `signatures` - Failure signatures for job log mining from the config file
+    /// * `cache` - Cache retention settings from the config file and `--keep-cache`
+    /// * `http` - Timeout, proxy and TLS settings for API requests
     ///
     /// # Returns
     ///
@@ -178,15 +385,38 @@ impl Cli {
     ///
     /// # Behavior
     ///
-    /// - If `clear_cache` is true, clears the cache and returns without fetching insights
-    /// - Otherwise, fetches pipelines from GitLab and displays results in the requested format
-    async fn execute_gitlab(&self, config: crate::config::GitLabConfig) -> Result<()> {
-        // Handle cache-only operations
+    /// - If `clear_cache` is true, clears the project's caches before fetching
+    /// - Fetches pipelines from GitLab and displays results in the requested format
+    async fn execute_gitlab(
+        &self,
+        config: crate::config::GitLabConfig,
+        costs: &CostsConfig,
+        labels: &[LabelRule],
+        signatures: &[SignatureRule],
+        cache: &CacheConfig,
+        http: &HttpSettings,
+    ) -> Result<()> {
+        let source = match (&config.from_raw, config.offline) {
+            (Some(path), _) => PipelineSource::Raw(path.clone()),
+            (None, true) => PipelineSource::Cache,
+            (None, false) => PipelineSource::Api,
+        };
+        if matches!(source, PipelineSource::Cache) && config.no_cache {
+            anyhow::bail!(
+                "--offline analyzes cached pipelines and cannot be combined with --no-cache"
+            );
+        }
+
         if config.clear_cache.unwrap_or(false) {
+            // Checked here too, as either may come from the config file
+            if matches!(source, PipelineSource::Cache) {
+                anyhow::bail!(
+                    "--offline analyzes cached pipelines and cannot be combined with --clear-cache"
+                );
+            }
             let project_path = config.project_path.as_ref().ok_or_else(|| anyhow::anyhow!("Project path is required"))?;
-            JobCache::clear_project_cache(project_path)?;
+            clear_project_caches(project_path)?;
             info!("Cache cleared successfully");
-            return Ok(());
         }
 
         let token = config.token.as_ref().map(|t| Token::from(t.as_str()));
@@ -196,7 +426,13 @@ impl Cli {
             &config.base_url,
             project_path.to_owned(),
             token,
-            !config.no_cache.unwrap_or(false),
+            &GitLabProviderOptions {
+                http: http.clone(),
+                rate_limits: config.rate_limits.rate_limits(),
+                use_cache: !config.no_cache.unwrap_or(false),
+                retention: cache.retention(),
+                test_reports: config.test_reports,
+            },
         )?;
 
         // Normal insights collection
@@ -223,6 +459,23 @@ impl Cli {
             );
         }
 
+        if let Some(threshold) = config.similarity_threshold {
+            if !(0.0..=1.0).contains(&threshold) {
+                anyhow::bail!("similarity-threshold must be between 0.0 and 1.0, got {threshold}");
+            }
+        }
+
+        let label_rules = LabelRules::new(labels)?;
+        let signature_rules = config
+            .failure_signatures
+            .then(|| SignatureRules::new(signatures))
+            .transpose()?;
+        let cost_rates = CostRates::from_rates(
+            costs.default.or(config.cost_per_minute),
+            &costs.tags,
+            &costs.runner_types,
+        );
+
         let insights = provider
             .collect_insights(
                 config.limit,
@@ -230,7 +483,20 @@ impl Cli {
                 since_datetime,
                 until_datetime,
                 config.min_type_percentage,
-                config.cost_per_minute,
+                cost_rates.as_ref(),
+                config
+                    .similarity_threshold
+                    .map_or(Clustering::Exact, Clustering::Similarity),
+                &label_rules,
+                &config.environments,
+                signature_rules.as_ref(),
+                config.sampling,
+                config.live,
+                config.merge_requests,
+                config.canceled_pipelines,
+                config.branch_timeline,
+                &source,
+                config.export_raw.as_deref(),
             )
             .await?;
 
@@ -272,11 +538,16 @@ impl Cli {
     /// # Arguments
     ///
     /// * `config` - GitHub configuration including authentication, repository path, and filters
+    /// * `http` - Timeout, proxy and TLS settings for API requests
     ///
     /// # Returns
     ///
     /// `Ok(())` on success, or an error if fetching/processing fails.
-    async fn execute_github(&self, config: crate::config::GitHubConfig) -> Result<()> {
+    async fn execute_github(
+        &self,
+        config: crate::config::GitHubConfig,
+        http: &HttpSettings,
+    ) -> Result<()> {
         let token = config.token.as_ref().map(|t| Token::from(t.as_str()));
 
         let repo_path = config.repo_path.as_ref().ok_or_else(|| anyhow::anyhow!("Repository path is required"))?;
@@ -284,6 +555,7 @@ impl Cli {
             config.base_url.to_owned(),
             repo_path.to_owned(),
             token,
+            http,
         )?;
 
         // Normal insights collection
@@ -310,14 +582,29 @@ impl Cli {
             );
         }
 
+        if let Some(workflow) = &config.workflow {
+            info!("Workflow filter: {workflow}");
+        }
+        if let Some(event) = &config.event {
+            info!("Event filter: {event}");
+        }
+
+        let billing = BillingModel::new(
+            config.cost_per_minute,
+            &config.runner_multipliers,
+            config.reconcile_billing,
+        );
+
         let insights = provider
             .collect_insights(
                 config.limit,
+                config.workflow.as_deref(),
+                config.event.as_deref(),
                 config.ref_.as_deref(),
                 since_datetime,
                 until_datetime,
                 config.min_type_percentage,
-                config.cost_per_minute,
+                &billing,
             )
             .await?;
 
@@ -354,6 +641,109 @@ impl Cli {
         Ok(())
     }
 
+    /// Executes a cache maintenance command.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - Cache subcommand to run
+    /// * `cache` - Cache retention settings from the config file, used by `prune`
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or an error if the cache cannot be read or written, or
+    /// verification finds unreadable files or corrupt entries.
+    fn execute_cache(command: &CacheCommands, cache: &CacheConfig) -> Result<()> {
+        match command {
+            CacheCommands::Verify { project_path } => {
+                let verifications = JobCache::verify(project_path.as_deref())?;
+                if verifications.is_empty() {
+                    println!("No cache files found");
+                    return Ok(());
+                }
+
+                for verification in &verifications {
+                    crate::output::print_cache_verification(verification);
+                }
+
+                let failed = verifications.iter().filter(|v| !v.is_ok()).count();
+                if failed > 0 {
+                    anyhow::bail!(
+                        "{failed} of {} cache files failed verification",
+                        verifications.len()
+                    );
+                }
+                println!("All {} cache files are valid", verifications.len());
+
+                Ok(())
+            }
+            CacheCommands::List => {
+                crate::output::print_cache_list(&JobCache::stats(None)?, Utc::now());
+                Ok(())
+            }
+            CacheCommands::Stats { project_path } => {
+                let stats = JobCache::stats(project_path.as_deref())?;
+                crate::output::print_cache_stats(&stats, Utc::now());
+                Ok(())
+            }
+            CacheCommands::Prune {
+                project_path,
+                max_age_days,
+                max_entries,
+            } => {
+                let retention = CacheConfig {
+                    keep: true,
+                    max_age_days: max_age_days.or(cache.max_age_days),
+                    max_entries: max_entries.or(cache.max_entries),
+                }
+                .retention();
+                if retention.max_age.is_none() && retention.max_entries.is_none() {
+                    anyhow::bail!(
+                        "Nothing to prune: pass --max-age-days or --max-entries, or set them in the [cache] config section"
+                    );
+                }
+
+                let results = JobCache::prune(project_path.as_deref(), retention)?;
+                crate::output::print_prune_results(&results);
+
+                Ok(())
+            }
+            CacheCommands::Clear { project_path } => {
+                let removed = match project_path {
+                    Some(project_path) => clear_project_caches(project_path)?,
+                    None => JobCache::clear_all()?,
+                };
+                if removed.is_empty() {
+                    println!("No cache found");
+                    return Ok(());
+                }
+
+                for path in &removed {
+                    println!("Removed {}", path.display());
+                }
+                println!("Cache cleared successfully");
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Merges the HTTP flags with the `[http]` config section, flags taking precedence.
+    fn http_config(&self, config: &HttpConfig) -> HttpConfig {
+        HttpConfig {
+            timeout_secs: self.timeout.or(config.timeout_secs),
+            connect_timeout_secs: self.connect_timeout.or(config.connect_timeout_secs),
+            proxy: self.proxy.clone().or_else(|| config.proxy.clone()),
+            ca_certs: if self.ca_certs.is_empty() {
+                config.ca_certs.clone()
+            } else {
+                self.ca_certs.clone()
+            },
+            client_cert: self.client_cert.clone().or_else(|| config.client_cert.clone()),
+            client_key: self.client_key.clone().or_else(|| config.client_key.clone()),
+            insecure: self.insecure || config.insecure,
+        }
+    }
+
     /// Executes the CLI command.
     ///
     /// Parses the subcommand and routes to the appropriate handler.
@@ -375,8 +765,21 @@ impl Cli {
                 since,
                 until,
                 min_type_percentage,
+                similarity_threshold,
+                sampling,
+                environments,
+                test_reports,
+                failure_signatures,
+                live,
+                merge_requests,
+                canceled_pipelines,
+                branch_timeline,
                 no_cache,
                 clear_cache,
+                keep_cache,
+                offline,
+                from_raw,
+                export_raw,
             } => {
                 // Convert NaiveDate to DateTime<Utc> (start of day UTC)
                 let since_datetime =
@@ -408,12 +811,46 @@ impl Cli {
                     since: since_str,
                     until: until_str,
                     min_type_percentage: merged_min_type_percentage,
+                    similarity_threshold: similarity_threshold.or(config_file.gitlab.similarity_threshold),
+                    sampling: sampling.unwrap_or(config_file.gitlab.sampling),
+                    environments: if environments.is_empty() {
+                        config_file.gitlab.environments.clone()
+                    } else {
+                        environments.clone()
+                    },
+                    test_reports: *test_reports || config_file.gitlab.test_reports,
+                    failure_signatures: *failure_signatures
+                        || config_file.gitlab.failure_signatures,
+                    live: *live || config_file.gitlab.live,
+                    merge_requests: *merge_requests || config_file.gitlab.merge_requests,
+                    canceled_pipelines: *canceled_pipelines
+                        || config_file.gitlab.canceled_pipelines,
+                    branch_timeline: *branch_timeline || config_file.gitlab.branch_timeline,
                     no_cache: *no_cache || config_file.gitlab.no_cache,
                     clear_cache: *clear_cache || config_file.gitlab.clear_cache,
+                    offline: *offline || config_file.gitlab.offline,
+                    from_raw: from_raw
+                        .clone()
+                        .or_else(|| config_file.gitlab.from_raw.clone()),
+                    export_raw: export_raw
+                        .clone()
+                        .or_else(|| config_file.gitlab.export_raw.clone()),
                     cost_per_minute: config_file.gitlab.cost_per_minute,
+                    rate_limits: config_file.gitlab.rate_limits.clone(),
                 };
 
-                self.execute_gitlab(config).await
+                self.execute_gitlab(
+                    config,
+                    &config_file.costs,
+                    &config_file.labels,
+                    &config_file.signatures,
+                    &CacheConfig {
+                        keep: *keep_cache || config_file.cache.keep,
+                        ..config_file.cache.clone()
+                    },
+                    &self.http_config(&config_file.http).settings(),
+                )
+                .await
             }
             Commands::Github {
                 token,
@@ -421,9 +858,12 @@ impl Cli {
                 repo_path,
                 limit,
                 ref_,
+                workflow,
+                event,
                 since,
                 until,
                 min_type_percentage,
+                reconcile_billing,
             } => {
                 // Convert NaiveDate to DateTime<Utc> (start of day UTC)
                 let since_datetime =
@@ -441,16 +881,51 @@ impl Cli {
                     token: token.cloned(),
                     base_url: base_url.to_string(),
                     repo_path: Some(repo_path.to_string()),
+                    workflow: workflow.clone().or_else(|| config_file.github.workflow.clone()),
+                    event: event.clone().or_else(|| config_file.github.event.clone()),
                     limit: *limit,
                     ref_: ref_.map(|s| s.to_string()),
                     since: since_str,
                     until: until_str,
                     min_type_percentage: *min_type_percentage,
-                    cost_per_minute: config_file.gitlab.cost_per_minute, // Reuse cost setting
+                    cost_per_minute: config_file
+                        .github
+                        .cost_per_minute
+                        .or(config_file.gitlab.cost_per_minute),
+                    runner_multipliers: config_file.github.runner_multipliers.clone(),
+                    reconcile_billing: *reconcile_billing || config_file.github.reconcile_billing,
                 };
 
-                self.execute_github(config).await
+                self.execute_github(config, &self.http_config(&config_file.http).settings())
+                    .await
             }
+            Commands::Cache { command } => Self::execute_cache(command, &config_file.cache),
         }
     }
 }
+
+/// Parses a Jaccard similarity threshold, which must lie between 0.0 and 1.0.
+fn parse_similarity_threshold(value: &str) -> std::result::Result<f64, String> {
+    let threshold: f64 = value
+        .parse()
+        .map_err(|_| format!("`{value}` is not a number"))?;
+
+    if (0.0..=1.0).contains(&threshold) {
+        Ok(threshold)
+    } else {
+        Err(format!("{threshold} is not between 0.0 and 1.0"))
+    }
+}
+
+/// Clears the job, test report and job log caches of a project.
+///
+/// # Returns
+///
+/// The removed cache files and directories.
+fn clear_project_caches(project_path: &str) -> Result<Vec<PathBuf>> {
+    let mut removed = JobCache::clear_project_cache(project_path)?;
+    removed.extend(TestReportCache::clear_project_cache(project_path)?);
+    removed.extend(TraceCache::clear_project_cache(project_path)?);
+
+    Ok(removed)
+}
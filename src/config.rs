@@ -1,6 +1,9 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::providers::{CacheRetention, HttpSettings, RateLimits, Sampling};
 
 /// Configuration file structure for CILens.
 ///
@@ -24,6 +27,26 @@ pub struct Config {
     /// Analysis parameters
     #[serde(default)]
     pub analysis: AnalysisConfig,
+
+    /// Per-runner compute cost rates
+    #[serde(default)]
+    pub costs: CostsConfig,
+
+    /// Job cache retention
+    #[serde(default)]
+    pub cache: CacheConfig,
+
+    /// HTTP client settings for both providers
+    #[serde(default)]
+    pub http: HttpConfig,
+
+    /// Pipeline type labelling rules, tried in order
+    #[serde(default)]
+    pub labels: Vec<LabelRule>,
+
+    /// Failure signatures for job log mining, tried in order before the built-in ones
+    #[serde(default)]
+    pub signatures: Vec<SignatureRule>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +79,42 @@ pub struct GitLabConfig {
     #[serde(default = "default_min_type_percentage")]
     pub min_type_percentage: u8,
 
+    /// Merge job sets with at least this Jaccard similarity (0.0-1.0) into one pipeline type
+    #[serde(default)]
+    pub similarity_threshold: Option<f64>,
+
+    /// How pipelines are sampled within `limit`: balanced, chronological or stratified
+    #[serde(default)]
+    pub sampling: Sampling,
+
+    /// Deployment environments to report delivery metrics for
+    #[serde(default = "default_environments")]
+    pub environments: Vec<String>,
+
+    /// Fetch JUnit test reports for per-test failure and flakiness metrics
+    #[serde(default)]
+    pub test_reports: bool,
+
+    /// Download failed job logs and cluster the failures by signature
+    #[serde(default)]
+    pub failure_signatures: bool,
+
+    /// Also report the pipelines that are running or pending right now
+    #[serde(default)]
+    pub live: bool,
+
+    /// Fetch the most recent merge request pipelines beyond the sample
+    #[serde(default)]
+    pub merge_requests: bool,
+
+    /// Fetch the most recent canceled pipelines beyond the sample
+    #[serde(default)]
+    pub canceled_pipelines: bool,
+
+    /// Fetch the most recent pipelines of `ref` in order for branch health
+    #[serde(default)]
+    pub branch_timeline: bool,
+
     /// Cost per minute for CI/CD compute (in cents)
     #[serde(default)]
     pub cost_per_minute: Option<f64>,
@@ -67,6 +126,22 @@ pub struct GitLabConfig {
     /// Clear job cache before running
     #[serde(default)]
     pub clear_cache: bool,
+
+    /// Analyze the pipelines cached by a previous run without API requests
+    #[serde(default)]
+    pub offline: bool,
+
+    /// Analyze pipelines from a raw data file without API requests
+    #[serde(default)]
+    pub from_raw: Option<PathBuf>,
+
+    /// Export the analyzed pipelines with their jobs to a raw data file
+    #[serde(default)]
+    pub export_raw: Option<PathBuf>,
+
+    /// Retry and concurrency limits for GitLab API requests
+    #[serde(default)]
+    pub rate_limits: RateLimitConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -82,6 +157,12 @@ pub struct GitHubConfig {
     /// GitHub repository path (e.g., 'owner/repo')
     pub repo_path: Option<String>,
 
+    /// Only analyze runs of this workflow (file name, path, ID or name)
+    pub workflow: Option<String>,
+
+    /// Only analyze runs triggered by this event (e.g., push, `pull_request`, schedule)
+    pub event: Option<String>,
+
     /// Maximum number of workflow runs to fetch
     #[serde(default = "default_limit")]
     pub limit: usize,
@@ -99,9 +180,18 @@ pub struct GitHubConfig {
     #[serde(default = "default_min_type_percentage")]
     pub min_type_percentage: u8,
 
-    /// Cost per minute for CI/CD compute (in cents)
+    /// Cost per minute of a standard Linux runner (in cents)
     #[serde(default)]
     pub cost_per_minute: Option<f64>,
+
+    /// Billing multipliers by runner label, overriding the built-in GitHub rates
+    /// (e.g., `"gpu-runner" = 8.0`)
+    #[serde(default)]
+    pub runner_multipliers: HashMap<String, f64>,
+
+    /// Reconcile billable minutes against the workflow run `timing` endpoint
+    #[serde(default)]
+    pub reconcile_billing: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -134,6 +224,183 @@ pub enum OutputFormat {
     Html,
 }
 
+/// Compute cost rates by runner.
+///
+/// Jobs are priced by their runner tags first, then by runner type or machine type,
+/// and fall back to `default` (or `gitlab.cost-per-minute` when unset).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct CostsConfig {
+    /// Cost per minute for jobs that match no tag or runner type
+    pub default: Option<f64>,
+
+    /// Cost per minute keyed by runner tag (e.g., `gpu = 0.50`)
+    #[serde(default)]
+    pub tags: HashMap<String, f64>,
+
+    /// Cost per minute keyed by runner type (`instance`, `group`, `project`) or a
+    /// machine type contained in the runner description (e.g., `saas-linux-xlarge`)
+    #[serde(default)]
+    pub runner_types: HashMap<String, f64>,
+}
+
+/// Job cache retention.
+///
+/// Applied whenever the cache is saved and by `cilens cache prune`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct CacheConfig {
+    /// Keep entries of earlier runs with other filters (e.g., `--ref`, `--limit`)
+    /// instead of replacing them with the current run's pipelines
+    #[serde(default)]
+    pub keep: bool,
+
+    /// Drop entries no run has used for this many days
+    pub max_age_days: Option<u32>,
+
+    /// Keep at most this many entries per project, dropping the least recently used
+    pub max_entries: Option<usize>,
+}
+
+impl CacheConfig {
+    /// Converts the configured limits into a job cache retention policy.
+    pub fn retention(&self) -> CacheRetention {
+        CacheRetention {
+            keep: self.keep,
+            max_age: self
+                .max_age_days
+                .map(|days| chrono::Duration::days(i64::from(days))),
+            max_entries: self.max_entries,
+        }
+    }
+}
+
+/// HTTP client settings for the GitLab and GitHub APIs.
+///
+/// Command-line flags take precedence. Paths are relative to the working directory.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct HttpConfig {
+    /// Timeout for a whole request in seconds
+    pub timeout_secs: Option<u64>,
+
+    /// Timeout for establishing a connection in seconds
+    pub connect_timeout_secs: Option<u64>,
+
+    /// Proxy URL for all requests (defaults to the `HTTPS_PROXY`/`HTTP_PROXY` variables)
+    pub proxy: Option<String>,
+
+    /// PEM files with CA certificates to trust in addition to the bundled ones
+    #[serde(default)]
+    pub ca_certs: Vec<PathBuf>,
+
+    /// PEM file with the client certificate for mutual TLS (may include the key)
+    pub client_cert: Option<PathBuf>,
+
+    /// PEM file with the client private key, if not part of `client-cert`
+    pub client_key: Option<PathBuf>,
+
+    /// Skip TLS certificate verification
+    #[serde(default)]
+    pub insecure: bool,
+}
+
+impl HttpConfig {
+    /// Converts the configured values into HTTP client settings.
+    pub fn settings(&self) -> HttpSettings {
+        HttpSettings {
+            timeout: self.timeout_secs.map(std::time::Duration::from_secs),
+            connect_timeout: self
+                .connect_timeout_secs
+                .map(std::time::Duration::from_secs),
+            proxy: self.proxy.clone(),
+            ca_certs: self.ca_certs.clone(),
+            client_cert: self.client_cert.clone(),
+            client_key: self.client_key.clone(),
+            insecure: self.insecure,
+        }
+    }
+}
+
+/// Retry and concurrency limits for GitLab API requests.
+///
+/// Unset values keep the defaults: 10 retries starting at 1 second and doubling up to
+/// 60 seconds, and up to 500 concurrent requests.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct RateLimitConfig {
+    /// Retries of a request after network errors, rate limiting or server errors
+    pub max_retries: Option<u32>,
+
+    /// Delay before the first retry in seconds, doubled for every further retry
+    pub retry_delay_secs: Option<u64>,
+
+    /// Upper bound for the retry delay in seconds; GitLab may ask for waits of up to
+    /// five times as long, requests asked to wait longer fail
+    pub max_retry_delay_secs: Option<u64>,
+
+    /// Upper bound for concurrent requests; fewer are sent while GitLab is throttling
+    pub max_concurrent_requests: Option<usize>,
+}
+
+impl RateLimitConfig {
+    /// Converts the configured values into rate limits for the GitLab client.
+    pub fn rate_limits(&self) -> RateLimits {
+        let defaults = RateLimits::default();
+
+        RateLimits {
+            max_retries: self.max_retries.unwrap_or(defaults.max_retries),
+            retry_delay: self
+                .retry_delay_secs
+                .map_or(defaults.retry_delay, std::time::Duration::from_secs),
+            max_retry_delay: self
+                .max_retry_delay_secs
+                .map_or(defaults.max_retry_delay, std::time::Duration::from_secs),
+            max_concurrent_requests: self
+                .max_concurrent_requests
+                .unwrap_or(defaults.max_concurrent_requests),
+        }
+    }
+}
+
+/// Rule that assigns a label to matching pipeline types.
+///
+/// Patterns are globs (`release/*`, `v?.*`) unless prefixed with `regex:`
+/// (e.g., `regex:refs/merge-requests/\d+/head`), and both must match the whole value.
+/// A rule matches when every configured criterion matches; criteria left empty are ignored.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct LabelRule {
+    /// Label assigned to matching pipeline types (e.g., "MR pipeline", "Nightly")
+    pub label: String,
+
+    /// Ref patterns that every ref of the pipeline type must match
+    #[serde(default)]
+    pub refs: Vec<String>,
+
+    /// Pipeline sources (e.g., `schedule`, `merge_request_event`) that every pipeline must have
+    #[serde(default)]
+    pub sources: Vec<String>,
+
+    /// Job name patterns; at least one job of the pipeline type must match
+    #[serde(default)]
+    pub jobs: Vec<String>,
+}
+
+/// Named failure signature for classifying failed job logs.
+///
+/// The pattern is a regular expression matched against each log line after ANSI codes
+/// and timestamps are stripped (e.g., `pattern = "could not connect to server"`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SignatureRule {
+    /// Signature name reported for matching failures (e.g., "Database unavailable")
+    pub name: String,
+
+    /// Regular expression matched against log lines
+    pub pattern: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct AnalysisConfig {
@@ -159,6 +426,11 @@ impl Default for Config {
             github: GitHubConfig::default(),
             output: OutputConfig::default(),
             analysis: AnalysisConfig::default(),
+            costs: CostsConfig::default(),
+            cache: CacheConfig::default(),
+            http: HttpConfig::default(),
+            labels: Vec::new(),
+            signatures: Vec::new(),
         }
     }
 }
@@ -174,9 +446,22 @@ impl Default for GitLabConfig {
             since: None,
             until: None,
             min_type_percentage: default_min_type_percentage(),
+            similarity_threshold: None,
+            sampling: Sampling::default(),
+            environments: default_environments(),
+            test_reports: false,
+            failure_signatures: false,
+            live: false,
+            merge_requests: false,
+            canceled_pipelines: false,
+            branch_timeline: false,
             cost_per_minute: None,
             no_cache: false,
             clear_cache: false,
+            offline: false,
+            from_raw: None,
+            export_raw: None,
+            rate_limits: RateLimitConfig::default(),
         }
     }
 }
@@ -187,12 +472,16 @@ impl Default for GitHubConfig {
             token: None,
             base_url: default_github_base_url(),
             repo_path: None,
+            workflow: None,
+            event: None,
             limit: default_limit(),
             ref_: None,
             since: None,
             until: None,
             min_type_percentage: default_min_type_percentage(),
             cost_per_minute: None,
+            runner_multipliers: HashMap::new(),
+            reconcile_billing: false,
         }
     }
 }
@@ -235,6 +524,10 @@ fn default_min_type_percentage() -> u8 {
     1
 }
 
+fn default_environments() -> Vec<String> {
+    vec!["production".to_string()]
+}
+
 impl Config {
     /// Load configuration from a file.
     ///
@@ -377,6 +670,90 @@ history-db = "/tmp/cilens.db"
         assert!(matches!(config.output.format, OutputFormat::Csv));
     }
 
+    #[test]
+    fn test_load_costs_config() {
+        let mut temp_file = NamedTempFile::with_suffix(".toml").unwrap();
+        let toml_content = r#"
+[costs]
+default = 0.01
+
+[costs.tags]
+gpu = 0.50
+
+[costs.runner-types]
+saas-linux-xlarge = 0.04
+"#;
+        write!(temp_file, "{}", toml_content).unwrap();
+
+        let config = Config::load_from_path(temp_file.path()).unwrap();
+        assert_eq!(config.costs.default, Some(0.01));
+        assert_eq!(config.costs.tags.get("gpu"), Some(&0.50));
+        assert_eq!(config.costs.runner_types.get("saas-linux-xlarge"), Some(&0.04));
+    }
+
+    #[test]
+    fn test_load_label_rules() {
+        let mut temp_file = NamedTempFile::with_suffix(".toml").unwrap();
+        let toml_content = r#"
+[[labels]]
+label = "Nightly"
+sources = ["schedule"]
+
+[[labels]]
+label = "Release tag"
+refs = ['regex:^v\d+\.\d+\.\d+$']
+"#;
+        write!(temp_file, "{}", toml_content).unwrap();
+
+        let config = Config::load_from_path(temp_file.path()).unwrap();
+        assert_eq!(config.labels.len(), 2);
+        assert_eq!(config.labels[0].label, "Nightly");
+        assert_eq!(config.labels[0].sources, vec!["schedule"]);
+        assert_eq!(config.labels[1].refs, vec![r"regex:^v\d+\.\d+\.\d+$"]);
+    }
+
+    #[test]
+    fn test_load_failure_signatures() {
+        let mut temp_file = NamedTempFile::with_suffix(".toml").unwrap();
+        let toml_content = r#"
+[gitlab]
+failure-signatures = true
+
+[[signatures]]
+name = "Database unavailable"
+pattern = 'could not connect to server: .*5432'
+"#;
+        write!(temp_file, "{}", toml_content).unwrap();
+
+        let config = Config::load_from_path(temp_file.path()).unwrap();
+        assert!(config.gitlab.failure_signatures);
+        assert_eq!(config.signatures.len(), 1);
+        assert_eq!(config.signatures[0].name, "Database unavailable");
+        assert_eq!(config.signatures[0].pattern, "could not connect to server: .*5432");
+    }
+
+    #[test]
+    fn test_load_rate_limits() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        let toml_content = r#"
+[gitlab.rate-limits]
+max-retries = 5
+retry-delay-secs = 2
+max-concurrent-requests = 20
+"#;
+        write!(temp_file, "{}", toml_content).unwrap();
+
+        let config = Config::load_from_path(temp_file.path()).unwrap();
+        let rate_limits = config.gitlab.rate_limits.rate_limits();
+        assert_eq!(rate_limits.max_retries, 5);
+        assert_eq!(rate_limits.retry_delay, std::time::Duration::from_secs(2));
+        assert_eq!(
+            rate_limits.max_retry_delay,
+            RateLimits::default().max_retry_delay
+        );
+        assert_eq!(rate_limits.max_concurrent_requests, 20);
+    }
+
     #[test]
     fn test_load_nonexistent_config() {
         let config = Config::load(Some(Path::new("nonexistent.toml"))).unwrap();
@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Top-level CI/CD insights for a project.
 ///
@@ -18,6 +19,231 @@ pub struct CIInsights {
     pub total_pipeline_types: usize,
     /// Detailed metrics for each pipeline type
     pub pipeline_types: Vec<PipelineType>,
+    /// How pipelines were sampled within the fetch limit (GitLab only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<SamplingSummary>,
+    /// How pipelines were clustered into types (GitLab only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clustering: Option<ClusteringSummary>,
+    /// Pipelines of types below `min_type_percentage`, excluded from `pipeline_types`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excluded: Option<ExcludedPipelines>,
+    /// Merge request lead-time metrics (GitLab only, when merge request pipelines exist)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merge_requests: Option<MergeRequestMetrics>,
+    /// Broken-branch incidents and MTTR (GitLab only, when filtering by `--ref`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch_health: Option<BranchHealth>,
+    /// Deployment metrics per configured environment (GitLab only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deployments: Vec<DeploymentMetrics>,
+    /// Failed jobs clustered by log failure signature (GitLab only, with `--failure-signatures`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failure_signatures: Vec<FailureSignature>,
+    /// Compute spent on canceled pipelines (GitLab only, when canceled pipelines were sampled)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canceled: Option<CanceledPipelines>,
+    /// Pipelines that are running or waiting to run right now (GitLab only, with `--live`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub live: Option<LivePipelines>,
+}
+
+/// Canceled pipelines, such as ones superseded by auto-cancel of redundant pipelines.
+///
+/// Every job execution in a canceled pipeline is wasted compute.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanceledPipelines {
+    /// Number of canceled pipelines analyzed
+    pub total_pipelines: usize,
+    /// Percentage of finished pipelines in the range that were canceled (of the analyzed
+    /// pipelines, sampling-weighted, when analyzing offline)
+    pub percentage: f64,
+    /// Runner minutes spent on jobs of canceled pipelines
+    pub runner_minutes: f64,
+    /// Cost of those minutes (when costs are configured)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+    /// URLs of the canceled pipelines that used the most runner minutes
+    pub links: Vec<String>,
+}
+
+/// Snapshot of the pipelines that are currently running or pending.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LivePipelines {
+    /// When the snapshot was taken
+    pub observed_at: DateTime<Utc>,
+    /// Number of running pipelines
+    pub running: usize,
+    /// Number of pipelines waiting for their first job to start
+    pub pending: usize,
+    /// Median time the pipelines waited before their first job started (seconds)
+    pub queued_p50: f64,
+    /// 95th percentile time the pipelines waited before their first job started (seconds)
+    pub queued_p95: f64,
+    /// Pipelines that waited longest, longest first
+    pub pipelines: Vec<LivePipeline>,
+}
+
+/// A pipeline that is currently running or pending.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LivePipeline {
+    /// Pipeline URL
+    pub url: String,
+    /// Git ref the pipeline runs on
+    #[serde(rename = "ref")]
+    pub ref_: String,
+    /// Trigger source (e.g., "push", "merge_request_event")
+    pub source: String,
+    /// Current status ("running" or "pending")
+    pub status: String,
+    /// When the pipeline was created
+    pub created_at: DateTime<Utc>,
+    /// Time from creation until the first job started, or until now if none has (seconds)
+    pub queued: f64,
+    /// Time since the first job started (seconds, 0 while pending)
+    pub running_for: f64,
+}
+
+/// Failed job executions whose logs share the same failure signature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailureSignature {
+    /// Name of the matching signature, or the normalized error line if none matched
+    pub signature: String,
+    /// Number of failed job executions with this signature
+    pub count: usize,
+    /// Names of the affected jobs, most affected first
+    pub jobs: Vec<String>,
+    /// Error line of one of the failures, as it appeared in the log
+    pub example_line: String,
+    /// URLs of example failed jobs
+    pub example_links: Vec<String>,
+}
+
+/// Delivery performance of one deployment environment, in the style of DORA metrics.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeploymentMetrics {
+    /// Environment name (e.g., "production")
+    pub environment: String,
+    /// Finished deployments, successful and failed
+    pub total_deployments: usize,
+    /// Successful deployments, including rollbacks
+    pub successful_deployments: usize,
+    /// Failed deployments
+    pub failed_deployments: usize,
+    /// Successful deployments of a commit that had already been deployed before
+    pub rollbacks: usize,
+    /// Successful deployments per day over the analyzed period
+    pub deployments_per_day: f64,
+    /// Start of the analyzed period: `--since`, or the oldest deployment fetched
+    pub period_start: DateTime<Utc>,
+    /// End of the analyzed period: `--until`, or when the insights were collected
+    pub period_end: DateTime<Utc>,
+    /// Percentage of changes followed by a rollback or a failed deployment
+    pub change_failure_rate: f64,
+    /// Median time from commit to successful deployment (seconds)
+    pub lead_time_p50: f64,
+    /// 95th percentile time from commit to successful deployment (seconds)
+    pub lead_time_p95: f64,
+}
+
+/// How long CI adds to getting a change merged, as P50/P95 across merged merge requests.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeRequestMetrics {
+    /// Merge requests with at least one analyzed pipeline
+    pub total_merge_requests: usize,
+    /// Merge requests that were merged
+    pub merged_merge_requests: usize,
+    /// Median number of pipelines run before merge
+    pub pipelines_until_merge_p50: f64,
+    /// 95th percentile number of pipelines run before merge
+    pub pipelines_until_merge_p95: f64,
+    /// Median total pipeline duration before merge (seconds)
+    pub ci_wait_p50: f64,
+    /// 95th percentile total pipeline duration before merge (seconds)
+    pub ci_wait_p95: f64,
+    /// Median time from the first pipeline to merge (seconds)
+    pub first_pipeline_to_merge_p50: f64,
+    /// 95th percentile time from the first pipeline to merge (seconds)
+    pub first_pipeline_to_merge_p95: f64,
+    /// Percentage of merge requests whose pipelines contained retried jobs
+    pub retry_rate: f64,
+}
+
+/// How often a branch was broken and how long it took to fix.
+///
+/// An incident starts with the first failed pipeline after a successful one and ends
+/// with the next successful pipeline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchHealth {
+    /// Git ref the pipelines ran on (e.g., "main")
+    #[serde(rename = "ref")]
+    pub ref_: String,
+    /// Number of incidents, including one still open
+    pub total_incidents: usize,
+    /// Median time to recovery across resolved incidents (seconds)
+    pub mttr_p50: f64,
+    /// 95th percentile time to recovery across resolved incidents (seconds)
+    pub mttr_p95: f64,
+    /// Percentage of the observed time the branch was red
+    pub red_time_percentage: f64,
+    /// Longest incidents, longest first
+    pub longest_incidents: Vec<BranchIncident>,
+}
+
+/// A period during which a branch's pipelines were failing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchIncident {
+    /// When the first failed pipeline finished
+    pub started_at: DateTime<Utc>,
+    /// When the fixing pipeline finished, `None` if the branch is still red
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// Time the branch was red (seconds), up to the last analyzed pipeline if still open
+    pub duration: f64,
+    /// Commit SHAs of the failed pipelines, oldest first
+    pub commits: Vec<String>,
+    /// First failed job of the first failed pipeline
+    pub culprit_job: Option<String>,
+    /// URL of the first failed pipeline
+    pub first_failed_pipeline: String,
+    /// URL of the pipeline that turned the branch green again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fixed_by_pipeline: Option<String>,
+}
+
+/// Pipelines whose types fell below `min_type_percentage`, aggregated into one group.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExcludedPipelines {
+    /// Number of pipeline types that were excluded
+    pub pipeline_types: usize,
+    /// Metrics aggregated over all excluded pipelines
+    pub metrics: TypeMetrics,
+}
+
+/// Sampling strategy used to choose the analyzed pipelines.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SamplingSummary {
+    /// Sampling strategy ("balanced", "chronological" or "stratified")
+    pub strategy: String,
+    /// Analyzed pipelines per status
+    pub sampled: BTreeMap<String, usize>,
+    /// Pipelines per status in the queried range that rates are weighted by (stratified only)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub population: BTreeMap<String, usize>,
+}
+
+/// Clustering mode used to group pipelines into types, and its effect on coverage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusteringSummary {
+    /// Clustering mode ("exact" or "similarity")
+    pub mode: String,
+    /// Minimum Jaccard similarity for merging job sets (similarity mode only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similarity_threshold: Option<f64>,
+    /// Pipelines dropped by `min_type_percentage` when clustering on exact job sets
+    pub exact_dropped_pipelines: usize,
+    /// Pipelines dropped by `min_type_percentage` with similarity clustering (similarity mode only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similarity_dropped_pipelines: Option<usize>,
 }
 
 /// A job that must complete before the current job can start.
@@ -47,6 +273,56 @@ pub struct JobCountWithLinks {
     pub links: Vec<String>,
 }
 
+/// Compute cost split by whether the runner time produced a usable result.
+///
+/// Built by summing the actual duration of every execution, so retries and failed
+/// pipelines are included rather than extrapolated from a median.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostBreakdown {
+    /// Cost of executions that contributed to successful pipelines
+    pub useful: f64,
+    /// Cost of executions that were thrown away by retrying a flaky job
+    pub flaky_retries: f64,
+    /// Cost of executions in pipelines that ultimately failed
+    pub failed_pipelines: f64,
+    /// Cost of executions in pipelines that were canceled (GitLab only)
+    #[serde(default)]
+    pub canceled_pipelines: f64,
+}
+
+impl CostBreakdown {
+    /// Total cost across all buckets.
+    pub fn total(&self) -> f64 {
+        self.useful + self.wasted()
+    }
+
+    /// Cost that did not contribute to a successful pipeline.
+    pub fn wasted(&self) -> f64 {
+        self.flaky_retries + self.failed_pipelines + self.canceled_pipelines
+    }
+}
+
+impl std::ops::AddAssign for CostBreakdown {
+    fn add_assign(&mut self, other: Self) {
+        self.useful += other.useful;
+        self.flaky_retries += other.flaky_retries;
+        self.failed_pipelines += other.failed_pipelines;
+        self.canceled_pipelines += other.canceled_pipelines;
+    }
+}
+
+/// Runner time and cost attributed to one cost class.
+///
+/// A cost class is the runner tag, runner type or default rate that priced an execution
+/// (e.g., "tag:gpu", "runner:saas-linux-xlarge", "default").
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostClassTotal {
+    /// Runner minutes billed to this class
+    pub minutes: f64,
+    /// Cost of those minutes
+    pub cost: f64,
+}
+
 /// Comprehensive metrics for a specific job across multiple pipeline executions.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JobMetrics {
@@ -70,7 +346,8 @@ pub struct JobMetrics {
     pub predecessors: Vec<PredecessorJob>,
     /// Percentage of executions that were flaky retries (0.0 if never retried)
     pub flakiness_rate: f64,
-    /// Flaky retry executions with clickable URLs
+    /// Flaky retry executions with clickable URLs; on GitLab this includes failures that
+    /// passed in another pipeline for the same commit, linked by both pipeline URLs
     pub flaky_retries: JobCountWithLinks,
     /// Failed executions (stayed failed) with clickable URLs
     pub failed_executions: JobCountWithLinks,
@@ -78,10 +355,43 @@ pub struct JobMetrics {
     pub failure_rate: f64,
     /// Total executions across all pipelines (includes retries and failures)
     pub total_executions: usize,
-    /// Estimated cost per execution (based on duration and cost rate)
+    /// Average cost per execution (total cost divided by executions)
     pub cost_per_execution: Option<f64>,
-    /// Total estimated cost across all executions
+    /// Total cost across all executions, from their actual durations
     pub total_cost: Option<f64>,
+    /// Total cost split into useful and wasted compute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_breakdown: Option<CostBreakdown>,
+    /// Runner minutes and cost per cost class
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub cost_by_class: BTreeMap<String, CostClassTotal>,
+    /// Total billable minutes across all executions (GitHub Actions only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub billable_minutes: Option<u64>,
+    /// Test cases that failed or were flaky, from JUnit reports (GitLab `--test-reports`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tests: Vec<TestMetrics>,
+}
+
+/// Reliability and duration of a single test case across job executions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestMetrics {
+    /// Test name, prefixed with its class name when reported
+    pub name: String,
+    /// Executions that ran the test (skipped executions are ignored)
+    pub total_executions: usize,
+    /// Failures that passed on retry or in another pipeline for the same commit
+    pub flaky_executions: usize,
+    /// Percentage of executions that were flaky failures
+    pub flakiness_rate: f64,
+    /// Failures that did not pass on retry or rerun
+    pub failed_executions: usize,
+    /// Percentage of executions that failed and stayed failed
+    pub failure_rate: f64,
+    /// Median test duration (seconds)
+    pub duration_p50: f64,
+    /// 95th percentile test duration (seconds)
+    pub duration_p95: f64,
 }
 
 /// A group of pipelines with identical job signatures.
@@ -91,12 +401,21 @@ pub struct PipelineType {
     pub id: String,
     /// Human-readable label (e.g., "Production Pipeline", "Development Pipeline")
     pub label: String,
+    /// Workflow name this type belongs to (GitHub Actions only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workflow: Option<String>,
     /// Unique CI stages found in this pipeline type
     pub stages: Vec<String>,
     /// Git refs (branches/tags) that triggered these pipelines
     pub ref_patterns: Vec<String>,
     /// Pipeline trigger sources (e.g., "push", "schedule")
     pub sources: Vec<String>,
+    /// Jobs that run in every pipeline of this type
+    #[serde(default)]
+    pub core_jobs: Vec<String>,
+    /// Jobs that run in only some pipelines of this type (similarity clustering)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub optional_jobs: Vec<String>,
     /// Aggregated metrics for this pipeline type
     pub metrics: TypeMetrics,
 }
@@ -112,7 +431,10 @@ pub struct TypeMetrics {
     pub successful_pipelines: PipelineCountWithLinks,
     /// Failed pipeline runs with clickable URLs
     pub failed_pipelines: PipelineCountWithLinks,
-    /// Percentage of successful pipeline runs
+    /// Canceled pipeline runs with clickable URLs (GitLab only)
+    #[serde(default)]
+    pub canceled_pipelines: PipelineCountWithLinks,
+    /// Percentage of successful pipeline runs among those that were not canceled
     pub success_rate: f64,
     /// Median pipeline duration (seconds)
     pub duration_p50: f64,
@@ -128,8 +450,43 @@ pub struct TypeMetrics {
     pub time_to_feedback_p99: f64,
     /// Per-job metrics, sorted by `time_to_feedback_p95` descending
     pub jobs: Vec<JobMetrics>,
-    /// Estimated cost per pipeline execution
+    /// Average cost per pipeline execution (total cost divided by pipelines)
     pub cost_per_pipeline: Option<f64>,
-    /// Total estimated cost across all pipelines
+    /// Total cost across all pipelines, from actual job durations
     pub total_cost: Option<f64>,
+    /// Total cost split into useful and wasted compute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_breakdown: Option<CostBreakdown>,
+    /// Runner minutes and cost per cost class, summed over all jobs
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub cost_by_class: BTreeMap<String, CostClassTotal>,
+    /// Total billable minutes across all pipelines (GitHub Actions only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub billable_minutes: Option<u64>,
+    /// Per-stage metrics in stage order (GitLab only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stages: Vec<StageMetrics>,
+}
+
+/// Metrics for one CI stage within a pipeline type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageMetrics {
+    /// Stage name
+    pub name: String,
+    /// Median wall-clock span from the stage's first job start to its last job finish (seconds)
+    pub span_p50: f64,
+    /// 95th percentile stage span (seconds)
+    pub span_p95: f64,
+    /// Share of total pipeline duration spent in this stage (percentage)
+    pub duration_share: f64,
+    /// Jobs that ran in this stage
+    pub jobs: Vec<String>,
+    /// Job with the highest P95 duration in this stage
+    pub slowest_job: Option<String>,
+    /// P95 duration of the slowest job (seconds)
+    pub slowest_job_duration_p95: f64,
+    /// Percentage of pipelines in which a job of this stage failed
+    pub failure_rate: f64,
+    /// Number of pipelines in which this stage ran
+    pub total_executions: usize,
 }
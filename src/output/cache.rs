@@ -0,0 +1,185 @@
+use chrono::{DateTime, Utc};
+use comfy_table::{Cell, Color as TableColor};
+
+use super::styling::{bright, cyan, dim};
+use super::tables::create_table;
+use crate::providers::{CacheStats, CacheVerification, PruneResult};
+
+/// Prints one row per project cache with its size, entry count and last hit rate.
+pub fn print_cache_list(stats: &[CacheStats], now: DateTime<Utc>) {
+    if stats.is_empty() {
+        println!("No cache files found");
+        return;
+    }
+
+    let mut table = create_table();
+    table.set_header(
+        [
+            "Project", "Instance", "Entries", "Size", "Last Run", "Hit Rate",
+        ]
+        .iter()
+        .map(|label| Cell::new(*label).fg(TableColor::Cyan))
+        .collect::<Vec<_>>(),
+    );
+
+    for project in stats {
+        let last_run = project.last_run.as_ref();
+        table.add_row(vec![
+            Cell::new(&project.project),
+            Cell::new(project.base_url.as_deref().unwrap_or("unknown")),
+            Cell::new(project.entries),
+            Cell::new(format_bytes(project.total_bytes())),
+            Cell::new(last_run.map_or_else(|| "-".to_string(), |run| format_age(run.at, now))),
+            Cell::new(format_hit_rate(last_run.and_then(|run| run.hit_rate()))),
+        ]);
+    }
+
+    println!("{table}");
+}
+
+/// Prints entry ages, disk usage per cache and the last run's hit rate of each project.
+pub fn print_cache_stats(stats: &[CacheStats], now: DateTime<Utc>) {
+    if stats.is_empty() {
+        println!("No cache files found");
+        return;
+    }
+
+    for project in stats {
+        println!(
+            "📦 {} {}",
+            bright(&project.project),
+            dim(project.base_url.as_deref().unwrap_or("unknown instance"))
+        );
+
+        let ages = match (project.newest_entry, project.oldest_entry) {
+            (Some(newest), Some(oldest)) => format!(
+                " (last used {} to {})",
+                format_age(newest, now),
+                format_age(oldest, now)
+            ),
+            _ => String::new(),
+        };
+        println!("   Entries:    {}{ages}", cyan(project.entries));
+        println!(
+            "   Disk usage: {} (jobs {}, test reports {}, job logs {})",
+            cyan(format_bytes(project.total_bytes())),
+            format_bytes(project.job_cache_bytes),
+            format_bytes(project.test_cache_bytes),
+            format_bytes(project.trace_cache_bytes)
+        );
+        match &project.last_run {
+            Some(run) => println!(
+                "   Last run:   {}, hit rate {} ({} of {} pipelines from cache)",
+                format_age(run.at, now),
+                cyan(format_hit_rate(run.hit_rate())),
+                run.hits,
+                run.hits + run.misses
+            ),
+            None => println!("   Last run:   unknown"),
+        }
+        println!("   File:       {}", dim(project.cache_file.display()));
+        println!();
+    }
+}
+
+/// Prints the result of verifying one cache file.
+pub fn print_cache_verification(verification: &CacheVerification) {
+    let file_name = verification
+        .cache_file
+        .file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+
+    if let Some(error) = &verification.error {
+        println!("❌ {file_name}: unreadable ({error})");
+        return;
+    }
+
+    let owner = match (&verification.project, &verification.base_url) {
+        (Some(project), Some(base_url)) => format!("{project} on {base_url}"),
+        _ => "unknown project".to_string(),
+    };
+    let written_by = verification
+        .cilens_version
+        .as_ref()
+        .map_or_else(String::new, |version| format!(", cilens {version}"));
+    let status = if verification.is_ok() { "✅" } else { "❌" };
+
+    println!(
+        "{status} {file_name}: {} pipelines ({owner}, schema {}{written_by})",
+        verification.pipelines, verification.schema_version
+    );
+    for (id, reason) in &verification.corrupt {
+        println!("   corrupt entry {id}: {reason}");
+    }
+}
+
+/// Prints how many entries were pruned from each project cache.
+pub fn print_prune_results(results: &[PruneResult]) {
+    if results.is_empty() {
+        println!("No cache files found");
+        return;
+    }
+
+    for result in results {
+        println!(
+            "🧹 {}: dropped {} entries, {} left",
+            result.project, result.dropped, result.remaining
+        );
+    }
+}
+
+fn format_hit_rate(rate: Option<f64>) -> String {
+    rate.map_or_else(|| "-".to_string(), |rate| format!("{rate:.1}%"))
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+fn format_age(at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let age = now - at;
+
+    if age.num_days() > 0 {
+        format!("{}d ago", age.num_days())
+    } else if age.num_hours() > 0 {
+        format!("{}h ago", age.num_hours())
+    } else {
+        format!("{}m ago", age.num_minutes().max(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MB");
+    }
+
+    #[test]
+    fn test_format_age() {
+        let now = Utc::now();
+
+        assert_eq!(format_age(now - Duration::days(3), now), "3d ago");
+        assert_eq!(format_age(now - Duration::hours(5), now), "5h ago");
+        assert_eq!(format_age(now + Duration::minutes(1), now), "0m ago");
+    }
+}
@@ -40,14 +40,15 @@ fn export_json(insights: &CIInsights, pretty: bool, output: &mut dyn Write) -> R
 
 fn export_csv(insights: &CIInsights, output: &mut dyn Write) -> Result<()> {
     // Write CSV header
-    writeln!(output, "Pipeline Type,Percentage,Total Pipelines,Success Rate,Duration P50,Duration P95,Duration P99,Time to Feedback P50,Time to Feedback P95,Time to Feedback P99,Cost per Pipeline,Total Cost")?;
+    writeln!(output, "Pipeline Type,Percentage,Total Pipelines,Success Rate,Duration P50,Duration P95,Duration P99,Time to Feedback P50,Time to Feedback P95,Time to Feedback P99,Cost per Pipeline,Total Cost,Useful Cost,Flaky Retry Cost,Failed Pipeline Cost,Canceled Pipeline Cost")?;
 
     // Write pipeline type data
     for pipeline_type in &insights.pipeline_types {
         let metrics = &pipeline_type.metrics;
+        let cost = metrics.cost_breakdown.unwrap_or_default();
         writeln!(
             output,
-            "\"{}\",{:.1},{},{:.1},{:.1},{:.1},{:.1},{:.1},{:.1},{:.1},{:.2},{:.2}",
+            "\"{}\",{:.1},{},{:.1},{:.1},{:.1},{:.1},{:.1},{:.1},{:.1},{:.2},{:.2},{:.2},{:.2},{:.2},{:.2}",
             pipeline_type.label,
             metrics.percentage,
             metrics.total_pipelines,
@@ -59,20 +60,25 @@ fn export_csv(insights: &CIInsights, output: &mut dyn Write) -> Result<()> {
             metrics.time_to_feedback_p95,
             metrics.time_to_feedback_p99,
             metrics.cost_per_pipeline.unwrap_or(0.0),
-            metrics.total_cost.unwrap_or(0.0)
+            metrics.total_cost.unwrap_or(0.0),
+            cost.useful,
+            cost.flaky_retries,
+            cost.failed_pipelines,
+            cost.canceled_pipelines
         )?;
     }
 
     // Write job data header
     writeln!(output)?;
-    writeln!(output, "Job Name,Pipeline Type,Duration P50,Duration P95,Duration P99,Time to Feedback P50,Time to Feedback P95,Time to Feedback P99,Flakiness Rate,Failure Rate,Total Executions,Cost per Execution,Total Cost")?;
+    writeln!(output, "Job Name,Pipeline Type,Duration P50,Duration P95,Duration P99,Time to Feedback P50,Time to Feedback P95,Time to Feedback P99,Flakiness Rate,Failure Rate,Total Executions,Cost per Execution,Total Cost,Useful Cost,Flaky Retry Cost,Failed Pipeline Cost,Canceled Pipeline Cost")?;
 
     // Write job data
     for pipeline_type in &insights.pipeline_types {
         for job in &pipeline_type.metrics.jobs {
+            let cost = job.cost_breakdown.unwrap_or_default();
             writeln!(
                 output,
-                "\"{}\",\"{}\",{:.1},{:.1},{:.1},{:.1},{:.1},{:.1},{:.1},{:.1},{},{:.2},{:.2}",
+                "\"{}\",\"{}\",{:.1},{:.1},{:.1},{:.1},{:.1},{:.1},{:.1},{:.1},{},{:.2},{:.2},{:.2},{:.2},{:.2},{:.2}",
                 job.name,
                 pipeline_type.label,
                 job.duration_p50,
@@ -85,7 +91,11 @@ fn export_csv(insights: &CIInsights, output: &mut dyn Write) -> Result<()> {
                 job.failure_rate,
                 job.total_executions,
                 job.cost_per_execution.unwrap_or(0.0),
-                job.total_cost.unwrap_or(0.0)
+                job.total_cost.unwrap_or(0.0),
+                cost.useful,
+                cost.flaky_retries,
+                cost.failed_pipelines,
+                cost.canceled_pipelines
             )?;
         }
     }
@@ -219,6 +229,7 @@ mod tests {
     use super::*;
     use crate::insights::{CIInsights, JobMetrics, PipelineType, TypeMetrics};
     use chrono::Utc;
+    use std::collections::BTreeMap;
 
     #[test]
     fn test_export_json() {
@@ -292,6 +303,10 @@ mod tests {
             total_executions: 100,
             cost_per_execution: Some(0.05),
             total_cost: Some(5.0),
+            cost_breakdown: None,
+            cost_by_class: BTreeMap::new(),
+            billable_minutes: None,
+            tests: vec![],
         };
 
         let type_metrics = TypeMetrics {
@@ -299,6 +314,7 @@ mod tests {
             total_pipelines: 50,
             successful_pipelines: Default::default(),
             failed_pipelines: Default::default(),
+            canceled_pipelines: Default::default(),
             success_rate: 90.0,
             duration_p50: 300.0,
             duration_p95: 600.0,
@@ -309,13 +325,20 @@ mod tests {
             jobs: vec![job_metrics],
             cost_per_pipeline: Some(0.25),
             total_cost: Some(12.5),
+            cost_breakdown: None,
+            cost_by_class: BTreeMap::new(),
+            billable_minutes: None,
+            stages: vec![],
         };
 
         let pipeline_type = PipelineType {
             label: "Test Pipeline".to_string(),
+            workflow: None,
             stages: vec!["build".to_string(), "test".to_string()],
             ref_patterns: vec!["main".to_string()],
             sources: vec!["push".to_string()],
+            core_jobs: vec![],
+            optional_jobs: vec![],
             metrics: type_metrics,
         };
 
@@ -326,6 +349,15 @@ mod tests {
             total_pipelines: 50,
             total_pipeline_types: 1,
             pipeline_types: vec![pipeline_type],
+            sampling: None,
+            clustering: None,
+            excluded: None,
+            merge_requests: None,
+            branch_health: None,
+            deployments: vec![],
+            failure_signatures: vec![],
+            canceled: None,
+            live: None,
         }
     }
 }
\ No newline at end of file
@@ -1,9 +1,13 @@
+mod cache;
 mod exports;
 mod progress;
 mod styling;
 mod summary;
 mod tables;
 
+pub use cache::{
+    print_cache_list, print_cache_stats, print_cache_verification, print_prune_results,
+};
 pub use exports::export_insights;
 pub use progress::PhaseProgress;
 pub use styling::{dim, magenta_bold};
@@ -23,3 +27,8 @@ pub fn print_banner() {
         dim("CI/CD Insights Tool")
     );
 }
+
+/// Prints a warning to stderr, regardless of the log level.
+pub fn print_warning(message: &str) {
+    eprintln!("{} {}", styling::bright_yellow("⚠️  Warning:"), message);
+}
@@ -1,12 +1,15 @@
 use std::fmt::Write;
 
-use crate::insights::{CIInsights, JobMetrics};
+use crate::insights::{
+    CIInsights, CanceledPipelines, ClusteringSummary, CostBreakdown, ExcludedPipelines, JobMetrics,
+    SamplingSummary,
+};
 use comfy_table::{Cell, Color as TableColor};
 
 use super::styling::{bright, bright_green, bright_red, bright_yellow, cyan, dim};
 use super::tables::{
     color_coded_duration_cell, color_coded_failure_cell, color_coded_flakiness_cell,
-    color_coded_success_cell, create_table,
+    color_coded_success_cell, color_coded_waste_cell, create_table,
 };
 
 /// Prints a human-readable summary of CI/CD insights to stdout.
@@ -17,6 +20,14 @@ use super::tables::{
 /// - Top 10 Slowest Jobs: Jobs with highest P95 time-to-feedback
 /// - Top 10 Failing Jobs: Most unreliable jobs by failure rate
 /// - Top 10 Flaky Jobs: Most intermittent jobs by flakiness rate
+/// - Top 10 Flaky Tests: Most intermittent test cases (GitLab, with `--test-reports`)
+/// - Failure Signatures: Failed jobs clustered by log signature (GitLab, with `--failure-signatures`)
+/// - Stages: Span, share of pipeline duration and failure rate per stage (GitLab)
+/// - Merge Requests: Pipelines, CI wait and time until merge (GitLab)
+/// - Branch Health: Broken-branch incidents, MTTR and red time (GitLab, with `--ref`)
+/// - Deployments: Frequency, change failure rate and lead time per environment (GitLab)
+/// - Live Pipelines: Running and pending pipelines and their queue time (GitLab, with `--live`)
+/// - Cost Breakdown: Useful vs. wasted compute per pipeline type (when costs are configured)
 /// - Next Steps: Actionable recommendations
 ///
 /// Color coding:
@@ -49,6 +60,22 @@ where
     sorted
 }
 
+/// Lists the first few job names, summarizing the rest.
+fn format_job_names(jobs: &[String]) -> String {
+    const SHOWN: usize = 3;
+    let shown = jobs
+        .iter()
+        .take(SHOWN)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if jobs.len() > SHOWN {
+        format!("{shown} (+{} more)", jobs.len() - SHOWN)
+    } else {
+        shown
+    }
+}
+
 fn format_critical_path(job: &JobMetrics) -> String {
     if job.predecessors.is_empty() {
         "None".to_string()
@@ -61,6 +88,95 @@ fn format_critical_path(job: &JobMetrics) -> String {
     }
 }
 
+#[allow(clippy::cast_precision_loss)]
+fn waste_rate(cost: &CostBreakdown) -> f64 {
+    let total = cost.total();
+    if total > 0.0 {
+        (cost.wasted() / total) * 100.0
+    } else {
+        0.0
+    }
+}
+
+fn cost_row(id: &str, label: &str, cost: &CostBreakdown) -> Vec<Cell> {
+    vec![
+        Cell::new(id),
+        Cell::new(label),
+        Cell::new(format!("${:.2}", cost.total())),
+        Cell::new(format!("${:.2}", cost.useful)),
+        Cell::new(format!("${:.2}", cost.flaky_retries)),
+        Cell::new(format!("${:.2}", cost.failed_pipelines)),
+        Cell::new(format!("${:.2}", cost.canceled_pipelines)),
+        color_coded_waste_cell(waste_rate(cost)),
+    ]
+}
+
+fn format_clustering(clustering: &ClusteringSummary) -> String {
+    match (
+        clustering.similarity_threshold,
+        clustering.similarity_dropped_pipelines,
+    ) {
+        (Some(threshold), Some(dropped)) => format!(
+            "similarity ≥ {threshold:.2} ({dropped} pipelines dropped, {} with exact job sets)",
+            clustering.exact_dropped_pipelines
+        ),
+        _ => format!(
+            "exact job sets ({} pipelines dropped)",
+            clustering.exact_dropped_pipelines
+        ),
+    }
+}
+
+fn format_sampling(sampling: &SamplingSummary) -> String {
+    let counts: Vec<String> = sampling
+        .sampled
+        .iter()
+        .map(|(status, &sampled)| match sampling.population.get(status) {
+            Some(population) => format!("{status} {sampled} of {population}"),
+            None => format!("{sampled} {status}"),
+        })
+        .collect();
+    let note = match sampling.strategy.as_str() {
+        "balanced" => "; failure rates are biased unless about half of all pipelines fail",
+        "stratified" => "; rates weighted by status share",
+        _ => "",
+    };
+
+    format!("{} ({}{note})", sampling.strategy, counts.join(", "))
+}
+
+fn format_canceled(canceled: &CanceledPipelines) -> String {
+    let cost = canceled
+        .cost
+        .map(|cost| format!(", ${cost:.2} wasted"))
+        .unwrap_or_default();
+
+    format!(
+        "{} pipelines ({:.1}%), {:.0} runner minutes{cost}",
+        canceled.total_pipelines, canceled.percentage, canceled.runner_minutes
+    )
+}
+
+fn format_coverage(excluded: &ExcludedPipelines) -> String {
+    let coverage = 100.0 - excluded.metrics.percentage;
+    let text = format!(
+        "{coverage:.1}% of pipelines ({} pipelines in {} small types excluded)",
+        excluded.metrics.total_pipelines, excluded.pipeline_types
+    );
+
+    if coverage >= 90.0 {
+        bright_green(text).to_string()
+    } else if coverage >= 75.0 {
+        bright_yellow(text).to_string()
+    } else {
+        bright_red(text).to_string()
+    }
+}
+
+fn format_hours(seconds: f64) -> String {
+    format!("{:.1}h", seconds / 3600.0)
+}
+
 fn calculate_overall_success_rate(insights: &CIInsights) -> (f64, usize) {
     let total_successful: usize = insights
         .pipeline_types
@@ -109,7 +225,7 @@ fn render_summary(insights: &CIInsights) -> String {
     };
 
     output.push_str(&format!(
-        "  {} {}\n  {} {}\n  {} {}\n  {} {}\n  {} {}\n  {} {}\n\n",
+        "  {} {}\n  {} {}\n  {} {}\n  {} {}\n  {} {}\n  {} {}\n",
         dim("Project:"),
         cyan(&insights.project),
         dim("Pipelines analyzed:"),
@@ -124,6 +240,36 @@ fn render_summary(insights: &CIInsights) -> String {
         dim(insights.collected_at.format("%Y-%m-%d %H:%M UTC"))
     ));
 
+    if let Some(sampling) = &insights.sampling {
+        output.push_str(&format!(
+            "  {} {}\n",
+            dim("Sampling:"),
+            format_sampling(sampling)
+        ));
+    }
+    if let Some(clustering) = &insights.clustering {
+        output.push_str(&format!(
+            "  {} {}\n",
+            dim("Clustering:"),
+            format_clustering(clustering)
+        ));
+    }
+    if let Some(canceled) = &insights.canceled {
+        output.push_str(&format!(
+            "  {} {}\n",
+            dim("Canceled:"),
+            bright_yellow(format_canceled(canceled))
+        ));
+    }
+    if let Some(excluded) = &insights.excluded {
+        output.push_str(&format!(
+            "  {} {}\n",
+            dim("Coverage:"),
+            format_coverage(excluded)
+        ));
+    }
+    output.push('\n');
+
     if insights.pipeline_types.is_empty() {
         output.push_str(&format!("{}\n", bright_yellow("No pipeline data found.")));
         return output;
@@ -201,8 +347,65 @@ fn render_summary(insights: &CIInsights) -> String {
         types_table.add_row(row);
     }
 
+    if let Some(excluded) = &insights.excluded {
+        types_table.add_row(vec![
+            Cell::new("other").fg(TableColor::DarkGrey),
+            Cell::new(format!("Other ({} types)", excluded.pipeline_types))
+                .fg(TableColor::DarkGrey),
+            Cell::new(format!("{:.1}%", excluded.metrics.percentage)),
+            color_coded_success_cell(excluded.metrics.success_rate),
+            color_coded_duration_cell(excluded.metrics.duration_p95),
+            Cell::new("N/A"),
+            Cell::new(""),
+        ]);
+    }
+
     output.push_str(&format!("{types_table}\n\n"));
 
+    // Stages (GitLab only), for the pipeline types shown above
+    let staged_types: Vec<_> = insights
+        .pipeline_types
+        .iter()
+        .take(10)
+        .filter(|pt| !pt.metrics.stages.is_empty())
+        .collect();
+
+    if !staged_types.is_empty() {
+        add_section_header(&mut output, "🧱", "Stages");
+
+        let mut stages_table = create_table();
+        stages_table.set_header(create_cyan_header(&[
+            "ID",
+            "Stage",
+            "P50 Span",
+            "P95 Span",
+            "Share",
+            "Slowest Job",
+            "Failure Rate",
+        ]));
+
+        for pt in staged_types {
+            for stage in &pt.metrics.stages {
+                let slowest_job = stage.slowest_job.as_ref().map_or_else(
+                    || "N/A".to_string(),
+                    |job| format!("{job}\n{:.1}min", stage.slowest_job_duration_p95 / 60.0),
+                );
+
+                stages_table.add_row(vec![
+                    Cell::new(&pt.id),
+                    Cell::new(&stage.name),
+                    color_coded_duration_cell(stage.span_p50),
+                    color_coded_duration_cell(stage.span_p95),
+                    Cell::new(format!("{:.1}%", stage.duration_share)),
+                    Cell::new(slowest_job),
+                    color_coded_failure_cell(stage.failure_rate),
+                ]);
+            }
+        }
+
+        output.push_str(&format!("{stages_table}\n\n"));
+    }
+
     // Collect and deduplicate jobs by name (taking worst metrics across pipeline types)
     let mut jobs_by_name: std::collections::HashMap<String, &crate::insights::JobMetrics> =
         std::collections::HashMap::new();
@@ -317,6 +520,265 @@ fn render_summary(insights: &CIInsights) -> String {
 
     output.push_str(&format!("{flaky_table}\n\n"));
 
+    // Top 10 Flaky Tests (only when test reports were fetched)
+    let mut flaky_tests: Vec<_> = all_jobs
+        .iter()
+        .flat_map(|job| job.tests.iter().map(move |test| (*job, test)))
+        .filter(|(_, test)| test.flaky_executions > 0)
+        .collect();
+    if !flaky_tests.is_empty() {
+        add_section_header(&mut output, "🧪", "Top 10 Flaky Tests");
+        flaky_tests.sort_by(|a, b| {
+            b.1.flakiness_rate
+                .partial_cmp(&a.1.flakiness_rate)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        let mut flaky_test_table = create_table();
+        flaky_test_table.set_header(create_cyan_header(&[
+            "#",
+            "Test",
+            "Job Name",
+            "Flaky",
+            "Failed",
+            "P95 Duration",
+            "Pipeline Type ID",
+        ]));
+        for (idx, (job, test)) in flaky_tests.iter().take(10).enumerate() {
+            flaky_test_table.add_row(vec![
+                Cell::new(idx + 1),
+                Cell::new(&test.name),
+                Cell::new(&job.name),
+                color_coded_flakiness_cell(test.flakiness_rate),
+                color_coded_failure_cell(test.failure_rate),
+                Cell::new(format!("{:.1}s", test.duration_p95)),
+                Cell::new(&job.pipeline_type_id),
+            ]);
+        }
+
+        output.push_str(&format!("{flaky_test_table}\n\n"));
+    }
+
+    // Failure Signatures (only when failed job logs were mined)
+    if !insights.failure_signatures.is_empty() {
+        add_section_header(&mut output, "🔎", "Top 10 Failure Signatures");
+        let mut signature_table = create_table();
+        signature_table.set_header(create_cyan_header(&[
+            "#",
+            "Signature",
+            "Failures",
+            "Jobs",
+            "Example",
+        ]));
+        for (idx, signature) in insights.failure_signatures.iter().take(10).enumerate() {
+            signature_table.add_row(vec![
+                Cell::new(idx + 1),
+                Cell::new(&signature.signature),
+                Cell::new(signature.count),
+                Cell::new(format_job_names(&signature.jobs)),
+                Cell::new(
+                    signature
+                        .example_links
+                        .first()
+                        .map_or("N/A", String::as_str),
+                ),
+            ]);
+        }
+        output.push_str(&format!("{signature_table}\n\n"));
+    }
+
+    // Merge Requests (GitLab merge request pipelines only)
+    if let Some(mrs) = &insights.merge_requests {
+        add_section_header(&mut output, "🔀", "Merge Requests");
+        output.push_str(&format!(
+            "  {} {}\n  {} {}\n\n",
+            dim("Merge requests:"),
+            bright_yellow(format!(
+                "{} ({} merged)",
+                mrs.total_merge_requests, mrs.merged_merge_requests
+            )),
+            dim("Needed a retry:"),
+            bright_yellow(format!("{:.1}%", mrs.retry_rate)),
+        ));
+
+        if mrs.merged_merge_requests > 0 {
+            let mut mr_table = create_table();
+            mr_table.set_header(create_cyan_header(&["Until Merge", "P50", "P95"]));
+            mr_table.add_row(vec![
+                Cell::new("Pipelines"),
+                Cell::new(format!("{:.0}", mrs.pipelines_until_merge_p50)),
+                Cell::new(format!("{:.0}", mrs.pipelines_until_merge_p95)),
+            ]);
+            mr_table.add_row(vec![
+                Cell::new("CI wait"),
+                color_coded_duration_cell(mrs.ci_wait_p50),
+                color_coded_duration_cell(mrs.ci_wait_p95),
+            ]);
+            mr_table.add_row(vec![
+                Cell::new("First pipeline to merge"),
+                Cell::new(format_hours(mrs.first_pipeline_to_merge_p50)),
+                Cell::new(format_hours(mrs.first_pipeline_to_merge_p95)),
+            ]);
+            output.push_str(&format!("{mr_table}\n\n"));
+        }
+    }
+
+    // Branch Health (GitLab, only when filtering by ref)
+    if let Some(health) = &insights.branch_health {
+        add_section_header(&mut output, "🚦", &format!("Branch Health ({})", health.ref_));
+        let red_time = format!("{:.1}%", health.red_time_percentage);
+        let red_time = if health.red_time_percentage < 5.0 {
+            bright_green(red_time)
+        } else if health.red_time_percentage < 15.0 {
+            bright_yellow(red_time)
+        } else {
+            bright_red(red_time)
+        };
+        output.push_str(&format!(
+            "  {} {}\n  {} {} / {}\n  {} {}\n\n",
+            dim("Incidents:"),
+            bright_yellow(health.total_incidents),
+            dim("MTTR (P50 / P95):"),
+            bright_yellow(format_hours(health.mttr_p50)),
+            bright_yellow(format_hours(health.mttr_p95)),
+            dim("Time red:"),
+            red_time,
+        ));
+
+        if !health.longest_incidents.is_empty() {
+            let mut incident_table = create_table();
+            incident_table.set_header(create_cyan_header(&[
+                "Started",
+                "Red For",
+                "Commits",
+                "Culprit Job",
+                "First Failed Pipeline",
+            ]));
+            for incident in &health.longest_incidents {
+                let red_for = format_hours(incident.duration);
+                incident_table.add_row(vec![
+                    Cell::new(incident.started_at.format("%Y-%m-%d %H:%M")),
+                    Cell::new(if incident.resolved_at.is_some() {
+                        red_for
+                    } else {
+                        format!("{red_for} (open)")
+                    }),
+                    Cell::new(incident.commits.len()),
+                    Cell::new(incident.culprit_job.as_deref().unwrap_or("N/A")),
+                    Cell::new(&incident.first_failed_pipeline),
+                ]);
+            }
+            output.push_str(&format!("{incident_table}\n\n"));
+        }
+    }
+
+    // Deployments (GitLab environments with finished deployments)
+    if !insights.deployments.is_empty() {
+        add_section_header(&mut output, "🚀", "Deployments");
+        let mut deployment_table = create_table();
+        deployment_table.set_header(create_cyan_header(&[
+            "Environment",
+            "Period",
+            "Deployments",
+            "Per Day",
+            "Failed",
+            "Rollbacks",
+            "Change Failure",
+            "Lead Time P50",
+            "Lead Time P95",
+        ]));
+        for deployments in &insights.deployments {
+            deployment_table.add_row(vec![
+                Cell::new(&deployments.environment),
+                Cell::new(format!(
+                    "{} to {}",
+                    deployments.period_start.format("%Y-%m-%d"),
+                    deployments.period_end.format("%Y-%m-%d")
+                )),
+                Cell::new(deployments.total_deployments),
+                Cell::new(format!("{:.2}", deployments.deployments_per_day)),
+                Cell::new(deployments.failed_deployments),
+                Cell::new(deployments.rollbacks),
+                color_coded_failure_cell(deployments.change_failure_rate),
+                Cell::new(format_hours(deployments.lead_time_p50)),
+                Cell::new(format_hours(deployments.lead_time_p95)),
+            ]);
+        }
+        output.push_str(&format!("{deployment_table}\n\n"));
+    }
+
+    // Live Pipelines (GitLab, with --live)
+    if let Some(live) = &insights.live {
+        add_section_header(&mut output, "⏳", "Live Pipelines");
+        output.push_str(&format!(
+            "  {} {}\n  {} {}\n  {} {} / {}\n\n",
+            dim("Running:"),
+            bright_yellow(live.running),
+            dim("Pending:"),
+            bright_yellow(live.pending),
+            dim("Queued (P50 / P95):"),
+            bright_yellow(format!("{:.1}min", live.queued_p50 / 60.0)),
+            bright_yellow(format!("{:.1}min", live.queued_p95 / 60.0)),
+        ));
+
+        if !live.pipelines.is_empty() {
+            let mut live_table = create_table();
+            live_table.set_header(create_cyan_header(&[
+                "Pipeline", "Ref", "Status", "Queued", "Running",
+            ]));
+            for pipeline in &live.pipelines {
+                live_table.add_row(vec![
+                    Cell::new(&pipeline.url),
+                    Cell::new(&pipeline.ref_),
+                    Cell::new(&pipeline.status),
+                    color_coded_duration_cell(pipeline.queued),
+                    Cell::new(format!("{:.1}min", pipeline.running_for / 60.0)),
+                ]);
+            }
+            output.push_str(&format!("{live_table}\n\n"));
+        }
+    }
+
+    // Cost Breakdown (only when a cost rate was configured)
+    let costed_types: Vec<_> = insights
+        .pipeline_types
+        .iter()
+        .filter_map(|pt| pt.metrics.cost_breakdown.map(|cost| (pt, cost)))
+        .collect();
+
+    if !costed_types.is_empty() {
+        add_section_header(&mut output, "💰", "Cost Breakdown");
+
+        let mut cost_table = create_table();
+        cost_table.set_header(create_cyan_header(&[
+            "ID",
+            "Type",
+            "Total",
+            "Useful",
+            "Flaky Retries",
+            "Failed Pipelines",
+            "Canceled",
+            "Wasted",
+        ]));
+
+        let mut overall = CostBreakdown::default();
+        for (pt, cost) in &costed_types {
+            overall += *cost;
+            cost_table.add_row(cost_row(&pt.id, &pt.label, cost));
+        }
+        if let Some(cost) = insights
+            .excluded
+            .as_ref()
+            .and_then(|excluded| excluded.metrics.cost_breakdown)
+        {
+            overall += cost;
+            cost_table.add_row(cost_row("other", "Other", &cost));
+        }
+        cost_table.add_row(cost_row("", "All types", &overall));
+
+        output.push_str(&format!("{cost_table}\n\n"));
+    }
+
     // Next Steps
     add_section_header(&mut output, "💡", "Next Steps");
     output.push_str(&format!(
@@ -338,10 +800,13 @@ fn render_summary(insights: &CIInsights) -> String {
 mod tests {
     use super::*;
     use crate::insights::{
-        CIInsights, JobCountWithLinks, JobMetrics, PipelineCountWithLinks, PipelineType,
+        BranchHealth, BranchIncident, CIInsights, DeploymentMetrics, FailureSignature,
+        JobCountWithLinks, JobMetrics, LivePipeline, LivePipelines, MergeRequestMetrics,
+        PipelineCountWithLinks, PipelineType, SamplingSummary, StageMetrics, TestMetrics,
         TypeMetrics,
     };
     use chrono::Utc;
+    use std::collections::BTreeMap;
 
     fn create_test_job(
         name: &str,
@@ -364,6 +829,12 @@ mod tests {
             failed_executions: JobCountWithLinks::default(),
             failure_rate,
             total_executions: 100,
+            cost_per_execution: None,
+            total_cost: None,
+            cost_breakdown: None,
+            cost_by_class: BTreeMap::new(),
+            billable_minutes: None,
+            tests: vec![],
         }
     }
 
@@ -378,9 +849,12 @@ mod tests {
         PipelineType {
             id: "test-type".to_string(),
             label: label.to_string(),
+            workflow: None,
             stages: vec!["test".to_string()],
             ref_patterns: vec!["main".to_string()],
             sources: vec!["push".to_string()],
+            core_jobs: vec![],
+            optional_jobs: vec![],
             metrics: TypeMetrics {
                 percentage,
                 total_pipelines: 100,
@@ -389,6 +863,7 @@ mod tests {
                     links: vec![example_url.to_string()],
                 },
                 failed_pipelines: PipelineCountWithLinks::default(),
+                canceled_pipelines: PipelineCountWithLinks::default(),
                 success_rate,
                 duration_p50: duration_p95 * 0.5,
                 duration_p95,
@@ -397,6 +872,12 @@ mod tests {
                 time_to_feedback_p95: 200.0,
                 time_to_feedback_p99: 300.0,
                 jobs,
+                cost_per_pipeline: None,
+                total_cost: None,
+                cost_breakdown: None,
+                cost_by_class: BTreeMap::new(),
+                billable_minutes: None,
+                stages: vec![],
             },
         }
     }
@@ -410,6 +891,15 @@ mod tests {
             total_pipelines: 0,
             total_pipeline_types: 0,
             pipeline_types: vec![],
+            sampling: None,
+            clustering: None,
+            excluded: None,
+            merge_requests: None,
+            branch_health: None,
+            deployments: vec![],
+            failure_signatures: vec![],
+            canceled: None,
+            live: None,
         };
 
         let output = render_summary(&insights);
@@ -442,6 +932,15 @@ mod tests {
             total_pipelines: 100,
             total_pipeline_types: 1,
             pipeline_types: vec![pipeline_type],
+            sampling: None,
+            clustering: None,
+            excluded: None,
+            merge_requests: None,
+            branch_health: None,
+            deployments: vec![],
+            failure_signatures: vec![],
+            canceled: None,
+            live: None,
         };
 
         let output = render_summary(&insights);
@@ -500,6 +999,15 @@ mod tests {
             total_pipelines: 200,
             total_pipeline_types: 2,
             pipeline_types: vec![pt1, pt2],
+            sampling: None,
+            clustering: None,
+            excluded: None,
+            merge_requests: None,
+            branch_health: None,
+            deployments: vec![],
+            failure_signatures: vec![],
+            canceled: None,
+            live: None,
         };
 
         let output = render_summary(&insights);
@@ -530,6 +1038,15 @@ mod tests {
             total_pipelines: 100,
             total_pipeline_types: 1,
             pipeline_types: vec![pipeline_type],
+            sampling: None,
+            clustering: None,
+            excluded: None,
+            merge_requests: None,
+            branch_health: None,
+            deployments: vec![],
+            failure_signatures: vec![],
+            canceled: None,
+            live: None,
         };
 
         let output = render_summary(&insights);
@@ -567,6 +1084,15 @@ mod tests {
             total_pipelines: 100,
             total_pipeline_types: 1,
             pipeline_types: vec![pipeline_type],
+            sampling: None,
+            clustering: None,
+            excluded: None,
+            merge_requests: None,
+            branch_health: None,
+            deployments: vec![],
+            failure_signatures: vec![],
+            canceled: None,
+            live: None,
         };
 
         let output = render_summary(&insights);
@@ -594,6 +1120,15 @@ mod tests {
             total_pipelines: 100,
             total_pipeline_types: 1,
             pipeline_types: vec![pipeline_type],
+            sampling: None,
+            clustering: None,
+            excluded: None,
+            merge_requests: None,
+            branch_health: None,
+            deployments: vec![],
+            failure_signatures: vec![],
+            canceled: None,
+            live: None,
         };
 
         let output = render_summary(&insights);
@@ -628,6 +1163,15 @@ mod tests {
             total_pipelines: 100,
             total_pipeline_types: 1,
             pipeline_types: vec![pipeline_type],
+            sampling: None,
+            clustering: None,
+            excluded: None,
+            merge_requests: None,
+            branch_health: None,
+            deployments: vec![],
+            failure_signatures: vec![],
+            canceled: None,
+            live: None,
         };
 
         let output = render_summary(&insights);
@@ -662,6 +1206,15 @@ mod tests {
             total_pipelines: 100,
             total_pipeline_types: 1,
             pipeline_types: vec![pipeline_type],
+            sampling: None,
+            clustering: None,
+            excluded: None,
+            merge_requests: None,
+            branch_health: None,
+            deployments: vec![],
+            failure_signatures: vec![],
+            canceled: None,
+            live: None,
         };
 
         let output = render_summary(&insights);
@@ -672,4 +1225,513 @@ mod tests {
         // Flaky jobs section should show top 10
         assert!(output.contains("Top 10 Flaky Jobs"));
     }
+
+    #[test]
+    fn test_render_summary_omits_cost_breakdown_without_costs() {
+        let pipeline_type = create_test_pipeline_type(
+            "Test",
+            100.0,
+            100.0,
+            500.0,
+            vec![create_test_job("job", 100.0, 0.0, 0.0)],
+            "https://example.com",
+        );
+
+        let insights = CIInsights {
+            provider: "GitLab".to_string(),
+            project: "test/project".to_string(),
+            collected_at: Utc::now(),
+            total_pipelines: 100,
+            total_pipeline_types: 1,
+            pipeline_types: vec![pipeline_type],
+            sampling: None,
+            clustering: None,
+            excluded: None,
+            merge_requests: None,
+            branch_health: None,
+            deployments: vec![],
+            failure_signatures: vec![],
+            canceled: None,
+            live: None,
+        };
+
+        let output = render_summary(&insights);
+
+        assert!(!output.contains("Cost Breakdown"));
+    }
+
+    #[test]
+    fn test_render_summary_shows_cost_breakdown() {
+        let mut pipeline_type = create_test_pipeline_type(
+            "Test",
+            100.0,
+            100.0,
+            500.0,
+            vec![create_test_job("job", 100.0, 0.0, 0.0)],
+            "https://example.com",
+        );
+        pipeline_type.metrics.cost_breakdown = Some(CostBreakdown {
+            useful: 6.0,
+            flaky_retries: 1.5,
+            failed_pipelines: 2.5,
+            canceled_pipelines: 0.0,
+        });
+
+        let insights = CIInsights {
+            provider: "GitLab".to_string(),
+            project: "test/project".to_string(),
+            collected_at: Utc::now(),
+            total_pipelines: 100,
+            total_pipeline_types: 1,
+            pipeline_types: vec![pipeline_type],
+            sampling: None,
+            clustering: None,
+            excluded: None,
+            merge_requests: None,
+            branch_health: None,
+            deployments: vec![],
+            failure_signatures: vec![],
+            canceled: None,
+            live: None,
+        };
+
+        let output = render_summary(&insights);
+
+        assert!(output.contains("Cost Breakdown"));
+        assert!(output.contains("$10.00"));
+        assert!(output.contains("$1.50"));
+        assert!(output.contains("$2.50"));
+        assert!(output.contains("40.0%"));
+        assert!(output.contains("All types"));
+    }
+
+    #[test]
+    fn test_render_summary_shows_excluded_pipelines() {
+        let pipeline_type = create_test_pipeline_type(
+            "Test",
+            80.0,
+            100.0,
+            500.0,
+            vec![create_test_job("job", 100.0, 0.0, 0.0)],
+            "https://example.com",
+        );
+        let mut other = create_test_pipeline_type("Other", 20.0, 50.0, 300.0, vec![], "");
+        other.metrics.total_pipelines = 25;
+
+        let insights = CIInsights {
+            provider: "GitLab".to_string(),
+            project: "test/project".to_string(),
+            collected_at: Utc::now(),
+            total_pipelines: 125,
+            total_pipeline_types: 1,
+            pipeline_types: vec![pipeline_type],
+            sampling: None,
+            clustering: None,
+            excluded: Some(ExcludedPipelines {
+                pipeline_types: 7,
+                metrics: other.metrics,
+            }),
+            merge_requests: None,
+            branch_health: None,
+            deployments: vec![],
+            failure_signatures: vec![],
+            canceled: None,
+            live: None,
+        };
+
+        let output = render_summary(&insights);
+
+        assert!(output.contains("Coverage:"));
+        assert!(output.contains("80.0% of pipelines (25 pipelines in 7 small types excluded)"));
+        assert!(output.contains("Other (7 types)"));
+    }
+
+    #[test]
+    fn test_render_summary_shows_stages() {
+        let mut pipeline_type = create_test_pipeline_type(
+            "Test",
+            100.0,
+            100.0,
+            500.0,
+            vec![create_test_job("sast", 100.0, 0.0, 0.0)],
+            "https://example.com",
+        );
+        pipeline_type.metrics.stages = vec![StageMetrics {
+            name: "scan".to_string(),
+            span_p50: 120.0,
+            span_p95: 240.0,
+            duration_share: 35.0,
+            jobs: vec!["sast".to_string()],
+            slowest_job: Some("sast".to_string()),
+            slowest_job_duration_p95: 240.0,
+            failure_rate: 12.5,
+            total_executions: 8,
+        }];
+
+        let insights = CIInsights {
+            provider: "GitLab".to_string(),
+            project: "test/project".to_string(),
+            collected_at: Utc::now(),
+            total_pipelines: 100,
+            total_pipeline_types: 1,
+            pipeline_types: vec![pipeline_type],
+            sampling: None,
+            clustering: None,
+            excluded: None,
+            merge_requests: None,
+            branch_health: None,
+            deployments: vec![],
+            failure_signatures: vec![],
+            canceled: None,
+            live: None,
+        };
+
+        let output = render_summary(&insights);
+
+        assert!(output.contains("Stages"));
+        assert!(output.contains("scan"));
+        assert!(output.contains("35.0%"));
+        assert!(output.contains("12.5%"));
+    }
+
+    #[test]
+    fn test_render_summary_shows_merge_requests() {
+        let pipeline_type = create_test_pipeline_type(
+            "Test",
+            100.0,
+            100.0,
+            500.0,
+            vec![create_test_job("job", 100.0, 0.0, 0.0)],
+            "https://example.com",
+        );
+
+        let insights = CIInsights {
+            provider: "GitLab".to_string(),
+            project: "test/project".to_string(),
+            collected_at: Utc::now(),
+            total_pipelines: 100,
+            total_pipeline_types: 1,
+            pipeline_types: vec![pipeline_type],
+            sampling: None,
+            clustering: None,
+            excluded: None,
+            merge_requests: Some(MergeRequestMetrics {
+                total_merge_requests: 12,
+                merged_merge_requests: 10,
+                pipelines_until_merge_p50: 2.0,
+                pipelines_until_merge_p95: 6.0,
+                ci_wait_p50: 900.0,
+                ci_wait_p95: 3600.0,
+                first_pipeline_to_merge_p50: 7200.0,
+                first_pipeline_to_merge_p95: 86_400.0,
+                retry_rate: 25.0,
+            }),
+            branch_health: None,
+            deployments: vec![],
+            failure_signatures: vec![],
+            canceled: None,
+            live: None,
+        };
+
+        let output = render_summary(&insights);
+
+        assert!(output.contains("Merge Requests"));
+        assert!(output.contains("12 (10 merged)"));
+        assert!(output.contains("25.0%"));
+        assert!(output.contains("2.0h"));
+        assert!(output.contains("24.0h"));
+    }
+
+    #[test]
+    fn test_render_summary_shows_branch_health() {
+        let pipeline_type = create_test_pipeline_type(
+            "Test",
+            100.0,
+            100.0,
+            500.0,
+            vec![create_test_job("job", 100.0, 0.0, 0.0)],
+            "https://example.com",
+        );
+
+        let insights = CIInsights {
+            provider: "GitLab".to_string(),
+            project: "test/project".to_string(),
+            collected_at: Utc::now(),
+            total_pipelines: 10,
+            total_pipeline_types: 1,
+            pipeline_types: vec![pipeline_type],
+            sampling: None,
+            clustering: None,
+            excluded: None,
+            merge_requests: None,
+            branch_health: Some(BranchHealth {
+                ref_: "main".to_string(),
+                total_incidents: 3,
+                mttr_p50: 1800.0,
+                mttr_p95: 7200.0,
+                red_time_percentage: 12.5,
+                longest_incidents: vec![BranchIncident {
+                    started_at: Utc::now(),
+                    resolved_at: None,
+                    duration: 5400.0,
+                    commits: vec!["abc".to_string(), "def".to_string()],
+                    culprit_job: Some("unit-tests".to_string()),
+                    first_failed_pipeline: "https://gitlab.com/org/repo/-/pipelines/2"
+                        .to_string(),
+                    fixed_by_pipeline: None,
+                }],
+            }),
+            deployments: vec![],
+            failure_signatures: vec![],
+            canceled: None,
+            live: None,
+        };
+
+        let output = render_summary(&insights);
+
+        assert!(output.contains("Branch Health (main)"));
+        assert!(output.contains("12.5%"));
+        assert!(output.contains("1.5h (open)"));
+        assert!(output.contains("unit-tests"));
+        assert!(output.contains("https://gitlab.com/org/repo/-/pipelines/2"));
+    }
+
+    #[test]
+    fn test_render_summary_shows_deployments() {
+        let pipeline_type = create_test_pipeline_type(
+            "Test",
+            100.0,
+            100.0,
+            500.0,
+            vec![create_test_job("job", 100.0, 0.0, 0.0)],
+            "https://example.com",
+        );
+
+        let insights = CIInsights {
+            provider: "GitLab".to_string(),
+            project: "test/project".to_string(),
+            collected_at: Utc::now(),
+            total_pipelines: 10,
+            total_pipeline_types: 1,
+            pipeline_types: vec![pipeline_type],
+            sampling: None,
+            clustering: None,
+            excluded: None,
+            merge_requests: None,
+            branch_health: None,
+            deployments: vec![DeploymentMetrics {
+                environment: "production".to_string(),
+                total_deployments: 30,
+                successful_deployments: 28,
+                failed_deployments: 2,
+                rollbacks: 1,
+                deployments_per_day: 0.93,
+                period_start: Utc::now() - chrono::Duration::days(30),
+                period_end: Utc::now(),
+                change_failure_rate: 10.7,
+                lead_time_p50: 10_800.0,
+                lead_time_p95: 172_800.0,
+            }],
+            failure_signatures: vec![],
+            canceled: None,
+            live: None,
+        };
+
+        let output = render_summary(&insights);
+
+        assert!(output.contains("Deployments"));
+        assert!(output.contains("production"));
+        assert!(output.contains("0.93"));
+        assert!(output.contains("3.0h"));
+        assert!(output.contains("48.0h"));
+    }
+
+    #[test]
+    fn test_render_summary_shows_flaky_tests() {
+        let mut job = create_test_job("rspec", 100.0, 0.0, 5.0);
+        job.tests = vec![TestMetrics {
+            name: "User#valid? rejects blank emails".to_string(),
+            total_executions: 40,
+            flaky_executions: 4,
+            flakiness_rate: 10.0,
+            failed_executions: 0,
+            failure_rate: 0.0,
+            duration_p50: 0.4,
+            duration_p95: 2.5,
+        }];
+        let pipeline_type = create_test_pipeline_type(
+            "Test",
+            100.0,
+            100.0,
+            500.0,
+            vec![job],
+            "https://example.com",
+        );
+
+        let insights = CIInsights {
+            provider: "GitLab".to_string(),
+            project: "test/project".to_string(),
+            collected_at: Utc::now(),
+            total_pipelines: 10,
+            total_pipeline_types: 1,
+            pipeline_types: vec![pipeline_type],
+            sampling: None,
+            clustering: None,
+            excluded: None,
+            merge_requests: None,
+            branch_health: None,
+            deployments: vec![],
+            failure_signatures: vec![],
+            canceled: None,
+            live: None,
+        };
+
+        let output = render_summary(&insights);
+
+        assert!(output.contains("Top 10 Flaky Tests"));
+        assert!(output.contains("User#valid? rejects blank emails"));
+        assert!(output.contains("2.5s"));
+    }
+
+    #[test]
+    fn test_render_summary_shows_failure_signatures() {
+        let insights = CIInsights {
+            provider: "GitLab".to_string(),
+            project: "test/project".to_string(),
+            collected_at: Utc::now(),
+            total_pipelines: 10,
+            total_pipeline_types: 1,
+            pipeline_types: vec![create_test_pipeline_type(
+                "Test",
+                100.0,
+                80.0,
+                500.0,
+                vec![create_test_job("e2e", 100.0, 20.0, 0.0)],
+                "https://example.com",
+            )],
+            sampling: None,
+            clustering: None,
+            excluded: None,
+            merge_requests: None,
+            branch_health: None,
+            deployments: vec![],
+            failure_signatures: vec![FailureSignature {
+                signature: "Out of memory".to_string(),
+                count: 7,
+                jobs: vec![
+                    "e2e".to_string(),
+                    "unit".to_string(),
+                    "lint".to_string(),
+                    "build".to_string(),
+                ],
+                example_line: "FATAL ERROR: JavaScript heap out of memory".to_string(),
+                example_links: vec!["https://gitlab.com/test/project/-/jobs/42".to_string()],
+            }],
+            canceled: None,
+            live: None,
+        };
+
+        let output = render_summary(&insights);
+
+        assert!(output.contains("Top 10 Failure Signatures"));
+        assert!(output.contains("Out of memory"));
+        assert!(output.contains("e2e, unit, lint (+1 more)"));
+        assert!(output.contains("https://gitlab.com/test/project/-/jobs/42"));
+    }
+
+    #[test]
+    fn test_render_summary_shows_sampling_strategy() {
+        let insights = CIInsights {
+            provider: "GitLab".to_string(),
+            project: "test/project".to_string(),
+            collected_at: Utc::now(),
+            total_pipelines: 20,
+            total_pipeline_types: 1,
+            pipeline_types: vec![create_test_pipeline_type(
+                "Test",
+                100.0,
+                90.0,
+                500.0,
+                vec![create_test_job("unit", 100.0, 10.0, 0.0)],
+                "https://example.com",
+            )],
+            sampling: Some(SamplingSummary {
+                strategy: "stratified".to_string(),
+                sampled: BTreeMap::from([("failed".to_string(), 10), ("success".to_string(), 10)]),
+                population: BTreeMap::from([
+                    ("failed".to_string(), 40),
+                    ("success".to_string(), 360),
+                ]),
+            }),
+            clustering: None,
+            excluded: None,
+            merge_requests: None,
+            branch_health: None,
+            deployments: vec![],
+            failure_signatures: vec![],
+            canceled: None,
+            live: None,
+        };
+
+        let output = render_summary(&insights);
+
+        assert!(output.contains("Sampling:"));
+        assert!(output.contains("stratified (failed 10 of 40, success 10 of 360"));
+    }
+
+    #[test]
+    fn test_render_summary_shows_canceled_and_live_pipelines() {
+        let insights = CIInsights {
+            provider: "GitLab".to_string(),
+            project: "test/project".to_string(),
+            collected_at: Utc::now(),
+            total_pipelines: 20,
+            total_pipeline_types: 1,
+            pipeline_types: vec![create_test_pipeline_type(
+                "Test",
+                100.0,
+                80.0,
+                500.0,
+                vec![create_test_job("e2e", 100.0, 20.0, 0.0)],
+                "https://example.com",
+            )],
+            sampling: None,
+            clustering: None,
+            excluded: None,
+            merge_requests: None,
+            branch_health: None,
+            deployments: vec![],
+            failure_signatures: vec![],
+            canceled: Some(CanceledPipelines {
+                total_pipelines: 4,
+                percentage: 20.0,
+                runner_minutes: 95.0,
+                cost: Some(11.4),
+                links: vec![],
+            }),
+            live: Some(LivePipelines {
+                observed_at: Utc::now(),
+                running: 1,
+                pending: 1,
+                queued_p50: 120.0,
+                queued_p95: 900.0,
+                pipelines: vec![LivePipeline {
+                    url: "https://gitlab.com/test/project/-/pipelines/7".to_string(),
+                    ref_: "main".to_string(),
+                    source: "push".to_string(),
+                    status: "pending".to_string(),
+                    created_at: Utc::now(),
+                    queued: 900.0,
+                    running_for: 0.0,
+                }],
+            }),
+        };
+
+        let output = render_summary(&insights);
+
+        assert!(output.contains("4 pipelines (20.0%), 95 runner minutes, $11.40 wasted"));
+        assert!(output.contains("Live Pipelines"));
+        assert!(output.contains("https://gitlab.com/test/project/-/pipelines/7"));
+        assert!(output.contains("15.0min"));
+    }
 }
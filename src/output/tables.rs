@@ -56,3 +56,14 @@ pub fn color_coded_flakiness_cell(rate: f64) -> Cell {
         Cell::new(text).fg(TableColor::Green)
     }
 }
+
+pub fn color_coded_waste_cell(rate: f64) -> Cell {
+    let text = format!("{rate:.1}%");
+    if rate >= 25.0 {
+        Cell::new(text).fg(TableColor::Red)
+    } else if rate >= 10.0 {
+        Cell::new(text).fg(TableColor::Yellow)
+    } else {
+        Cell::new(text).fg(TableColor::Green)
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+
+use super::types::GitHubJob;
+
+/// Label present on every self-hosted runner; these minutes are not billed by GitHub.
+const SELF_HOSTED_LABEL: &str = "self-hosted";
+
+/// Billing multipliers relative to a standard 2-core Linux runner.
+const LINUX_MULTIPLIER: f64 = 1.0;
+const WINDOWS_MULTIPLIER: f64 = 2.0;
+const MACOS_MULTIPLIER: f64 = 10.0;
+const MACOS_LARGE_MULTIPLIER: f64 = 15.0;
+const MACOS_XLARGE_MULTIPLIER: f64 = 20.0;
+
+/// Core count of the standard runners the base multipliers are defined for.
+const STANDARD_RUNNER_CORES: f64 = 2.0;
+
+/// GitHub Actions cost model.
+///
+/// GitHub rounds every job up to the whole minute and bills each minute at a rate that
+/// depends on the runner: Windows minutes cost twice as much as Linux minutes, macOS
+/// minutes ten times as much, and larger runners scale with their core count. The
+/// configured `cost_per_minute` is the rate of a standard 2-core Linux runner.
+#[derive(Debug, Clone, Default)]
+pub struct BillingModel {
+    /// Cost per minute of a standard Linux runner
+    cost_per_minute: Option<f64>,
+    /// Multiplier overrides keyed by lowercase runner label
+    runner_multipliers: HashMap<String, f64>,
+    /// Whether to reconcile billable time against the run `timing` endpoint
+    reconcile_timing: bool,
+}
+
+impl BillingModel {
+    /// Create a new billing model.
+    ///
+    /// # Arguments
+    ///
+    /// * `cost_per_minute` - Optional cost per minute of a standard Linux runner
+    /// * `runner_multipliers` - Multiplier overrides by runner label (e.g., custom larger runners)
+    /// * `reconcile_timing` - Use billable time reported by the `timing` endpoint when available
+    pub fn new(
+        cost_per_minute: Option<f64>,
+        runner_multipliers: &HashMap<String, f64>,
+        reconcile_timing: bool,
+    ) -> Self {
+        Self {
+            cost_per_minute,
+            runner_multipliers: runner_multipliers
+                .iter()
+                .map(|(label, multiplier)| (label.to_lowercase(), *multiplier))
+                .collect(),
+            reconcile_timing,
+        }
+    }
+
+    /// Whether the run `timing` endpoint should be queried.
+    pub fn reconcile_timing(&self) -> bool {
+        self.reconcile_timing
+    }
+
+    /// Whether a cost rate is configured.
+    pub fn has_cost(&self) -> bool {
+        self.cost_per_minute.is_some()
+    }
+
+    /// Billing multiplier for a job, from configured overrides or the runner labels.
+    pub fn multiplier(&self, job: &GitHubJob) -> f64 {
+        job.labels
+            .iter()
+            .find_map(|label| self.runner_multipliers.get(&label.to_lowercase()))
+            .copied()
+            .unwrap_or_else(|| default_multiplier(&job.labels))
+    }
+
+    /// Minutes GitHub bills for a job, rounded up to the whole minute.
+    ///
+    /// Prefers the billable time reported by the `timing` endpoint, falling back to the
+    /// job's own timestamps. Jobs that never started are not billed.
+    pub fn billable_minutes(job: &GitHubJob) -> u64 {
+        let millis = job.billable_ms.or_else(|| {
+            let (started, completed) = (job.started_at?, job.completed_at?);
+            u64::try_from((completed - started).num_milliseconds()).ok()
+        });
+
+        millis.map_or(0, |ms| ms.div_ceil(60_000))
+    }
+
+    /// Cost of a single job execution, or `None` if no cost rate is configured.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn job_cost(&self, job: &GitHubJob) -> Option<f64> {
+        self.cost_per_minute
+            .map(|rate| Self::billable_minutes(job) as f64 * rate * self.multiplier(job))
+    }
+}
+
+/// Billing multiplier derived from GitHub-hosted runner labels.
+///
+/// Recognises the runner OS (`ubuntu-*`, `windows-*`, `macos-*`), macOS `-large` and
+/// `-xlarge` runners, and larger Linux/Windows runners whose label carries a core count
+/// (e.g., `ubuntu-22.04-16core`, `windows-latest-8-cores`). Self-hosted runners are free.
+pub fn default_multiplier(labels: &[String]) -> f64 {
+    let labels: Vec<String> = labels.iter().map(|l| l.to_lowercase()).collect();
+
+    if labels.iter().any(|l| l == SELF_HOSTED_LABEL) {
+        return 0.0;
+    }
+
+    if let Some(macos) = labels.iter().find(|l| l.contains("macos")) {
+        return if macos.ends_with("-xlarge") {
+            MACOS_XLARGE_MULTIPLIER
+        } else if macos.ends_with("-large") {
+            MACOS_LARGE_MULTIPLIER
+        } else {
+            MACOS_MULTIPLIER
+        };
+    }
+
+    let os_multiplier = if labels.iter().any(|l| l.contains("windows")) {
+        WINDOWS_MULTIPLIER
+    } else {
+        LINUX_MULTIPLIER
+    };
+
+    let cores = labels.iter().find_map(|l| core_count(l));
+    cores.map_or(os_multiplier, |cores| {
+        os_multiplier * (f64::from(cores) / STANDARD_RUNNER_CORES).max(1.0)
+    })
+}
+
+/// Extracts the core count from larger-runner labels such as `16core` or `8-cores`.
+fn core_count(label: &str) -> Option<u32> {
+    let index = label.find("core")?;
+    let digits: String = label[..index]
+        .trim_end_matches(['-', '_'])
+        .chars()
+        .rev()
+        .take_while(char::is_ascii_digit)
+        .collect();
+
+    digits.chars().rev().collect::<String>().parse().ok()
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn create_job(labels: &[&str], seconds: i64) -> GitHubJob {
+        let started = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        GitHubJob {
+            id: 1,
+            name: "build".to_string(),
+            status: "completed".to_string(),
+            conclusion: Some("success".to_string()),
+            started_at: Some(started),
+            completed_at: Some(started + Duration::seconds(seconds)),
+            steps: vec![],
+            labels: labels.iter().map(|l| (*l).to_string()).collect(),
+            html_url: None,
+            billable_ms: None,
+        }
+    }
+
+    fn labels(values: &[&str]) -> Vec<String> {
+        values.iter().map(|l| (*l).to_string()).collect()
+    }
+
+    #[test]
+    fn test_default_multiplier_by_os() {
+        assert_eq!(default_multiplier(&labels(&["ubuntu-latest"])), 1.0);
+        assert_eq!(default_multiplier(&labels(&["windows-2022"])), 2.0);
+        assert_eq!(default_multiplier(&labels(&["macos-14"])), 10.0);
+    }
+
+    #[test]
+    fn test_default_multiplier_for_larger_runners() {
+        assert_eq!(default_multiplier(&labels(&["ubuntu-22.04-16core"])), 8.0);
+        assert_eq!(
+            default_multiplier(&labels(&["windows-latest-8-cores"])),
+            8.0
+        );
+        assert_eq!(default_multiplier(&labels(&["macos-latest-large"])), 15.0);
+        assert_eq!(default_multiplier(&labels(&["macos-14-xlarge"])), 20.0);
+    }
+
+    #[test]
+    fn test_self_hosted_runners_are_free() {
+        assert_eq!(
+            default_multiplier(&labels(&["self-hosted", "linux", "x64"])),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_billable_minutes_round_up_per_job() {
+        assert_eq!(BillingModel::billable_minutes(&create_job(&[], 1)), 1);
+        assert_eq!(BillingModel::billable_minutes(&create_job(&[], 60)), 1);
+        assert_eq!(BillingModel::billable_minutes(&create_job(&[], 61)), 2);
+    }
+
+    #[test]
+    fn test_billable_minutes_prefers_timing_endpoint() {
+        let mut job = create_job(&[], 30);
+        job.billable_ms = Some(150_000);
+
+        assert_eq!(BillingModel::billable_minutes(&job), 3);
+    }
+
+    #[test]
+    fn test_job_cost_uses_configured_override() {
+        let overrides = HashMap::from([("GPU-Runner".to_string(), 4.0)]);
+        let model = BillingModel::new(Some(0.008), &overrides, false);
+
+        let cost = model.job_cost(&create_job(&["gpu-runner"], 90)).unwrap();
+
+        assert!((cost - 2.0 * 0.008 * 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_job_cost_without_rate() {
+        let model = BillingModel::new(None, &HashMap::new(), false);
+
+        assert_eq!(model.job_cost(&create_job(&["ubuntu-latest"], 90)), None);
+    }
+}
@@ -1,11 +1,14 @@
 use anyhow::{Context, Result};
-use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, USER_AGENT};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, LINK, USER_AGENT};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
+use url::Url;
 
 use crate::auth::Token;
+use crate::providers::http::HttpSettings;
 
-use super::types::{GitHubJob, GitHubWorkflowRun};
+use super::types::{GitHubJob, GitHubWorkflow, GitHubWorkflowRun};
 
 /// GitHub API client for fetching workflow data.
 #[derive(Clone)]
@@ -29,11 +32,19 @@ impl GitHubClient {
     /// * `owner` - Repository owner/organization
     /// * `repo` - Repository name
     /// * `token` - Optional GitHub personal access token
+    /// * `http` - Timeout, proxy and TLS settings for the HTTP client
     ///
     /// # Returns
     ///
-    /// A configured GitHub API client.
-    pub fn new(base_url: String, owner: String, repo: String, token: Option<Token>) -> Self {
+    /// A configured GitHub API client, or an error if the token or HTTP settings are
+    /// invalid.
+    pub fn new(
+        base_url: String,
+        owner: String,
+        repo: String,
+        token: Option<Token>,
+        http: &HttpSettings,
+    ) -> Result<Self> {
         let mut headers = HeaderMap::new();
         headers.insert(USER_AGENT, HeaderValue::from_static("cilens/1.0"));
 
@@ -44,17 +55,14 @@ impl GitHubClient {
             );
         }
 
-        let client = reqwest::Client::builder()
-            .default_headers(headers)
-            .build()
-            .expect("Failed to build HTTP client");
+        let client = http.build_client(reqwest::Client::builder().default_headers(headers))?;
 
-        Self {
+        Ok(Self {
             client,
             base_url,
             owner,
             repo,
-        }
+        })
     }
 
     /// Fetch workflow runs from GitHub API.
@@ -62,6 +70,8 @@ impl GitHubClient {
     /// # Arguments
     ///
     /// * `limit` - Maximum number of workflow runs to fetch
+    /// * `workflow` - Optional workflow filter (file name, path, numeric ID or display name)
+    /// * `event` - Optional triggering event filter (e.g., "push", "pull_request", "schedule")
     /// * `branch` - Optional branch filter
     /// * `since` - Optional start date filter
     /// * `until` - Optional end date filter
@@ -72,35 +82,45 @@ impl GitHubClient {
     pub async fn fetch_workflow_runs(
         &self,
         limit: usize,
+        workflow: Option<&str>,
+        event: Option<&str>,
         branch: Option<&str>,
         since: Option<chrono::DateTime<chrono::Utc>>,
         until: Option<chrono::DateTime<chrono::Utc>>,
     ) -> Result<Vec<GitHubWorkflowRun>> {
+        // Workflow-scoped runs live under /actions/workflows/{id}/runs
+        let runs_endpoint = match workflow {
+            Some(workflow) => {
+                let workflow_id = self.resolve_workflow_id(workflow).await?;
+                format!(
+                    "{}/repos/{}/{}/actions/workflows/{}/runs",
+                    self.base_url, self.owner, self.repo, workflow_id
+                )
+            }
+            None => format!(
+                "{}/repos/{}/{}/actions/runs",
+                self.base_url, self.owner, self.repo
+            ),
+        };
+
         let mut all_runs = Vec::new();
         let mut page = 1;
         let per_page = 100.min(limit);
 
         loop {
-            let mut url = format!(
-                "{}/repos/{}/{}/actions/runs?per_page={}&page={}",
-                self.base_url, self.owner, self.repo, per_page, page
-            );
-
-            if let Some(branch) = branch {
-                url.push_str(&format!("&branch={}", branch));
-            }
-
-            if let Some(since) = since {
-                url.push_str(&format!("&created=>={}", since.format("%Y-%m-%dT%H:%M:%SZ")));
-            }
-
-            if let Some(until) = until {
-                url.push_str(&format!("&created=<={}", until.format("%Y-%m-%dT%H:%M:%SZ")));
-            }
+            let url = workflow_runs_url(
+                &runs_endpoint,
+                per_page,
+                page,
+                event,
+                branch,
+                since,
+                until,
+            )?;
 
             let response: WorkflowRunsResponse = self
                 .client
-                .get(&url)
+                .get(url)
                 .send()
                 .await
                 .context("Failed to fetch workflow runs")?
@@ -122,6 +142,7 @@ impl GitHubClient {
                     run.jobs = jobs;
                     run.jobs_count = run.jobs.len();
                 }
+                run.duration = run_duration_seconds(run);
             }
 
             all_runs.extend(filtered_runs);
@@ -139,6 +160,63 @@ impl GitHubClient {
         Ok(all_runs)
     }
 
+    /// Resolve a `--workflow` argument to an identifier accepted by the workflow runs endpoint.
+    ///
+    /// Numeric IDs and workflow file names (e.g., "ci.yml") are used as-is, and
+    /// full paths (e.g., ".github/workflows/ci.yml") are reduced to their file name.
+    /// Anything else is matched case-insensitively against workflow display names.
+    async fn resolve_workflow_id(&self, workflow: &str) -> Result<String> {
+        if let Some(identifier) = workflow_identifier(workflow) {
+            return Ok(identifier);
+        }
+
+        let workflows = self.fetch_workflows().await?;
+
+        workflows
+            .iter()
+            .find(|w| w.name.eq_ignore_ascii_case(workflow))
+            .map(|w| w.id.to_string())
+            .ok_or_else(|| {
+                let available = workflows
+                    .iter()
+                    .map(|w| format!("'{}' ({})", w.name, w.path))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                anyhow::anyhow!("Workflow '{workflow}' not found. Available workflows: {available}")
+            })
+    }
+
+    /// Fetch all workflow definitions for the repository.
+    ///
+    /// Follows the `Link: rel="next"` header until every page has been read.
+    async fn fetch_workflows(&self) -> Result<Vec<GitHubWorkflow>> {
+        let mut workflows = Vec::new();
+        let mut next_url = Some(format!(
+            "{}/repos/{}/{}/actions/workflows?per_page=100",
+            self.base_url, self.owner, self.repo
+        ));
+
+        while let Some(url) = next_url {
+            let response = self
+                .client
+                .get(&url)
+                .send()
+                .await
+                .context("Failed to fetch workflows")?;
+
+            next_url = next_page_url(response.headers());
+
+            let page: WorkflowsResponse = response
+                .json()
+                .await
+                .context("Failed to parse workflows response")?;
+
+            workflows.extend(page.workflows);
+        }
+
+        Ok(workflows)
+    }
+
     /// Fetch jobs for a specific workflow run.
     async fn fetch_jobs_for_run(&self, run_id: u64) -> Result<Vec<GitHubJob>> {
         let url = format!(
@@ -158,6 +236,46 @@ impl GitHubClient {
 
         Ok(response.jobs)
     }
+
+    /// Fetch billable timing for a workflow run and copy it onto the run's jobs.
+    ///
+    /// The `timing` endpoint reports the milliseconds GitHub bills per job, grouped by
+    /// runner OS. Jobs missing from the response keep their timestamp-based estimate.
+    ///
+    /// # Returns
+    ///
+    /// Total billable milliseconds reported for the run.
+    pub async fn reconcile_run_timing(&self, run: &mut GitHubWorkflowRun) -> Result<u64> {
+        let url = format!(
+            "{}/repos/{}/{}/actions/runs/{}/timing",
+            self.base_url, self.owner, self.repo, run.id
+        );
+
+        let response: RunTimingResponse = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to fetch workflow run timing")?
+            .json()
+            .await
+            .context("Failed to parse workflow run timing response")?;
+
+        let billable_by_job: HashMap<u64, u64> = response
+            .billable
+            .values()
+            .flat_map(|os| os.job_runs.iter())
+            .map(|job_run| (job_run.job_id, job_run.duration_ms))
+            .collect();
+
+        for job in &mut run.jobs {
+            if let Some(duration_ms) = billable_by_job.get(&job.id) {
+                job.billable_ms = Some(*duration_ms);
+            }
+        }
+
+        Ok(response.billable.values().map(|os| os.total_ms).sum())
+    }
 }
 
 /// Response from GitHub API for workflow runs.
@@ -166,8 +284,209 @@ struct WorkflowRunsResponse {
     workflow_runs: Vec<GitHubWorkflowRun>,
 }
 
+/// Response from GitHub API for workflow definitions.
+#[derive(Deserialize)]
+struct WorkflowsResponse {
+    workflows: Vec<GitHubWorkflow>,
+}
+
 /// Response from GitHub API for workflow jobs.
 #[derive(Deserialize)]
 struct WorkflowJobsResponse {
     jobs: Vec<GitHubJob>,
-}
\ No newline at end of file
+}
+
+/// Response from GitHub API for workflow run billable timing.
+#[derive(Deserialize)]
+struct RunTimingResponse {
+    /// Billable time keyed by runner OS (e.g., "UBUNTU", "WINDOWS", "MACOS")
+    #[serde(default)]
+    billable: HashMap<String, BillableTiming>,
+}
+
+/// Billable time for one runner OS within a workflow run.
+#[derive(Deserialize)]
+struct BillableTiming {
+    total_ms: u64,
+    #[serde(default)]
+    job_runs: Vec<BillableJobRun>,
+}
+
+/// Billable time for a single job.
+#[derive(Deserialize)]
+struct BillableJobRun {
+    job_id: u64,
+    duration_ms: u64,
+}
+
+/// Build the workflow runs URL for one page, encoding every filter as a query parameter.
+fn workflow_runs_url(
+    runs_endpoint: &str,
+    per_page: usize,
+    page: usize,
+    event: Option<&str>,
+    branch: Option<&str>,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    until: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<Url> {
+    let mut url = Url::parse(runs_endpoint)
+        .with_context(|| format!("Invalid workflow runs URL: {runs_endpoint}"))?;
+
+    {
+        let mut query = url.query_pairs_mut();
+        query.append_pair("per_page", &per_page.to_string());
+        query.append_pair("page", &page.to_string());
+
+        if let Some(event) = event {
+            query.append_pair("event", event);
+        }
+
+        if let Some(branch) = branch {
+            query.append_pair("branch", branch);
+        }
+
+        if let Some(since) = since {
+            query.append_pair("created", &format!(">={}", since.format("%Y-%m-%dT%H:%M:%SZ")));
+        }
+
+        if let Some(until) = until {
+            query.append_pair("created", &format!("<={}", until.format("%Y-%m-%dT%H:%M:%SZ")));
+        }
+    }
+
+    Ok(url)
+}
+
+/// Returns the `rel="next"` target of a paginated response's `Link` header, if any.
+fn next_page_url(headers: &HeaderMap) -> Option<String> {
+    let link = headers.get(LINK)?.to_str().ok()?;
+
+    link.split(',').find_map(|entry| {
+        let mut parts = entry.split(';');
+        let target = parts.next()?.trim();
+        parts
+            .any(|param| param.trim() == r#"rel="next""#)
+            .then(|| target.trim_start_matches('<').trim_end_matches('>').to_string())
+    })
+}
+
+/// Returns the identifier to use directly in `/actions/workflows/{id}/runs`, or `None`
+/// when the argument is a display name that must be looked up.
+fn workflow_identifier(workflow: &str) -> Option<String> {
+    if workflow.chars().all(|c| c.is_ascii_digit()) {
+        return Some(workflow.to_string());
+    }
+
+    let file_name = workflow.rsplit('/').next().unwrap_or(workflow);
+    let is_workflow_file = std::path::Path::new(file_name)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("yml") || ext.eq_ignore_ascii_case("yaml"));
+
+    is_workflow_file.then(|| file_name.to_string())
+}
+
+/// Wall-clock duration of a run in seconds.
+///
+/// Spans from the earliest job start to the latest job completion, falling back to
+/// the run's own timestamps when no job timing is available.
+fn run_duration_seconds(run: &GitHubWorkflowRun) -> u64 {
+    let first_start = run.jobs.iter().filter_map(|j| j.started_at).min();
+    let last_completion = run.jobs.iter().filter_map(|j| j.completed_at).max();
+
+    let seconds = match (first_start, last_completion) {
+        (Some(start), Some(end)) => (end - start).num_seconds(),
+        _ => (run.updated_at - run.run_started_at.unwrap_or(run.created_at)).num_seconds(),
+    };
+
+    u64::try_from(seconds).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workflow_identifier_numeric_id() {
+        assert_eq!(workflow_identifier("161335"), Some("161335".to_string()));
+    }
+
+    #[test]
+    fn test_workflow_identifier_file_name() {
+        assert_eq!(workflow_identifier("ci.yml"), Some("ci.yml".to_string()));
+        assert_eq!(workflow_identifier("release.yaml"), Some("release.yaml".to_string()));
+    }
+
+    #[test]
+    fn test_workflow_identifier_strips_path() {
+        assert_eq!(
+            workflow_identifier(".github/workflows/codeql.yml"),
+            Some("codeql.yml".to_string())
+        );
+    }
+
+    #[test]
+    fn test_workflow_identifier_display_name_needs_lookup() {
+        assert_eq!(workflow_identifier("CI"), None);
+        assert_eq!(workflow_identifier("CodeQL Analysis"), None);
+    }
+
+    #[test]
+    fn test_workflow_runs_url_encodes_filters() {
+        let since = chrono::DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        let url = workflow_runs_url(
+            "https://api.github.com/repos/o/r/actions/runs",
+            100,
+            2,
+            Some("pull_request&x=1"),
+            Some("feature/a+b"),
+            Some(since),
+            None,
+        )
+        .unwrap();
+
+        let pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("per_page".to_string(), "100".to_string()),
+                ("page".to_string(), "2".to_string()),
+                ("event".to_string(), "pull_request&x=1".to_string()),
+                ("branch".to_string(), "feature/a+b".to_string()),
+                ("created".to_string(), ">=2024-01-01T00:00:00Z".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_next_page_url_follows_rel_next() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            LINK,
+            HeaderValue::from_static(
+                r#"<https://api.github.com/repositories/1/actions/workflows?per_page=100&page=2>; rel="next", <https://api.github.com/repositories/1/actions/workflows?per_page=100&page=3>; rel="last""#,
+            ),
+        );
+
+        assert_eq!(
+            next_page_url(&headers),
+            Some("https://api.github.com/repositories/1/actions/workflows?per_page=100&page=2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_next_page_url_none_on_last_page() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            LINK,
+            HeaderValue::from_static(
+                r#"<https://api.github.com/repositories/1/actions/workflows?per_page=100&page=1>; rel="prev", <https://api.github.com/repositories/1/actions/workflows?per_page=100&page=1>; rel="first""#,
+            ),
+        );
+
+        assert_eq!(next_page_url(&headers), None);
+        assert_eq!(next_page_url(&HeaderMap::new()), None);
+    }
+}
@@ -1,6 +1,10 @@
+mod billing;
 mod client;
+mod pipeline_metrics;
+mod pipeline_types;
 mod provider;
 mod tests;
 mod types;
 
+pub use billing::BillingModel;
 pub use provider::GitHubProvider;
\ No newline at end of file
@@ -0,0 +1,400 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::billing::BillingModel;
+use super::types::{links, GitHubJob, GitHubWorkflowRun};
+use crate::insights::{
+    CostBreakdown, JobCountWithLinks, JobMetrics, PipelineCountWithLinks, TypeMetrics,
+};
+use crate::providers::stats::{calculate_percentiles, cmp_f64};
+
+#[allow(clippy::cast_precision_loss)]
+fn calculate_rate(count: usize, total: usize) -> f64 {
+    if total > 0 {
+        (count as f64 / total as f64) * 100.0
+    } else {
+        0.0
+    }
+}
+
+fn is_success(conclusion: Option<&String>) -> bool {
+    conclusion.is_some_and(|c| c == "success")
+}
+
+/// Job execution time in seconds, if the job has both timestamps.
+#[allow(clippy::cast_precision_loss)]
+fn job_duration(job: &GitHubJob) -> Option<f64> {
+    let (started, completed) = (job.started_at?, job.completed_at?);
+    Some((completed - started).num_milliseconds().max(0) as f64 / 1000.0)
+}
+
+/// Seconds from the start of the run until the job completed.
+#[allow(clippy::cast_precision_loss)]
+fn job_time_to_feedback(run: &GitHubWorkflowRun, job: &GitHubJob) -> Option<f64> {
+    let run_start = run.run_started_at.unwrap_or(run.created_at);
+    let completed = job.completed_at?;
+    Some((completed - run_start).num_milliseconds().max(0) as f64 / 1000.0)
+}
+
+/// Calculates metrics for a group of GitHub Actions workflow runs.
+///
+/// Mirrors the GitLab calculation: duration and time-to-feedback percentiles come from
+/// successful runs, while failure counts consider every run. GitHub job listings only
+/// return the latest attempt, so flakiness is not detected here.
+///
+/// Costs follow GitHub's billing: every job execution is rounded up to the whole
+/// minute and charged at its runner's rate, and the type total is the sum of its jobs.
+/// Jobs in failed runs count as wasted on failed pipelines.
+pub(super) fn calculate_type_metrics(
+    pipeline_type_id: &str,
+    runs: &[&GitHubWorkflowRun],
+    percentage: f64,
+    repo_url: &str,
+    billing: &BillingModel,
+) -> TypeMetrics {
+    let total_pipelines = runs.len();
+
+    let (successful, failed): (Vec<&GitHubWorkflowRun>, Vec<&GitHubWorkflowRun>) = runs
+        .iter()
+        .partition(|run| is_success(run.conclusion.as_ref()));
+
+    let to_links = |runs: &[&GitHubWorkflowRun]| PipelineCountWithLinks {
+        count: runs.len(),
+        links: runs
+            .iter()
+            .map(|run| links::run_link(repo_url, run))
+            .collect(),
+    };
+
+    #[allow(clippy::cast_precision_loss)]
+    let durations: Vec<f64> = successful.iter().map(|run| run.duration as f64).collect();
+    let (duration_p50, duration_p95, duration_p99) = calculate_percentiles(&durations);
+
+    let first_feedback_times: Vec<f64> = successful
+        .iter()
+        .filter_map(|run| {
+            run.jobs
+                .iter()
+                .filter(|job| is_success(job.conclusion.as_ref()))
+                .filter_map(|job| job_time_to_feedback(run, job))
+                .min_by(|a, b| cmp_f64(*a, *b))
+        })
+        .collect();
+    let (time_to_feedback_p50, time_to_feedback_p95, time_to_feedback_p99) =
+        calculate_percentiles(&first_feedback_times);
+
+    let mut jobs = aggregate_job_metrics(pipeline_type_id, runs, repo_url, billing);
+    jobs.sort_by(|a, b| cmp_f64(b.time_to_feedback_p95, a.time_to_feedback_p95));
+
+    let billable_minutes: u64 = jobs.iter().filter_map(|job| job.billable_minutes).sum();
+
+    let cost_breakdown = billing.has_cost().then(|| {
+        jobs.iter().filter_map(|job| job.cost_breakdown).fold(
+            CostBreakdown::default(),
+            |mut acc, cost| {
+                acc += cost;
+                acc
+            },
+        )
+    });
+    #[allow(clippy::cast_precision_loss)]
+    let cost_per_pipeline = cost_breakdown.map(|cost| cost.total() / total_pipelines.max(1) as f64);
+    let total_cost = cost_breakdown.map(|cost| cost.total());
+
+    TypeMetrics {
+        percentage,
+        total_pipelines,
+        successful_pipelines: to_links(&successful),
+        failed_pipelines: to_links(&failed),
+        canceled_pipelines: PipelineCountWithLinks::default(),
+        success_rate: calculate_rate(successful.len(), total_pipelines),
+        duration_p50,
+        duration_p95,
+        duration_p99,
+        time_to_feedback_p50,
+        time_to_feedback_p95,
+        time_to_feedback_p99,
+        jobs,
+        cost_per_pipeline,
+        total_cost,
+        cost_breakdown,
+        cost_by_class: BTreeMap::new(),
+        billable_minutes: Some(billable_minutes),
+        stages: vec![],
+    }
+}
+
+#[derive(Default)]
+struct JobData {
+    durations: Vec<f64>,
+    time_to_feedbacks: Vec<f64>,
+    total_executions: usize,
+    failed_links: Vec<String>,
+    billable_minutes: u64,
+    cost: CostBreakdown,
+}
+
+fn aggregate_job_metrics(
+    pipeline_type_id: &str,
+    runs: &[&GitHubWorkflowRun],
+    repo_url: &str,
+    billing: &BillingModel,
+) -> Vec<JobMetrics> {
+    let mut job_data: HashMap<&str, JobData> = HashMap::new();
+
+    for run in runs {
+        for job in &run.jobs {
+            let data = job_data.entry(job.name.as_str()).or_default();
+            data.total_executions += 1;
+            data.billable_minutes += BillingModel::billable_minutes(job);
+            let cost = billing.job_cost(job).unwrap_or(0.0);
+            if is_success(run.conclusion.as_ref()) {
+                data.cost.useful += cost;
+            } else {
+                data.cost.failed_pipelines += cost;
+            }
+
+            if is_success(job.conclusion.as_ref()) {
+                data.durations.extend(job_duration(job));
+                data.time_to_feedbacks
+                    .extend(job_time_to_feedback(run, job));
+            } else if job.conclusion.as_deref() == Some("failure") {
+                data.failed_links.push(links::job_link(repo_url, run, job));
+            }
+        }
+    }
+
+    job_data
+        .into_iter()
+        .map(|(name, data)| {
+            let (duration_p50, duration_p95, duration_p99) = calculate_percentiles(&data.durations);
+            let (time_to_feedback_p50, time_to_feedback_p95, time_to_feedback_p99) =
+                calculate_percentiles(&data.time_to_feedbacks);
+
+            let cost_breakdown = billing.has_cost().then_some(data.cost);
+            #[allow(clippy::cast_precision_loss)]
+            let cost_per_execution =
+                cost_breakdown.map(|cost| cost.total() / data.total_executions.max(1) as f64);
+            let total_cost = cost_breakdown.map(|cost| cost.total());
+
+            JobMetrics {
+                name: name.to_string(),
+                pipeline_type_id: pipeline_type_id.to_string(),
+                duration_p50,
+                duration_p95,
+                duration_p99,
+                time_to_feedback_p50,
+                time_to_feedback_p95,
+                time_to_feedback_p99,
+                predecessors: vec![],
+                flakiness_rate: 0.0,
+                flaky_retries: JobCountWithLinks::default(),
+                failure_rate: calculate_rate(data.failed_links.len(), data.total_executions),
+                failed_executions: JobCountWithLinks {
+                    count: data.failed_links.len(),
+                    links: data.failed_links,
+                },
+                total_executions: data.total_executions,
+                cost_per_execution,
+                total_cost,
+                cost_breakdown,
+                cost_by_class: BTreeMap::new(),
+                billable_minutes: Some(data.billable_minutes),
+                tests: vec![],
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    const REPO_URL: &str = "https://github.com/owner/repo";
+
+    fn create_job(id: u64, name: &str, conclusion: &str, offset: i64, seconds: i64) -> GitHubJob {
+        let run_start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let started = run_start + Duration::seconds(offset);
+        GitHubJob {
+            id,
+            name: name.to_string(),
+            status: "completed".to_string(),
+            conclusion: Some(conclusion.to_string()),
+            started_at: Some(started),
+            completed_at: Some(started + Duration::seconds(seconds)),
+            steps: vec![],
+            labels: vec![],
+            html_url: None,
+            billable_ms: None,
+        }
+    }
+
+    fn create_run(
+        id: u64,
+        conclusion: &str,
+        duration: u64,
+        jobs: Vec<GitHubJob>,
+    ) -> GitHubWorkflowRun {
+        let created = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        GitHubWorkflowRun {
+            id,
+            name: Some("CI".to_string()),
+            head_branch: Some("main".to_string()),
+            head_sha: "abc123".to_string(),
+            path: ".github/workflows/ci.yml".to_string(),
+            display_title: "Test run".to_string(),
+            run_number: id,
+            event: "push".to_string(),
+            status: "completed".to_string(),
+            conclusion: Some(conclusion.to_string()),
+            jobs_count: jobs.len(),
+            jobs,
+            created_at: created,
+            updated_at: created,
+            run_started_at: Some(created),
+            duration,
+            html_url: None,
+        }
+    }
+
+    #[test]
+    fn test_success_rate_and_links() {
+        let run1 = create_run(
+            1,
+            "success",
+            100,
+            vec![create_job(1, "build", "success", 0, 60)],
+        );
+        let run2 = create_run(
+            2,
+            "failure",
+            100,
+            vec![create_job(2, "build", "failure", 0, 30)],
+        );
+        let runs = vec![&run1, &run2];
+
+        let metrics =
+            calculate_type_metrics("type-0", &runs, 100.0, REPO_URL, &BillingModel::default());
+
+        assert_eq!(metrics.total_pipelines, 2);
+        assert_eq!(metrics.success_rate, 50.0);
+        assert_eq!(metrics.successful_pipelines.count, 1);
+        assert_eq!(metrics.failed_pipelines.count, 1);
+    }
+
+    #[test]
+    fn test_job_durations_and_time_to_feedback() {
+        let run = create_run(
+            1,
+            "success",
+            150,
+            vec![
+                create_job(1, "build", "success", 0, 60),
+                create_job(2, "test", "success", 60, 90),
+            ],
+        );
+        let runs = vec![&run];
+
+        let metrics =
+            calculate_type_metrics("type-0", &runs, 100.0, REPO_URL, &BillingModel::default());
+
+        let test_job = metrics.jobs.iter().find(|j| j.name == "test").unwrap();
+        assert_eq!(test_job.duration_p50, 90.0);
+        assert_eq!(test_job.time_to_feedback_p50, 150.0);
+        assert_eq!(metrics.time_to_feedback_p50, 60.0);
+        // Sorted by time-to-feedback, slowest first
+        assert_eq!(metrics.jobs[0].name, "test");
+    }
+
+    #[test]
+    fn test_job_failure_rate() {
+        let run1 = create_run(
+            1,
+            "success",
+            100,
+            vec![create_job(1, "test", "success", 0, 60)],
+        );
+        let run2 = create_run(
+            2,
+            "failure",
+            100,
+            vec![create_job(2, "test", "failure", 0, 60)],
+        );
+        let runs = vec![&run1, &run2];
+
+        let metrics =
+            calculate_type_metrics("type-0", &runs, 100.0, REPO_URL, &BillingModel::default());
+
+        let job = &metrics.jobs[0];
+        assert_eq!(job.total_executions, 2);
+        assert_eq!(job.failed_executions.count, 1);
+        assert_eq!(job.failure_rate, 50.0);
+        assert_eq!(job.pipeline_type_id, "type-0");
+        assert_eq!(
+            job.failed_executions.links,
+            vec!["https://github.com/owner/repo/actions/runs/2/job/2".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_links_prefer_api_html_url() {
+        let mut run = create_run(
+            7,
+            "failure",
+            100,
+            vec![create_job(8, "test", "failure", 0, 60)],
+        );
+        run.html_url = Some("https://github.example.com/owner/repo/actions/runs/7".to_string());
+        run.jobs[0].html_url =
+            Some("https://github.example.com/owner/repo/actions/runs/7/job/8".to_string());
+        let runs = vec![&run];
+
+        let metrics =
+            calculate_type_metrics("type-0", &runs, 100.0, REPO_URL, &BillingModel::default());
+
+        assert_eq!(
+            metrics.failed_pipelines.links,
+            vec!["https://github.example.com/owner/repo/actions/runs/7".to_string()]
+        );
+        assert_eq!(
+            metrics.jobs[0].failed_executions.links,
+            vec!["https://github.example.com/owner/repo/actions/runs/7/job/8".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_billable_minutes_and_costs() {
+        let mut macos_job = create_job(2, "ios", "success", 0, 61);
+        macos_job.labels = vec!["macos-14".to_string()];
+        let run1 = create_run(
+            1,
+            "success",
+            100,
+            vec![create_job(1, "build", "success", 0, 30), macos_job],
+        );
+        let run2 = create_run(
+            2,
+            "failure",
+            100,
+            vec![create_job(3, "build", "failure", 0, 90)],
+        );
+        let runs = vec![&run1, &run2];
+        let billing = BillingModel::new(Some(0.008), &HashMap::new(), false);
+
+        let metrics = calculate_type_metrics("type-0", &runs, 100.0, REPO_URL, &billing);
+
+        let build = metrics.jobs.iter().find(|j| j.name == "build").unwrap();
+        assert_eq!(build.billable_minutes, Some(3));
+        assert!((build.total_cost.unwrap() - 3.0 * 0.008).abs() < 1e-9);
+        let ios = metrics.jobs.iter().find(|j| j.name == "ios").unwrap();
+        assert_eq!(ios.billable_minutes, Some(2));
+        assert!((ios.total_cost.unwrap() - 2.0 * 0.008 * 10.0).abs() < 1e-9);
+        assert_eq!(metrics.billable_minutes, Some(5));
+        assert!((metrics.total_cost.unwrap() - 0.184).abs() < 1e-9);
+        assert!((metrics.cost_per_pipeline.unwrap() - 0.092).abs() < 1e-9);
+        let breakdown = metrics.cost_breakdown.unwrap();
+        assert!((breakdown.failed_pipelines - 2.0 * 0.008).abs() < 1e-9);
+        assert!((breakdown.useful - 0.168).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,369 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use super::billing::BillingModel;
+use super::types::GitHubWorkflowRun;
+use crate::insights::{ClusteringSummary, ExcludedPipelines, PipelineType};
+use crate::providers::grouping::PipelineGrouping;
+
+fn extract_job_signature(run: &GitHubWorkflowRun) -> Vec<String> {
+    run.jobs
+        .iter()
+        .map(|j| j.name.clone())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Human-readable workflow name, falling back to the workflow file name.
+fn workflow_name(run: &GitHubWorkflowRun) -> String {
+    run.name
+        .clone()
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| run.path.rsplit('/').next().unwrap_or(&run.path).to_string())
+}
+
+/// Groups workflow runs by workflow file, then by job signature within each workflow.
+///
+/// Runs from different workflows (e.g., `ci.yml` and `release.yml`) never share a type,
+/// even if they happen to run identically named jobs. Each resulting type carries the
+/// workflow name so the report can be read per workflow. Runs are clustered once; the
+/// types below `min_type_percentage` are aggregated into the excluded group.
+///
+/// # Arguments
+///
+/// * `runs` - Collection of workflow runs to analyze
+/// * `min_type_percentage` - Minimum percentage (0-100) of all runs required for a type to be included
+/// * `repo_url` - Repository web URL for generating links
+/// * `billing` - GitHub billing model used for cost estimates
+///
+/// # Returns
+///
+/// Pipeline types ordered by workflow path, then by frequency (most common first), with
+/// the excluded runs and the clustering summary.
+pub(super) fn group_workflow_types(
+    runs: &[GitHubWorkflowRun],
+    min_type_percentage: u8,
+    repo_url: &str,
+    billing: &BillingModel,
+) -> PipelineGrouping {
+    let total_runs = runs.len();
+    #[allow(clippy::cast_precision_loss)]
+    let percentage_of = |count: usize| (count as f64 / total_runs.max(1) as f64) * 100.0;
+
+    let (kept, excluded_clusters): (Vec<_>, Vec<_>) = cluster_workflow_runs(runs)
+        .into_iter()
+        .enumerate()
+        .partition(|(_, cluster)| percentage_of(cluster.len()) >= f64::from(min_type_percentage));
+
+    let pipeline_types = kept
+        .iter()
+        .map(|(index, cluster_runs)| {
+            create_pipeline_type(*index, cluster_runs, total_runs, repo_url, billing)
+        })
+        .collect();
+
+    let excluded_clusters: Vec<Vec<&GitHubWorkflowRun>> = excluded_clusters
+        .into_iter()
+        .map(|(_, cluster)| cluster)
+        .collect();
+    let dropped_runs = excluded_clusters.iter().map(Vec::len).sum();
+
+    PipelineGrouping {
+        pipeline_types,
+        excluded: aggregate_excluded_runs(excluded_clusters, total_runs, repo_url, billing),
+        clustering: ClusteringSummary {
+            mode: "exact".to_string(),
+            similarity_threshold: None,
+            exact_dropped_pipelines: dropped_runs,
+            similarity_dropped_pipelines: None,
+        },
+    }
+}
+
+/// Aggregates the clusters below `min_type_percentage` into one group.
+///
+/// # Returns
+///
+/// Metrics over all excluded runs, or `None` if no run was excluded.
+fn aggregate_excluded_runs(
+    excluded_clusters: Vec<Vec<&GitHubWorkflowRun>>,
+    total_runs: usize,
+    repo_url: &str,
+    billing: &BillingModel,
+) -> Option<ExcludedPipelines> {
+    if excluded_clusters.is_empty() {
+        return None;
+    }
+
+    let pipeline_types = excluded_clusters.len();
+    let excluded: Vec<&GitHubWorkflowRun> = excluded_clusters.into_iter().flatten().collect();
+    #[allow(clippy::cast_precision_loss)]
+    let percentage = (excluded.len() as f64 / total_runs.max(1) as f64) * 100.0;
+    let metrics = super::pipeline_metrics::calculate_type_metrics(
+        "other", &excluded, percentage, repo_url, billing,
+    );
+
+    Some(ExcludedPipelines {
+        pipeline_types,
+        metrics,
+    })
+}
+
+/// Clusters runs by workflow path, then by job signature, most common first per workflow.
+fn cluster_workflow_runs(runs: &[GitHubWorkflowRun]) -> Vec<Vec<&GitHubWorkflowRun>> {
+    let mut workflows: BTreeMap<&str, BTreeMap<Vec<String>, Vec<&GitHubWorkflowRun>>> =
+        BTreeMap::new();
+    for run in runs {
+        workflows
+            .entry(run.path.as_str())
+            .or_default()
+            .entry(extract_job_signature(run))
+            .or_default()
+            .push(run);
+    }
+
+    let mut clusters: Vec<Vec<&GitHubWorkflowRun>> = Vec::new();
+    for signatures in workflows.into_values() {
+        let mut workflow_clusters: Vec<_> = signatures.into_values().collect();
+        workflow_clusters.sort_by(|a, b| b.len().cmp(&a.len()));
+        clusters.extend(workflow_clusters);
+    }
+
+    clusters
+}
+
+fn create_pipeline_type(
+    index: usize,
+    runs: &[&GitHubWorkflowRun],
+    total_runs: usize,
+    repo_url: &str,
+    billing: &BillingModel,
+) -> PipelineType {
+    #[allow(clippy::cast_precision_loss)]
+    let percentage = (runs.len() as f64 / total_runs.max(1) as f64) * 100.0;
+
+    let id = format!("type-{index}");
+
+    // All runs in a cluster share the same workflow path
+    let workflow = runs.first().map(|run| workflow_name(run));
+
+    let collect_unique = |values: Vec<String>| -> Vec<String> {
+        values
+            .into_iter()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect()
+    };
+
+    let ref_patterns = collect_unique(
+        runs.iter()
+            .filter_map(|run| run.head_branch.clone())
+            .collect(),
+    );
+    let sources = collect_unique(runs.iter().map(|run| run.event.clone()).collect());
+
+    // All runs in a cluster share the same job signature
+    let core_jobs = runs
+        .first()
+        .map(|run| extract_job_signature(run))
+        .unwrap_or_default();
+
+    let metrics =
+        super::pipeline_metrics::calculate_type_metrics(&id, runs, percentage, repo_url, billing);
+
+    PipelineType {
+        id,
+        label: workflow.clone().unwrap_or_else(|| "Unknown".to_string()),
+        workflow,
+        stages: vec![],
+        ref_patterns,
+        sources,
+        core_jobs,
+        optional_jobs: vec![],
+        metrics,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::types::GitHubJob;
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    const REPO_URL: &str = "https://github.com/owner/repo";
+
+    fn create_job(id: u64, name: &str) -> GitHubJob {
+        let started = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        GitHubJob {
+            id,
+            name: name.to_string(),
+            status: "completed".to_string(),
+            conclusion: Some("success".to_string()),
+            started_at: Some(started),
+            completed_at: Some(started + chrono::Duration::seconds(60)),
+            steps: vec![],
+            labels: vec!["ubuntu-latest".to_string()],
+            html_url: None,
+            billable_ms: None,
+        }
+    }
+
+    fn create_run(
+        id: u64,
+        name: &str,
+        path: &str,
+        event: &str,
+        jobs: Vec<GitHubJob>,
+    ) -> GitHubWorkflowRun {
+        let created = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        GitHubWorkflowRun {
+            id,
+            name: Some(name.to_string()),
+            head_branch: Some("main".to_string()),
+            head_sha: "abc123".to_string(),
+            path: path.to_string(),
+            display_title: "Test run".to_string(),
+            run_number: id,
+            event: event.to_string(),
+            status: "completed".to_string(),
+            conclusion: Some("success".to_string()),
+            jobs_count: jobs.len(),
+            jobs,
+            created_at: created,
+            updated_at: created + chrono::Duration::seconds(120),
+            run_started_at: Some(created),
+            duration: 120,
+            html_url: None,
+        }
+    }
+
+    #[test]
+    fn test_groups_separate_workflows_with_identical_jobs() {
+        let runs = vec![
+            create_run(
+                1,
+                "CI",
+                ".github/workflows/ci.yml",
+                "push",
+                vec![create_job(1, "build")],
+            ),
+            create_run(
+                2,
+                "CI",
+                ".github/workflows/ci.yml",
+                "push",
+                vec![create_job(2, "build")],
+            ),
+            create_run(
+                3,
+                "Release",
+                ".github/workflows/release.yml",
+                "push",
+                vec![create_job(3, "build")],
+            ),
+        ];
+
+        let types =
+            group_workflow_types(&runs, 0, REPO_URL, &BillingModel::default()).pipeline_types;
+
+        assert_eq!(types.len(), 2);
+        assert_eq!(types[0].workflow.as_deref(), Some("CI"));
+        assert_eq!(types[0].metrics.total_pipelines, 2);
+        assert_eq!(types[1].workflow.as_deref(), Some("Release"));
+        assert_eq!(types[1].metrics.total_pipelines, 1);
+    }
+
+    #[test]
+    fn test_splits_workflow_by_job_signature() {
+        let runs = vec![
+            create_run(
+                1,
+                "CI",
+                ".github/workflows/ci.yml",
+                "push",
+                vec![create_job(1, "build")],
+            ),
+            create_run(
+                2,
+                "CI",
+                ".github/workflows/ci.yml",
+                "pull_request",
+                vec![create_job(2, "build"), create_job(3, "lint")],
+            ),
+        ];
+
+        let types =
+            group_workflow_types(&runs, 0, REPO_URL, &BillingModel::default()).pipeline_types;
+
+        assert_eq!(types.len(), 2);
+        assert!(types.iter().all(|pt| pt.workflow.as_deref() == Some("CI")));
+    }
+
+    #[test]
+    fn test_collects_events_as_sources() {
+        let runs = vec![
+            create_run(
+                1,
+                "CI",
+                ".github/workflows/ci.yml",
+                "push",
+                vec![create_job(1, "build")],
+            ),
+            create_run(
+                2,
+                "CI",
+                ".github/workflows/ci.yml",
+                "schedule",
+                vec![create_job(2, "build")],
+            ),
+        ];
+
+        let types =
+            group_workflow_types(&runs, 0, REPO_URL, &BillingModel::default()).pipeline_types;
+
+        assert_eq!(types.len(), 1);
+        assert_eq!(types[0].sources.len(), 2);
+        assert!(types[0].sources.contains(&"schedule".to_string()));
+    }
+
+    #[test]
+    fn test_filters_by_min_type_percentage() {
+        let mut runs: Vec<GitHubWorkflowRun> = (0..9)
+            .map(|i| {
+                create_run(
+                    i,
+                    "CI",
+                    ".github/workflows/ci.yml",
+                    "push",
+                    vec![create_job(i, "build")],
+                )
+            })
+            .collect();
+        runs.push(create_run(
+            9,
+            "CodeQL",
+            ".github/workflows/codeql.yml",
+            "schedule",
+            vec![create_job(9, "analyze")],
+        ));
+
+        let grouping = group_workflow_types(&runs, 20, REPO_URL, &BillingModel::default());
+
+        assert_eq!(grouping.pipeline_types.len(), 1);
+        assert_eq!(grouping.pipeline_types[0].workflow.as_deref(), Some("CI"));
+
+        let excluded = grouping.excluded.unwrap();
+        assert_eq!(excluded.pipeline_types, 1);
+        assert_eq!(excluded.metrics.total_pipelines, 1);
+        assert_eq!(grouping.clustering.mode, "exact");
+        assert_eq!(grouping.clustering.exact_dropped_pipelines, 1);
+    }
+
+    #[test]
+    fn test_workflow_name_falls_back_to_file_name() {
+        let mut run = create_run(1, "", ".github/workflows/ci.yml", "push", vec![]);
+        run.name = None;
+
+        assert_eq!(workflow_name(&run), "ci.yml");
+    }
+}
@@ -4,9 +4,11 @@ use std::sync::Arc;
 
 use crate::auth::Token;
 use crate::insights::CIInsights;
+use crate::providers::http::HttpSettings;
 
+use super::billing::BillingModel;
 use super::client::GitHubClient;
-use super::types::GitHubWorkflowRun;
+use super::types::{links, GitHubWorkflowRun};
 
 /// Provider for collecting CI/CD insights from GitHub Actions.
 pub struct GitHubProvider {
@@ -16,6 +18,8 @@ pub struct GitHubProvider {
     owner: String,
     /// Repository name
     repo: String,
+    /// Repository web URL used for report links
+    repo_url: String,
 }
 
 impl GitHubProvider {
@@ -26,6 +30,7 @@ impl GitHubProvider {
     /// * `base_url` - GitHub API base URL
     /// * `project_path` - Repository path in format "owner/repo"
     /// * `token` - Optional GitHub personal access token
+    /// * `http` - Timeout, proxy and TLS settings for API requests
     ///
     /// # Returns
     ///
@@ -34,6 +39,7 @@ impl GitHubProvider {
         base_url: String,
         project_path: String,
         token: Option<Token>,
+        http: &HttpSettings,
     ) -> Result<Self> {
         let parts: Vec<&str> = project_path.split('/').collect();
         if parts.len() != 2 {
@@ -43,12 +49,14 @@ impl GitHubProvider {
         let owner = parts[0].to_string();
         let repo = parts[1].to_string();
 
-        let client = GitHubClient::new(base_url, owner.clone(), repo.clone(), token);
+        let repo_url = links::repository_url(&base_url, &owner, &repo);
+        let client = GitHubClient::new(base_url, owner.clone(), repo.clone(), token, http)?;
 
         Ok(Self {
             client: Arc::new(client),
             owner,
             repo,
+            repo_url,
         })
     }
 
@@ -60,16 +68,18 @@ impl GitHubProvider {
     /// # Arguments
     ///
     /// * `limit` - Maximum number of workflow runs to analyze
+    /// * `workflow` - Optional workflow filter (file name, path, numeric ID or display name)
+    /// * `event` - Optional triggering event filter (e.g., "push", "pull_request", "schedule")
     /// * `branch` - Optional branch filter
     /// * `since` - Optional start date for filtering runs
     /// * `until` - Optional end date for filtering runs
     /// * `min_type_percentage` - Minimum percentage threshold for workflow type filtering
-    /// * `cost_per_minute` - Optional cost per minute for CI/CD compute
+    /// * `billing` - Billing model with runner rates and timing reconciliation settings
     ///
     /// # Returns
     ///
-    /// `CIInsights` containing workflow types grouped by job signature,
-    /// with comprehensive metrics for each type and job.
+    /// `CIInsights` containing workflow types grouped by workflow file and then by
+    /// job signature, with comprehensive metrics for each type and job.
     ///
     /// # Errors
     ///
@@ -77,14 +87,17 @@ impl GitHubProvider {
     /// - GitHub API requests fail after retries
     /// - Repository or workflow data is not found
     /// - Network or parsing errors occur
+    #[allow(clippy::too_many_arguments)]
     pub async fn collect_insights(
         &self,
         limit: usize,
+        workflow: Option<&str>,
+        event: Option<&str>,
         branch: Option<&str>,
         since: Option<DateTime<Utc>>,
         until: Option<DateTime<Utc>>,
         min_type_percentage: u8,
-        cost_per_minute: Option<f64>,
+        billing: &BillingModel,
     ) -> Result<CIInsights> {
         log::info!(
             "Starting insights collection for GitHub repository: {}/{}",
@@ -93,37 +106,85 @@ impl GitHubProvider {
         );
 
         // Fetch workflow runs from GitHub API
-        let workflow_runs = self
+        let mut workflow_runs = self
             .client
-            .fetch_workflow_runs(limit, branch, since, until)
+            .fetch_workflow_runs(limit, workflow, event, branch, since, until)
             .await
             .context("Failed to fetch workflow runs")?;
 
         log::info!("Fetched {} workflow runs", workflow_runs.len());
 
+        if billing.reconcile_timing() {
+            self.reconcile_billing(&mut workflow_runs).await;
+        }
+
         // Convert GitHub workflow runs to CIInsights
-        let insights = self.convert_to_insights(workflow_runs, min_type_percentage, cost_per_minute);
+        let insights = self.convert_to_insights(workflow_runs, min_type_percentage, billing);
 
         Ok(insights)
     }
 
+    /// Replace estimated job durations with the billable time GitHub reports.
+    ///
+    /// Runs whose timing cannot be fetched keep their timestamp-based estimate.
+    async fn reconcile_billing(&self, workflow_runs: &mut [GitHubWorkflowRun]) {
+        let estimated_minutes: u64 = workflow_runs
+            .iter()
+            .flat_map(|run| &run.jobs)
+            .map(BillingModel::billable_minutes)
+            .sum();
+
+        let mut reported_ms = 0;
+        for run in workflow_runs.iter_mut() {
+            match self.client.reconcile_run_timing(run).await {
+                Ok(total_ms) => reported_ms += total_ms,
+                Err(e) => log::warn!("Failed to fetch billable timing for run {}: {e:#}", run.id),
+            }
+        }
+
+        let reconciled_minutes: u64 = workflow_runs
+            .iter()
+            .flat_map(|run| &run.jobs)
+            .map(BillingModel::billable_minutes)
+            .sum();
+
+        log::info!(
+            "Billable minutes: {estimated_minutes} estimated from job timestamps, \
+             {reconciled_minutes} after reconciliation ({} reported by GitHub)",
+            reported_ms.div_ceil(60_000)
+        );
+    }
+
     /// Convert GitHub workflow runs to CIInsights format.
     fn convert_to_insights(
         &self,
         workflow_runs: Vec<GitHubWorkflowRun>,
         min_type_percentage: u8,
-        cost_per_minute: Option<f64>,
+        billing: &BillingModel,
     ) -> CIInsights {
-        // For now, create a basic structure. This would need more implementation
-        // to fully match the GitLab provider's functionality.
+        let grouping = super::pipeline_types::group_workflow_types(
+            &workflow_runs,
+            min_type_percentage,
+            &self.repo_url,
+            billing,
+        );
 
         CIInsights {
             provider: "GitHub Actions".to_string(),
             project: format!("{}/{}", self.owner, self.repo),
             collected_at: Utc::now(),
             total_pipelines: workflow_runs.len(),
-            total_pipeline_types: 1, // Simplified for now
-            pipeline_types: vec![], // Would need to implement pipeline type grouping
+            total_pipeline_types: grouping.pipeline_types.len(),
+            pipeline_types: grouping.pipeline_types,
+            sampling: None,
+            clustering: Some(grouping.clustering),
+            excluded: grouping.excluded,
+            merge_requests: None,
+            branch_health: None,
+            deployments: vec![],
+            failure_signatures: vec![],
+            canceled: None,
+            live: None,
         }
     }
 }
\ No newline at end of file
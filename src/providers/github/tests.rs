@@ -2,6 +2,7 @@
 mod tests {
     use super::*;
     use crate::auth::Token;
+    use crate::providers::{BillingModel, HttpSettings};
 
     #[test]
     fn test_github_provider_creation() {
@@ -9,6 +10,7 @@ mod tests {
             "https://api.github.com".to_string(),
             "owner/repo".to_string(),
             Some(Token::from("test-token")),
+            &HttpSettings::default(),
         ).unwrap();
 
         assert_eq!(provider.owner, "owner");
@@ -21,6 +23,7 @@ mod tests {
             "https://api.github.com".to_string(),
             "invalid-path".to_string(),
             None,
+            &HttpSettings::default(),
         );
 
         assert!(result.is_err());
@@ -33,6 +36,7 @@ mod tests {
             "https://api.github.com".to_string(),
             "owner/repo/extra".to_string(),
             None,
+            &HttpSettings::default(),
         );
 
         assert!(result.is_err());
@@ -44,11 +48,12 @@ mod tests {
             "https://api.github.com".to_string(),
             "test-owner/test-repo".to_string(),
             None,
+            &HttpSettings::default(),
         ).unwrap();
 
         // This would normally make API calls, but for testing we just check
         // that the method exists and returns a basic structure
-        let result = provider.collect_insights(10, None, None, None, 1, None).await;
+        let result = provider.collect_insights(10, None, None, None, None, None, 1, &BillingModel::default()).await;
 
         // Since we don't have a real implementation yet, this might fail
         // but the structure should be correct
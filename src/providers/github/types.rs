@@ -25,15 +25,37 @@ pub struct GitHubWorkflowRun {
     /// Conclusion of the run (success, failure, etc.)
     pub conclusion: Option<String>,
     /// Number of jobs in the workflow
+    #[serde(default)]
     pub jobs_count: usize,
-    /// Jobs in this workflow run
+    /// Jobs in this workflow run (populated by a separate API call)
+    #[serde(default)]
     pub jobs: Vec<GitHubJob>,
     /// When the run was created
     pub created_at: DateTime<Utc>,
     /// When the run was updated
     pub updated_at: DateTime<Utc>,
-    /// Total duration in seconds
+    /// When the latest attempt of the run started
+    #[serde(default)]
+    pub run_started_at: Option<DateTime<Utc>>,
+    /// Total duration in seconds (computed from job timestamps)
+    #[serde(default)]
     pub duration: u64,
+    /// Web URL of the run, as reported by the API
+    #[serde(default)]
+    pub html_url: Option<String>,
+}
+
+/// GitHub Actions workflow definition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitHubWorkflow {
+    /// Unique identifier for the workflow
+    pub id: u64,
+    /// Name of the workflow
+    pub name: String,
+    /// Path to the workflow file (e.g., ".github/workflows/ci.yml")
+    pub path: String,
+    /// Workflow state (e.g., "active", "disabled_manually")
+    pub state: String,
 }
 
 /// Job within a GitHub Actions workflow run.
@@ -55,6 +77,12 @@ pub struct GitHubJob {
     pub steps: Vec<GitHubStep>,
     /// Labels for the runner
     pub labels: Vec<String>,
+    /// Web URL of the job, as reported by the API
+    #[serde(default)]
+    pub html_url: Option<String>,
+    /// Billable time in milliseconds reported by the run `timing` endpoint
+    #[serde(default)]
+    pub billable_ms: Option<u64>,
 }
 
 /// Step within a GitHub Actions job.
@@ -76,15 +104,103 @@ pub struct GitHubStep {
 
 /// Links for GitHub resources.
 pub mod links {
-    use super::*;
+    use super::{GitHubJob, GitHubWorkflowRun};
+
+    const PUBLIC_API_URL: &str = "https://api.github.com";
+    const PUBLIC_WEB_URL: &str = "https://github.com";
+
+    /// Derive the web UI base URL from the configured API base URL.
+    ///
+    /// `https://api.github.com` maps to `https://github.com`; GitHub Enterprise Server
+    /// URLs such as `https://github.example.com/api/v3` lose their `/api/v3` suffix.
+    pub fn web_base_url(api_base_url: &str) -> String {
+        let trimmed = api_base_url.trim_end_matches('/');
+        if trimmed == PUBLIC_API_URL {
+            return PUBLIC_WEB_URL.to_string();
+        }
+        trimmed
+            .strip_suffix("/api/v3")
+            .unwrap_or(trimmed)
+            .to_string()
+    }
+
+    /// Generate the web URL of a repository (e.g., `https://github.com/owner/repo`).
+    pub fn repository_url(api_base_url: &str, owner: &str, repo: &str) -> String {
+        format!("{}/{owner}/{repo}", web_base_url(api_base_url))
+    }
 
     /// Generate URL for a workflow run.
-    pub fn workflow_run_url(owner: &str, repo: &str, run_id: u64) -> String {
-        format!("https://github.com/{}/{}/actions/runs/{}", owner, repo, run_id)
+    pub fn workflow_run_url(repo_url: &str, run_id: u64) -> String {
+        format!("{repo_url}/actions/runs/{run_id}")
     }
 
-    /// Generate URL for a job.
-    pub fn job_url(owner: &str, repo: &str, job_id: u64) -> String {
-        format!("https://github.com/{}/{}/actions/runs/{}", owner, repo, job_id)
+    /// Generate URL for a job within a workflow run.
+    pub fn job_url(repo_url: &str, run_id: u64, job_id: u64) -> String {
+        format!("{repo_url}/actions/runs/{run_id}/job/{job_id}")
     }
-}
\ No newline at end of file
+
+    /// URL for a workflow run, preferring the `html_url` returned by the API.
+    pub fn run_link(repo_url: &str, run: &GitHubWorkflowRun) -> String {
+        run.html_url
+            .clone()
+            .unwrap_or_else(|| workflow_run_url(repo_url, run.id))
+    }
+
+    /// URL for a job, preferring the `html_url` returned by the API.
+    pub fn job_link(repo_url: &str, run: &GitHubWorkflowRun, job: &GitHubJob) -> String {
+        job.html_url
+            .clone()
+            .unwrap_or_else(|| job_url(repo_url, run.id, job.id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::links::*;
+
+    #[test]
+    fn test_web_base_url_for_github_com() {
+        assert_eq!(web_base_url("https://api.github.com"), "https://github.com");
+        assert_eq!(web_base_url("https://api.github.com/"), "https://github.com");
+    }
+
+    #[test]
+    fn test_web_base_url_for_enterprise_server() {
+        assert_eq!(
+            web_base_url("https://github.example.com/api/v3"),
+            "https://github.example.com"
+        );
+        assert_eq!(
+            web_base_url("https://github.example.com/api/v3/"),
+            "https://github.example.com"
+        );
+    }
+
+    #[test]
+    fn test_links_for_github_com() {
+        let repo_url = repository_url("https://api.github.com", "owner", "repo");
+
+        assert_eq!(
+            workflow_run_url(&repo_url, 42),
+            "https://github.com/owner/repo/actions/runs/42"
+        );
+        assert_eq!(
+            job_url(&repo_url, 42, 1337),
+            "https://github.com/owner/repo/actions/runs/42/job/1337"
+        );
+    }
+
+    #[test]
+    fn test_links_for_enterprise_server() {
+        let repo_url = repository_url("https://github.example.com/api/v3", "owner", "repo");
+
+        assert_eq!(
+            workflow_run_url(&repo_url, 42),
+            "https://github.example.com/owner/repo/actions/runs/42"
+        );
+        assert_eq!(
+            job_url(&repo_url, 42, 1337),
+            "https://github.example.com/owner/repo/actions/runs/42/job/1337"
+        );
+    }
+}
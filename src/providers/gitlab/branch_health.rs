@@ -0,0 +1,271 @@
+use chrono::{DateTime, Utc};
+
+use super::links::pipeline_id_to_url;
+use super::types::GitLabPipeline;
+use crate::insights::{BranchHealth, BranchIncident};
+use crate::providers::stats::{calculate_percentiles, cmp_f64};
+
+/// Number of incidents listed in `longest_incidents`.
+const LONGEST_INCIDENTS: usize = 5;
+
+/// Calculates broken-branch incidents and time to recovery for a single ref.
+///
+/// Pipelines are ordered by creation time. An incident starts with the first failed
+/// pipeline after a successful one and ends with the next successful pipeline; failures
+/// before the first success are ignored because their start is unknown. Incidents are
+/// timed from the finish of the first failed pipeline to the finish of the fixing one.
+/// An incident that is still open is timed up to the last analyzed pipeline and counts
+/// towards red time but not towards MTTR.
+///
+/// # Arguments
+///
+/// * `pipelines` - Pipelines of a single ref, forming a contiguous timeline (all recent
+///   pipelines, not a balanced or stratified sample)
+/// * `ref_` - The ref the pipelines ran on
+/// * `base_url` - GitLab instance base URL for pipeline links
+/// * `project_path` - Project path for pipeline links
+///
+/// # Returns
+///
+/// Branch health, or `None` if no successful or failed pipeline has a timestamp.
+pub(super) fn calculate_branch_health(
+    pipelines: &[GitLabPipeline],
+    ref_: &str,
+    base_url: &str,
+    project_path: &str,
+) -> Option<BranchHealth> {
+    let mut timeline: Vec<(DateTime<Utc>, &GitLabPipeline)> = pipelines
+        .iter()
+        .filter(|p| p.status == "success" || p.status == "failed")
+        .filter_map(|p| p.created_at.map(|created| (created, p)))
+        .collect();
+    timeline.sort_by_key(|(created, _)| *created);
+
+    let observed_start = finish_time(timeline.first()?.1)?;
+    let observed_end = timeline.iter().filter_map(|(_, p)| finish_time(p)).max()?;
+
+    let mut incidents = Vec::new();
+    let mut open: Option<BranchIncident> = None;
+    let mut seen_success = false;
+
+    for (_, pipeline) in &timeline {
+        let Some(at) = finish_time(pipeline) else {
+            continue;
+        };
+
+        if pipeline.status == "success" {
+            if let Some(mut incident) = open.take() {
+                incident.resolved_at = Some(at);
+                incident.fixed_by_pipeline =
+                    Some(pipeline_id_to_url(base_url, project_path, &pipeline.id));
+                incidents.push(incident);
+            }
+            seen_success = true;
+        } else if let Some(incident) = &mut open {
+            if !incident.commits.contains(&pipeline.sha) {
+                incident.commits.push(pipeline.sha.clone());
+            }
+        } else if seen_success {
+            open = Some(BranchIncident {
+                started_at: at,
+                resolved_at: None,
+                duration: 0.0,
+                commits: vec![pipeline.sha.clone()],
+                culprit_job: culprit_job(pipeline),
+                first_failed_pipeline: pipeline_id_to_url(base_url, project_path, &pipeline.id),
+                fixed_by_pipeline: None,
+            });
+        }
+    }
+    incidents.extend(open);
+
+    #[allow(clippy::cast_precision_loss)]
+    for incident in &mut incidents {
+        let end = incident.resolved_at.unwrap_or(observed_end);
+        incident.duration = ((end - incident.started_at).num_seconds() as f64).max(0.0);
+    }
+
+    let recovery_times: Vec<f64> = incidents
+        .iter()
+        .filter(|incident| incident.resolved_at.is_some())
+        .map(|incident| incident.duration)
+        .collect();
+    let (mttr_p50, mttr_p95, _) = calculate_percentiles(&recovery_times);
+
+    let red_time: f64 = incidents.iter().map(|incident| incident.duration).sum();
+    #[allow(clippy::cast_precision_loss)]
+    let observed = (observed_end - observed_start).num_seconds() as f64;
+    let red_time_percentage = if observed > 0.0 {
+        (red_time / observed * 100.0).min(100.0)
+    } else {
+        0.0
+    };
+
+    let total_incidents = incidents.len();
+    incidents.sort_by(|a, b| cmp_f64(b.duration, a.duration));
+    incidents.truncate(LONGEST_INCIDENTS);
+
+    Some(BranchHealth {
+        ref_: ref_.to_string(),
+        total_incidents,
+        mttr_p50,
+        mttr_p95,
+        red_time_percentage,
+        longest_incidents: incidents,
+    })
+}
+
+fn finish_time(pipeline: &GitLabPipeline) -> Option<DateTime<Utc>> {
+    pipeline.finished_at.or(pipeline.created_at)
+}
+
+/// Returns the failed job that ran earliest in stage order.
+fn culprit_job(pipeline: &GitLabPipeline) -> Option<String> {
+    let stage_index = |stage: &str| {
+        pipeline
+            .stages
+            .iter()
+            .position(|s| s == stage)
+            .unwrap_or(usize::MAX)
+    };
+
+    pipeline
+        .jobs
+        .iter()
+        .filter(|job| job.status == "FAILED" && !job.retried)
+        .min_by(|a, b| {
+            stage_index(&a.stage)
+                .cmp(&stage_index(&b.stage))
+                .then_with(|| a.name.cmp(&b.name))
+        })
+        .map(|job| job.name.clone())
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use crate::providers::gitlab::types::GitLabJob;
+    use chrono::{Duration, TimeZone};
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap() + Duration::minutes(minutes)
+    }
+
+    fn create_job(name: &str, stage: &str, status: &str) -> GitLabJob {
+        GitLabJob {
+            id: format!("gid://gitlab/Ci::Job/{name}"),
+            name: name.to_string(),
+            stage: stage.to_string(),
+            duration: 60.0,
+            status: status.to_string(),
+            retried: false,
+            needs: None,
+            tags: vec![],
+            runner: None,
+            test_cases: vec![],
+        }
+    }
+
+    fn create_pipeline(id: u32, status: &str, minute: i64, jobs: Vec<GitLabJob>) -> GitLabPipeline {
+        GitLabPipeline {
+            id: format!("gid://gitlab/Ci::Pipeline/{id}"),
+            ref_: "main".to_string(),
+            sha: format!("sha{id}"),
+            weight: 1.0,
+            source: "push".to_string(),
+            status: status.to_string(),
+            duration: 60,
+            created_at: Some(at(minute)),
+            finished_at: Some(at(minute + 1)),
+            merge_request: None,
+            stages: vec!["build".to_string(), "test".to_string()],
+            jobs,
+        }
+    }
+
+    fn health(pipelines: &[GitLabPipeline]) -> BranchHealth {
+        calculate_branch_health(pipelines, "main", "https://gitlab.com", "org/repo").unwrap()
+    }
+
+    mod calculate_branch_health {
+        use super::*;
+
+        #[test]
+        fn returns_none_without_pipelines() {
+            assert!(
+                calculate_branch_health(&[], "main", "https://gitlab.com", "org/repo").is_none()
+            );
+        }
+
+        #[test]
+        fn detects_incident_from_failure_to_next_success() {
+            let pipelines = vec![
+                create_pipeline(1, "success", 0, vec![]),
+                create_pipeline(2, "failed", 10, vec![create_job("unit", "test", "FAILED")]),
+                create_pipeline(3, "failed", 20, vec![]),
+                create_pipeline(4, "success", 40, vec![]),
+            ];
+
+            let health = health(&pipelines);
+
+            assert_eq!(health.total_incidents, 1);
+            let incident = &health.longest_incidents[0];
+            assert_eq!(incident.duration, 1800.0);
+            assert_eq!(incident.commits, vec!["sha2", "sha3"]);
+            assert_eq!(incident.culprit_job.as_deref(), Some("unit"));
+            assert_eq!(
+                incident.first_failed_pipeline,
+                "https://gitlab.com/org/repo/-/pipelines/2"
+            );
+            assert_eq!(
+                incident.fixed_by_pipeline.as_deref(),
+                Some("https://gitlab.com/org/repo/-/pipelines/4")
+            );
+            assert_eq!(health.mttr_p50, 1800.0);
+            assert_eq!(health.red_time_percentage, 75.0);
+        }
+
+        #[test]
+        fn ignores_failures_before_first_success() {
+            let pipelines = vec![
+                create_pipeline(1, "failed", 0, vec![]),
+                create_pipeline(2, "success", 10, vec![]),
+            ];
+
+            assert_eq!(health(&pipelines).total_incidents, 0);
+        }
+
+        #[test]
+        fn excludes_open_incident_from_mttr() {
+            let pipelines = vec![
+                create_pipeline(1, "success", 0, vec![]),
+                create_pipeline(2, "failed", 10, vec![]),
+                create_pipeline(3, "failed", 20, vec![]),
+            ];
+
+            let health = health(&pipelines);
+
+            assert_eq!(health.total_incidents, 1);
+            assert!(health.longest_incidents[0].resolved_at.is_none());
+            assert_eq!(health.longest_incidents[0].duration, 600.0);
+            assert_eq!(health.mttr_p50, 0.0);
+            assert_eq!(health.red_time_percentage, 50.0);
+        }
+
+        #[test]
+        fn picks_culprit_in_stage_order() {
+            let pipeline = create_pipeline(
+                1,
+                "failed",
+                0,
+                vec![
+                    create_job("e2e", "test", "FAILED"),
+                    create_job("compile", "build", "FAILED"),
+                ],
+            );
+
+            assert_eq!(culprit_job(&pipeline).as_deref(), Some("compile"));
+        }
+    }
+}
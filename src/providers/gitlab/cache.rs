@@ -1,45 +1,320 @@
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
+use chrono::{DateTime, Duration, Utc};
 use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-use crate::error::Result;
+use crate::error::{CILensError, Result};
 
-use super::types::{GitLabJob, GitLabPipeline};
+use super::sampling::Sampling;
+use super::storage::{self, FileLock};
+use super::test_cache::TestReportCache;
+use super::trace_cache::TraceCache;
+use super::types::{GitLabJob, GitLabMergeRequest, GitLabPipeline};
 
 /// Cached pipeline data.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct CachedPipeline {
     /// Cached job data
     jobs: Vec<GitLabJob>,
+    /// Pipeline metadata for incremental fetching (absent in caches written before it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    metadata: Option<PipelineMetadata>,
+    /// When a run last used the entry (absent in caches written before it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cached_at: Option<DateTime<Utc>>,
+}
+
+/// Cached pipeline metadata, everything but the jobs and the sampling weight.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PipelineMetadata {
+    ref_: String,
+    source: String,
+    sha: String,
+    status: String,
+    duration: usize,
+    created_at: Option<DateTime<Utc>>,
+    finished_at: Option<DateTime<Utc>>,
+    merge_request: Option<GitLabMergeRequest>,
+    stages: Vec<String>,
+}
+
+/// Filters a pipeline fetch was made with.
+///
+/// Cached pipelines can only be extended incrementally by a fetch with the same filters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FetchScope {
+    /// Git ref filter
+    pub ref_: Option<String>,
+    /// Sampling strategy the pipelines were chosen with
+    pub sampling: Sampling,
+    /// Maximum number of pipelines
+    pub limit: usize,
+    /// Start date filter
+    pub updated_after: Option<DateTime<Utc>>,
+    /// End date filter (fetches with one are never continued incrementally)
+    #[serde(default)]
+    pub updated_before: Option<DateTime<Utc>>,
+}
+
+impl FetchScope {
+    /// Whether a cached pipeline could be returned by a fetch with these filters.
+    ///
+    /// Finished pipelines are last updated when they finish, so the finish time (or the
+    /// creation time, if unknown) stands in for the update time the API filters on.
+    pub(super) fn contains(&self, pipeline: &GitLabPipeline) -> bool {
+        let updated_at = pipeline.finished_at.or(pipeline.created_at);
+
+        self.ref_.as_ref().is_none_or(|ref_| pipeline.ref_ == *ref_)
+            && self
+                .updated_after
+                .is_none_or(|after| updated_at.is_none_or(|updated_at| updated_at >= after))
+            && self
+                .updated_before
+                .is_none_or(|before| updated_at.is_none_or(|updated_at| updated_at <= before))
+    }
+}
+
+/// Latest pipeline update seen by a fetch, so the next one only asks for newer updates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighWaterMark {
+    /// Filters the cached pipelines were fetched with
+    pub scope: FetchScope,
+    /// Latest `updatedAt` of the fetched pipelines
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Version of the cache file layout written by this build.
+///
+/// - 0: bare map of pipeline ID to cached jobs
+/// - 1: pipelines with metadata, and the high-water mark
+/// - 2: envelope naming the cilens version, GitLab instance and project
+///
+/// Older files are migrated forward when loaded; newer ones are ignored.
+const SCHEMA_VERSION: u32 = 2;
+
+/// Cache lookups of the last run that saved the cache.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunStats {
+    /// When the run saved the cache
+    pub at: DateTime<Utc>,
+    /// Pipelines whose jobs were read from the cache
+    pub hits: usize,
+    /// Pipelines whose jobs had to be fetched
+    pub misses: usize,
+}
+
+impl RunStats {
+    /// Share of pipelines served from the cache (0-100), `None` if nothing was looked up.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        #[allow(clippy::cast_precision_loss)]
+        (lookups > 0).then(|| self.hits as f64 / lookups as f64 * 100.0)
+    }
+}
+
+/// How long job cache entries are kept.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CacheRetention {
+    /// Keep entries of earlier runs (e.g., with another `--ref` or `--limit`) instead of
+    /// replacing them with the current run's pipelines
+    pub keep: bool,
+    /// Drop entries no run has used for longer than this
+    pub max_age: Option<Duration>,
+    /// Keep at most this many entries, dropping the least recently used
+    pub max_entries: Option<usize>,
+}
+
+impl CacheRetention {
+    /// Drops entries past the maximum age, then the least recently used entries past the
+    /// maximum count. Entries without a timestamp count as the oldest.
+    ///
+    /// # Returns
+    ///
+    /// The number of dropped entries.
+    fn apply(&self, pipelines: &mut HashMap<String, CachedPipeline>, now: DateTime<Utc>) -> usize {
+        let before = pipelines.len();
+
+        if let Some(max_age) = self.max_age {
+            pipelines.retain(|_, cached| cached.cached_at.is_some_and(|at| now - at <= max_age));
+        }
+
+        if let Some(max_entries) = self.max_entries {
+            if pipelines.len() > max_entries {
+                let mut entries: Vec<(String, Option<DateTime<Utc>>)> = pipelines
+                    .iter()
+                    .map(|(id, cached)| (id.clone(), cached.cached_at))
+                    .collect();
+                entries.sort_by_key(|(id, cached_at)| (Reverse(*cached_at), Reverse(id.clone())));
+                for (id, _) in entries.into_iter().skip(max_entries) {
+                    pipelines.remove(&id);
+                }
+            }
+        }
+
+        before - pipelines.len()
+    }
+}
+
+/// Size, age and usage of one project's cache, as reported by [`JobCache::stats`].
+#[derive(Debug)]
+pub struct CacheStats {
+    /// Job cache file
+    pub cache_file: PathBuf,
+    /// Project the cache belongs to (the file name for caches that don't record it)
+    pub project: String,
+    /// GitLab instance the cache belongs to, if recorded
+    pub base_url: Option<String>,
+    /// Number of cached pipelines
+    pub entries: usize,
+    /// Size of the job cache file in bytes
+    pub job_cache_bytes: u64,
+    /// Size of the test report cache in bytes
+    pub test_cache_bytes: u64,
+    /// Size of the cached job logs in bytes
+    pub trace_cache_bytes: u64,
+    /// When the least recently used entry was last used
+    pub oldest_entry: Option<DateTime<Utc>>,
+    /// When the most recently used entry was last used
+    pub newest_entry: Option<DateTime<Utc>>,
+    /// Cache lookups of the last run
+    pub last_run: Option<RunStats>,
+}
+
+impl CacheStats {
+    /// Total size of the project's caches in bytes.
+    pub fn total_bytes(&self) -> u64 {
+        self.job_cache_bytes + self.test_cache_bytes + self.trace_cache_bytes
+    }
+}
+
+/// Entries dropped from one project's cache by [`JobCache::prune`].
+#[derive(Debug)]
+pub struct PruneResult {
+    /// Project the cache belongs to (the file name for caches that don't record it)
+    pub project: String,
+    /// Number of dropped entries
+    pub dropped: usize,
+    /// Number of entries left
+    pub remaining: usize,
+}
+
+/// On-disk cache layout.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    /// cilens version that wrote the file (unknown before schema 2)
+    #[serde(default)]
+    cilens_version: Option<String>,
+    /// Layout version, see [`SCHEMA_VERSION`]
+    schema_version: u32,
+    /// GitLab instance the pipelines were fetched from (unknown before schema 2)
+    #[serde(default)]
+    base_url: Option<String>,
+    /// Project the pipelines belong to (unknown before schema 2)
+    #[serde(default)]
+    project: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    high_water_mark: Option<HighWaterMark>,
+    /// Cache lookups of the last run that saved the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_run: Option<RunStats>,
+    pipelines: HashMap<String, CachedPipeline>,
+}
+
+impl CacheFile {
+    /// Returns the GitLab instance or project this file belongs to, if it isn't the
+    /// given one. Files from before schema 2 don't record theirs and match any.
+    fn owner_mismatch(&self, base_url: &str, project: &str) -> Option<String> {
+        match (&self.base_url, &self.project) {
+            (Some(url), _) if url != base_url => Some(url.clone()),
+            (_, Some(path)) if path != project => Some(path.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// A cache file read from disk and migrated to the current schema.
+struct LoadedCache {
+    /// Readable contents
+    file: CacheFile,
+    /// Schema version the file was written with
+    schema_version: u32,
+    /// IDs of entries that could not be read, with the reason
+    corrupt: Vec<(String, String)>,
+}
+
+/// Result of verifying a cache file with [`JobCache::verify`].
+#[derive(Debug)]
+pub struct CacheVerification {
+    /// Verified cache file
+    pub cache_file: PathBuf,
+    /// Schema version the file was written with
+    pub schema_version: u32,
+    /// cilens version that wrote the file, if recorded
+    pub cilens_version: Option<String>,
+    /// GitLab instance the file belongs to, if recorded
+    pub base_url: Option<String>,
+    /// Project the file belongs to, if recorded
+    pub project: Option<String>,
+    /// Number of readable pipeline entries
+    pub pipelines: usize,
+    /// Pipeline IDs of unreadable entries, with the reason
+    pub corrupt: Vec<(String, String)>,
+    /// Why the whole file could not be read, if it couldn't
+    pub error: Option<String>,
+}
+
+impl CacheVerification {
+    /// Whether the file was readable and has no corrupt entries.
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none() && self.corrupt.is_empty()
+    }
 }
 
 /// Job cache for GitLab pipelines.
 ///
-/// Caches job data for completed pipelines to avoid redundant API calls.
-/// Uses per-project cache files in platform-specific cache directories:
-/// - Linux: `~/.cache/cilens/gitlab/{project-slug}.json`
-/// - macOS: `~/Library/Caches/cilens/gitlab/{project-slug}.json`
+/// Caches job data and metadata for completed pipelines to avoid redundant API calls,
+/// together with the high-water mark of the last fetch for incremental fetching.
+/// Uses gzip-compressed per-project cache files in platform-specific cache directories:
+/// - Linux: `~/.cache/cilens/gitlab/{project-slug}@{instance-slug}.json.gz`
+/// - macOS: `~/Library/Caches/cilens/gitlab/{project-slug}@{instance-slug}.json.gz`
 ///
 /// Cache is loaded into memory at startup and immutable - new cache is derived from final pipeline data.
+/// Saving replaces the file atomically while holding a lock, so parallel runs on the
+/// same project never see or leave a half-written cache.
 pub struct JobCache {
     cache_file: PathBuf,
+    /// Cache file of an older cilens the cache was loaded from, removed on save
+    migrated_from: Option<PathBuf>,
+    base_url: String,
+    project: String,
     pipelines: HashMap<String, CachedPipeline>,
+    high_water_mark: Option<HighWaterMark>,
+    retention: CacheRetention,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
     enabled: bool,
 }
 
 impl JobCache {
     /// Creates a new job cache instance.
     ///
-    /// Loads existing cache from disk if available. All cache data is kept in memory
-    /// for fast lookups.
+    /// Loads existing cache from disk if available, migrating older layouts and
+    /// dropping corrupt entries. A cache written for another GitLab instance or project
+    /// that maps to the same file is ignored. All cache data is kept in memory for fast
+    /// lookups.
     ///
     /// # Arguments
     ///
     /// * `project_path` - GitLab project path (e.g., "group/project")
+    /// * `base_url` - GitLab instance base URL the pipelines are fetched from
     /// * `enabled` - Whether caching is enabled
+    /// * `retention` - Which entries to keep when saving
     ///
     /// # Returns
     ///
@@ -48,54 +323,92 @@ impl JobCache {
     /// # Errors
     ///
     /// Returns error if cache directory cannot be determined or created.
-    pub fn new(project_path: &str, enabled: bool) -> Result<Self> {
+    pub fn new(
+        project_path: &str,
+        base_url: &str,
+        enabled: bool,
+        retention: CacheRetention,
+    ) -> Result<Self> {
+        let base_url = base_url.trim_end_matches('/').to_string();
+
         if !enabled {
             debug!("Job cache disabled");
             return Ok(Self {
                 cache_file: PathBuf::new(),
+                migrated_from: None,
+                base_url,
+                project: project_path.to_string(),
                 pipelines: HashMap::new(),
+                high_water_mark: None,
+                retention,
+                hits: AtomicUsize::new(0),
+                misses: AtomicUsize::new(0),
                 enabled: false,
             });
         }
 
         // Use platform-specific cache directory
-        let cache_dir = dirs::cache_dir()
-            .ok_or_else(|| crate::error::CILensError::Cache("No cache directory found".into()))?
-            .join("cilens")
-            .join("gitlab");
-
+        let cache_dir = cache_dir()?;
         fs::create_dir_all(&cache_dir)?;
 
-        // Generate cache filename from project path (e.g., "group/project" → "group-project.json")
-        let cache_filename = project_path.replace('/', "-") + ".json";
-        let cache_file = cache_dir.join(cache_filename);
+        let cache = Self::open(&cache_dir, base_url, project_path, retention);
+        info!("Job cache enabled at: {}", cache.cache_file.display());
+
+        Ok(cache)
+    }
+
+    /// Loads the cache of a project on a GitLab instance from `cache_dir`.
+    ///
+    /// The file is named after both (e.g., "group/project" on <https://gitlab.com> →
+    /// "group-project@gitlab.com.json.gz"). Without one, the file older cilens versions
+    /// named after the project only is loaded if it belongs to the instance, and
+    /// replaced on the next save.
+    fn open(
+        cache_dir: &Path,
+        base_url: String,
+        project_path: &str,
+        retention: CacheRetention,
+    ) -> Self {
+        let cache_file = cache_dir.join(cache_filename(&base_url, project_path));
+        let unscoped_file = cache_dir.join(unscoped_cache_filename(project_path));
 
         // Load existing cache from disk (immutable after loading)
-        let pipelines = if cache_file.exists() {
-            fs::read_to_string(&cache_file)
-                .ok()
-                .and_then(|content| serde_json::from_str(&content).ok())
-                .inspect(|_| debug!("Loaded cache from: {}", cache_file.display()))
-                .unwrap_or_else(|| {
-                    warn!("Failed to load cache, starting with empty cache");
-                    HashMap::new()
-                })
-        } else {
-            HashMap::new()
+        let (cache, migrated_from) = match existing_cache_file(&cache_file) {
+            Some(existing) => (load_cache(&existing, &base_url, project_path), None),
+            None => match existing_cache_file(&unscoped_file)
+                .and_then(|existing| load_cache(&existing, &base_url, project_path))
+            {
+                Some(cache) => {
+                    info!(
+                        "Migrating cache {} to {}",
+                        unscoped_file.display(),
+                        cache_file.display()
+                    );
+                    (Some(cache), Some(unscoped_file))
+                }
+                None => (None, None),
+            },
         };
+        let cache = cache.unwrap_or_default();
 
-        info!("Job cache enabled at: {}", cache_file.display());
-
-        Ok(Self {
+        Self {
             cache_file,
-            pipelines,
+            migrated_from,
+            base_url,
+            project: project_path.to_string(),
+            pipelines: cache.pipelines,
+            high_water_mark: cache.high_water_mark,
+            retention,
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
             enabled: true,
-        })
+        }
     }
 
     /// Attempts to retrieve cached jobs for a pipeline.
     ///
     /// Performs in-memory lookup for fast access. Cache is immutable after loading.
+    /// Hits and misses are counted for the run statistics saved with the cache.
     ///
     /// Returns `None` if:
     /// - Caching is disabled
@@ -109,79 +422,640 @@ impl JobCache {
             return None;
         }
 
-        self.pipelines.get(pipeline_id).map(|cached| {
+        let jobs = self.pipelines.get(pipeline_id).map(|cached| {
             debug!("Cache hit for pipeline {pipeline_id}");
             cached.jobs.clone()
-        })
+        });
+        let counter = if jobs.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+
+        jobs
+    }
+
+    /// Counts pipelines served from or bypassing the cache without a [`JobCache::get`],
+    /// e.g. when merging an incremental fetch.
+    pub(super) fn record_lookups(&self, hits: usize, misses: usize) {
+        self.hits.fetch_add(hits, Ordering::Relaxed);
+        self.misses.fetch_add(misses, Ordering::Relaxed);
+    }
+
+    /// Returns the latest pipeline update seen by the last fetch with the same filters.
+    ///
+    /// Returns `None` if caching is disabled, the last fetch used different filters, or
+    /// any cached pipeline lacks the metadata needed to rebuild it.
+    pub(super) fn high_water_mark(&self, scope: &FetchScope) -> Option<DateTime<Utc>> {
+        if !self.enabled || self.pipelines.values().any(|p| p.metadata.is_none()) {
+            return None;
+        }
+
+        self.high_water_mark
+            .as_ref()
+            .filter(|mark| mark.scope == *scope)
+            .map(|mark| mark.updated_at)
+    }
+
+    /// Rebuilds all cached pipelines from their metadata and jobs.
+    ///
+    /// Test cases are not part of the job cache and have to be attached separately.
+    pub(super) fn pipelines(&self) -> Vec<GitLabPipeline> {
+        if !self.enabled {
+            return Vec::new();
+        }
+
+        self.pipelines
+            .iter()
+            .filter_map(|(id, cached)| {
+                let metadata = cached.metadata.clone()?;
+                Some(GitLabPipeline {
+                    id: id.clone(),
+                    ref_: metadata.ref_,
+                    source: metadata.source,
+                    sha: metadata.sha,
+                    status: metadata.status,
+                    duration: metadata.duration,
+                    created_at: metadata.created_at,
+                    finished_at: metadata.finished_at,
+                    merge_request: metadata.merge_request,
+                    stages: metadata.stages,
+                    jobs: cached.jobs.clone(),
+                    weight: 1.0,
+                })
+            })
+            .collect()
     }
 
     /// Derives cache from fetched pipelines and saves to disk.
     ///
-    /// Transforms the pipeline data into cache format and persists it.
-    /// Client already filters to only completed pipelines (success/failed).
+    /// Transforms the pipeline data into cache format and persists it, together with
+    /// the hit rate of this run. Entries of earlier runs are only kept with
+    /// `CacheRetention::keep`, and the retention limits are applied before writing.
+    /// Client already filters to only completed pipelines (success/failed/canceled).
+    ///
+    /// The cache file is locked while it is rebuilt. With `keep`, the entries are
+    /// re-read under the lock, so pipelines saved by a parallel run since this one
+    /// started are kept as well.
     ///
     /// # Arguments
     ///
     /// * `pipelines` - Fetched pipeline data to cache
-    pub fn save_pipelines(&self, pipelines: &[GitLabPipeline]) -> Result<()> {
+    /// * `high_water_mark` - Latest pipeline update seen, `None` if the fetch can't be
+    ///   continued incrementally (e.g., it had an end date)
+    pub fn save_pipelines(
+        &self,
+        pipelines: &[GitLabPipeline],
+        high_water_mark: Option<&HighWaterMark>,
+    ) -> Result<()> {
         if !self.enabled {
             return Ok(());
         }
 
+        let _lock = FileLock::acquire(&self.cache_file)?;
+        let now = Utc::now();
+        let mut cached = if self.retention.keep {
+            self.current_pipelines()
+        } else {
+            HashMap::new()
+        };
+
         // Derive cache from pipeline data - keyed by pipeline ID only
-        let cache: HashMap<String, CachedPipeline> = pipelines
-            .iter()
-            .map(|pipeline| {
-                (
-                    pipeline.id.clone(),
-                    CachedPipeline {
-                        jobs: pipeline.jobs.clone(),
-                    },
-                )
-            })
-            .collect();
+        for pipeline in pipelines {
+            cached.insert(
+                pipeline.id.clone(),
+                CachedPipeline {
+                    jobs: pipeline.jobs.clone(),
+                    metadata: Some(PipelineMetadata {
+                        ref_: pipeline.ref_.clone(),
+                        source: pipeline.source.clone(),
+                        sha: pipeline.sha.clone(),
+                        status: pipeline.status.clone(),
+                        duration: pipeline.duration,
+                        created_at: pipeline.created_at,
+                        finished_at: pipeline.finished_at,
+                        merge_request: pipeline.merge_request.clone(),
+                        stages: pipeline.stages.clone(),
+                    }),
+                    cached_at: Some(now),
+                },
+            );
+        }
+
+        let dropped = self.retention.apply(&mut cached, now);
+        if dropped > 0 {
+            debug!("Dropped {dropped} cache entries past the retention limits");
+        }
+
+        // The next fetch can only continue from the mark if all of this run's pipelines are kept
+        let complete = pipelines.iter().all(|p| cached.contains_key(&p.id));
+        let cache = CacheFile {
+            cilens_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            schema_version: SCHEMA_VERSION,
+            base_url: Some(self.base_url.clone()),
+            project: Some(self.project.clone()),
+            high_water_mark: high_water_mark.filter(|_| complete).cloned(),
+            last_run: Some(RunStats {
+                at: now,
+                hits: self.hits.load(Ordering::Relaxed),
+                misses: self.misses.load(Ordering::Relaxed),
+            }),
+            pipelines: cached,
+        };
 
-        // Write to disk
-        let content = serde_json::to_string(&cache)?;
-        fs::write(&self.cache_file, content)?;
+        write_cache_file(&self.cache_file, &cache)?;
+        if let Some(migrated_from) = &self.migrated_from {
+            remove_cache_file(migrated_from)?;
+        }
 
         debug!(
             "Saved {} pipelines to cache: {}",
-            cache.len(),
+            cache.pipelines.len(),
             self.cache_file.display()
         );
 
         Ok(())
     }
 
+    /// Returns the entries currently on disk, falling back to those loaded at startup
+    /// if the file was removed or can no longer be read.
+    fn current_pipelines(&self) -> HashMap<String, CachedPipeline> {
+        existing_cache_file(&self.cache_file)
+            .and_then(|existing| read_cache_file(&existing).ok())
+            .filter(|loaded| {
+                loaded
+                    .file
+                    .owner_mismatch(&self.base_url, &self.project)
+                    .is_none()
+            })
+            .map_or_else(|| self.pipelines.clone(), |loaded| loaded.file.pipelines)
+    }
+
+    /// Verifies cache files, reporting unreadable files and corrupt entries.
+    ///
+    /// # Arguments
+    ///
+    /// * `project_path` - Project whose cache files to verify, or `None` for all projects
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache directory cannot be determined or listed.
+    pub fn verify(project_path: Option<&str>) -> Result<Vec<CacheVerification>> {
+        Ok(existing_cache_files(project_path)?
+            .into_iter()
+            .map(|cache_file| verify_cache_file(&cache_file))
+            .collect())
+    }
+
+    /// Reports size, age and hit rate of the cache of one or all projects.
+    ///
+    /// Unreadable cache files are skipped with a warning (see [`JobCache::verify`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `project_path` - Project whose cache to report, or `None` for all projects
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache directory cannot be determined or listed.
+    pub fn stats(project_path: Option<&str>) -> Result<Vec<CacheStats>> {
+        let stats = existing_cache_files(project_path)?
+            .into_iter()
+            .filter_map(|cache_file| {
+                let loaded = read_cache_file(&cache_file)
+                    .inspect_err(|e| warn!("Skipping {}: {e}", cache_file.display()))
+                    .ok()?;
+                let project = loaded
+                    .file
+                    .project
+                    .clone()
+                    .unwrap_or_else(|| storage::file_stem(&cache_file));
+                let entry_ages = loaded.file.pipelines.values().filter_map(|p| p.cached_at);
+                // The other caches are named after the job cache, so they share its instance
+                let cache_stem = storage::file_stem(&cache_file);
+
+                Some(CacheStats {
+                    job_cache_bytes: fs::metadata(&cache_file).map_or(0, |m| m.len()),
+                    test_cache_bytes: TestReportCache::disk_usage(&cache_stem),
+                    trace_cache_bytes: TraceCache::disk_usage(&cache_stem),
+                    oldest_entry: entry_ages.clone().min(),
+                    newest_entry: entry_ages.max(),
+                    cache_file,
+                    project,
+                    base_url: loaded.file.base_url,
+                    entries: loaded.file.pipelines.len(),
+                    last_run: loaded.file.last_run,
+                })
+            })
+            .collect();
+
+        Ok(stats)
+    }
+
+    /// Drops cache entries past the retention limits from one or all projects.
+    ///
+    /// A project's high-water mark is discarded when entries are dropped, so its next
+    /// run fetches all pipelines again.
+    ///
+    /// # Arguments
+    ///
+    /// * `project_path` - Project whose cache to prune, or `None` for all projects
+    /// * `retention` - Maximum entry age and count
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache directory cannot be listed or a pruned cache file
+    /// cannot be written. Unreadable cache files are skipped with a warning.
+    pub fn prune(
+        project_path: Option<&str>,
+        retention: CacheRetention,
+    ) -> Result<Vec<PruneResult>> {
+        let now = Utc::now();
+        let mut results = Vec::new();
+
+        for cache_file in existing_cache_files(project_path)? {
+            let _lock = FileLock::acquire(&cache_file)?;
+            let mut loaded = match read_cache_file(&cache_file) {
+                Ok(loaded) => loaded,
+                Err(e) => {
+                    warn!("Skipping {}: {e}", cache_file.display());
+                    continue;
+                }
+            };
+
+            let dropped = retention.apply(&mut loaded.file.pipelines, now);
+            if dropped > 0 {
+                loaded.file.high_water_mark = None;
+                loaded.file.cilens_version = Some(env!("CARGO_PKG_VERSION").to_string());
+                write_cache_file(&cache_file, &loaded.file)?;
+            }
+
+            results.push(PruneResult {
+                project: loaded
+                    .file
+                    .project
+                    .unwrap_or_else(|| storage::file_stem(&cache_file)),
+                dropped,
+                remaining: loaded.file.pipelines.len(),
+            });
+        }
+
+        Ok(results)
+    }
+
+    /// Clears the job, test report and job log caches of all projects.
+    ///
+    /// Job cache files are removed one by one while holding their lock, then the rest of
+    /// the cache directory.
+    ///
+    /// # Returns
+    ///
+    /// The removed cache files and job log directories, without lock files.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a cache file cannot be locked or removed, or the cache
+    /// directory cannot be listed or removed.
+    pub fn clear_all() -> Result<Vec<PathBuf>> {
+        let cache_dir = cache_dir()?;
+        if !cache_dir.exists() {
+            info!("No cache found");
+            return Ok(Vec::new());
+        }
+
+        let mut removed: Vec<PathBuf> = fs::read_dir(&cache_dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                let file_name = path.to_string_lossy();
+                !file_name.ends_with(".lock") && !file_name.ends_with(".tmp")
+            })
+            .collect();
+        removed.sort();
+
+        for cache_file in list_cache_files(&cache_dir)? {
+            remove_cache_file(&cache_file)?;
+        }
+        fs::remove_dir_all(&cache_dir)?;
+        info!("Cache cleared: {}", cache_dir.display());
+
+        Ok(removed)
+    }
+
     /// Clears cached data for a specific project.
     ///
-    /// Removes the project's cache file from disk.
+    /// Removes the project's cache files of all GitLab instances from disk, including
+    /// uncompressed ones and those named after the project only, written by an older
+    /// cilens.
     ///
     /// # Arguments
     ///
     /// * `project_path` - GitLab project path (e.g., "group/project")
     ///
+    /// # Returns
+    ///
+    /// The removed cache files.
+    ///
     /// # Errors
     ///
-    /// Returns an error if cache file cannot be removed.
-    pub fn clear_project_cache(project_path: &str) -> Result<()> {
-        let cache_dir = dirs::cache_dir()
-            .ok_or_else(|| crate::error::CILensError::Cache("No cache directory found".into()))?
-            .join("cilens")
-            .join("gitlab");
+    /// Returns an error if the cache directory cannot be listed or a cache file cannot
+    /// be removed.
+    pub fn clear_project_cache(project_path: &str) -> Result<Vec<PathBuf>> {
+        let cache_files = existing_cache_files(Some(project_path))?;
+        for cache_file in &cache_files {
+            remove_cache_file(cache_file)?;
+            info!("Cache cleared: {}", cache_file.display());
+        }
 
-        let cache_filename = project_path.replace('/', "-") + ".json";
-        let cache_file = cache_dir.join(cache_filename);
+        Ok(cache_files)
+    }
+}
 
-        if cache_file.exists() {
-            fs::remove_file(&cache_file)?;
-            info!("Cache cleared: {}", cache_file.display());
-        } else {
-            info!("No cache file found for project: {project_path}");
+/// Returns the platform-specific directory holding the GitLab caches.
+pub(super) fn cache_dir() -> Result<PathBuf> {
+    Ok(dirs::cache_dir()
+        .ok_or_else(|| CILensError::Cache("No cache directory found".into()))?
+        .join("cilens")
+        .join("gitlab"))
+}
+
+/// Returns the cache file name of a project on a GitLab instance, e.g.
+/// `group-project@gitlab.example.com-8443.json.gz` for `group/project` on
+/// <https://gitlab.example.com:8443>.
+fn cache_filename(base_url: &str, project_path: &str) -> String {
+    cache_stem(base_url, project_path) + ".json.gz"
+}
+
+/// Returns the name the caches of a project on a GitLab instance are stored under, e.g.
+/// `group-project@gitlab.example.com-8443`. The test report and job log caches append
+/// their own suffix to it.
+///
+/// Project paths never contain `@`, so the project's caches of all instances can be
+/// found by their prefix.
+pub(super) fn cache_stem(base_url: &str, project_path: &str) -> String {
+    let instance: String = base_url
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_end_matches('/')
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+
+    format!("{}@{instance}", project_slug(project_path))
+}
+
+/// Returns the cache file name older cilens versions used for a project on any instance.
+fn unscoped_cache_filename(project_path: &str) -> String {
+    project_slug(project_path) + ".json.gz"
+}
+
+fn project_slug(project_path: &str) -> String {
+    project_path.replace('/', "-")
+}
+
+/// Whether a cache file belongs to a project, on any GitLab instance.
+fn is_project_cache_file(cache_file: &Path, project_path: &str) -> bool {
+    is_project_cache_stem(&storage::file_stem(cache_file), project_path)
+}
+
+/// Whether a [`cache_stem`] belongs to a project, on any GitLab instance, including
+/// the project slug older cilens versions used alone.
+pub(super) fn is_project_cache_stem(stem: &str, project_path: &str) -> bool {
+    let slug = project_slug(project_path);
+
+    stem == slug
+        || stem
+            .strip_prefix(&slug)
+            .is_some_and(|instance| instance.starts_with('@'))
+}
+
+/// Removes a cache file together with its uncompressed counterpart of an older cilens
+/// and its lock file, holding the lock so no parallel run is saving meanwhile.
+fn remove_cache_file(cache_file: &Path) -> Result<()> {
+    let lock = FileLock::acquire(cache_file)?;
+    let compressed = cache_file
+        .extension()
+        .is_some_and(|extension| extension == "gz");
+    if compressed && legacy_cache_file(cache_file).exists() {
+        fs::remove_file(legacy_cache_file(cache_file))?;
+    }
+    if cache_file.exists() {
+        fs::remove_file(cache_file)?;
+    }
+
+    lock.remove()
+}
+
+/// Returns the uncompressed cache file older cilens versions wrote instead of `cache_file`.
+fn legacy_cache_file(cache_file: &Path) -> PathBuf {
+    cache_file.with_extension("")
+}
+
+/// Returns the compressed cache file if it exists, otherwise an uncompressed one left
+/// by an older cilens, which is migrated on the next save.
+fn existing_cache_file(cache_file: &Path) -> Option<PathBuf> {
+    [cache_file.to_path_buf(), legacy_cache_file(cache_file)]
+        .into_iter()
+        .find(|path| path.exists())
+}
+
+/// Loads the cache for a project, returning `None` (to start with an empty cache) if
+/// the file is unreadable or belongs to another GitLab instance or project.
+fn load_cache(cache_file: &Path, base_url: &str, project_path: &str) -> Option<CacheFile> {
+    let loaded = match read_cache_file(cache_file) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            warn!("Failed to load cache ({e}), starting with empty cache");
+            return None;
         }
+    };
 
-        Ok(())
+    if let Some(owner) = loaded.file.owner_mismatch(base_url, project_path) {
+        warn!(
+            "Cache file {} belongs to {owner}, not {base_url}/{project_path}; starting with empty cache",
+            cache_file.display()
+        );
+        return None;
+    }
+    if !loaded.corrupt.is_empty() {
+        warn!(
+            "Dropped {} corrupt cache entries (see `cilens cache verify`)",
+            loaded.corrupt.len()
+        );
+    }
+    if loaded.schema_version < SCHEMA_VERSION {
+        info!(
+            "Migrating cache from schema version {} to {SCHEMA_VERSION}",
+            loaded.schema_version
+        );
+    }
+
+    debug!("Loaded cache from: {}", cache_file.display());
+    Some(loaded.file)
+}
+
+/// Reads a cache file and migrates it to the current schema.
+///
+/// Pipeline entries are read one by one, so a corrupt entry is reported and skipped
+/// instead of discarding the whole cache.
+///
+/// # Errors
+///
+/// Returns an error if the file can't be read, isn't JSON, doesn't hold a pipeline map
+/// or was written with a newer schema version.
+fn read_cache_file(cache_file: &Path) -> Result<LoadedCache> {
+    let content = storage::read_to_string(cache_file)?;
+    let mut value: Value = serde_json::from_str(&content)?;
+
+    let schema_version = detect_schema_version(&value);
+    if schema_version > SCHEMA_VERSION {
+        return Err(CILensError::Cache(format!(
+            "schema version {schema_version} was written by a newer cilens (supported: {SCHEMA_VERSION})"
+        )));
+    }
+    // Schema 0 is a bare pipeline map, schema 1 adds the envelope around it
+    if schema_version == 0 {
+        value = serde_json::json!({ "pipelines": value });
+    }
+
+    let Value::Object(mut header) = value else {
+        return Err(CILensError::Cache("cache file is not a JSON object".into()));
+    };
+    let Some(Value::Object(entries)) = header.remove("pipelines") else {
+        return Err(CILensError::Cache("cache file has no pipeline map".into()));
+    };
+    let high_water_mark = header.remove("high_water_mark");
+    header.insert("schema_version".into(), SCHEMA_VERSION.into());
+    header.insert("pipelines".into(), Value::Object(serde_json::Map::new()));
+    let mut file: CacheFile = serde_json::from_value(Value::Object(header))?;
+
+    let mut corrupt = Vec::new();
+    if let Some(high_water_mark) = high_water_mark {
+        match serde_json::from_value(high_water_mark) {
+            Ok(high_water_mark) => file.high_water_mark = high_water_mark,
+            Err(e) => corrupt.push(("high_water_mark".to_string(), e.to_string())),
+        }
+    }
+    for (id, entry) in entries {
+        match serde_json::from_value(entry) {
+            Ok(pipeline) => {
+                file.pipelines.insert(id, pipeline);
+            }
+            Err(e) => corrupt.push((id, e.to_string())),
+        }
+    }
+    corrupt.sort();
+
+    Ok(LoadedCache {
+        file,
+        schema_version,
+        corrupt,
+    })
+}
+
+/// Writes a cache file gzip-compressed, replacing it atomically.
+///
+/// An uncompressed file of an older cilens is replaced by its compressed counterpart.
+/// Callers hold the file's [`FileLock`].
+fn write_cache_file(cache_file: &Path, cache: &CacheFile) -> Result<()> {
+    let content = storage::compress(&serde_json::to_vec(cache)?)?;
+
+    if cache_file
+        .extension()
+        .is_some_and(|extension| extension == "gz")
+    {
+        storage::write_atomically(cache_file, &content)?;
+        let legacy_file = legacy_cache_file(cache_file);
+        if legacy_file.exists() {
+            fs::remove_file(&legacy_file)?;
+        }
+    } else {
+        let mut compressed_file = cache_file.as_os_str().to_owned();
+        compressed_file.push(".gz");
+        storage::write_atomically(Path::new(&compressed_file), &content)?;
+        fs::remove_file(cache_file)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the existing cache files of a project on all GitLab instances, or all cache
+/// files for `None`.
+fn existing_cache_files(project_path: Option<&str>) -> Result<Vec<PathBuf>> {
+    let cache_files = list_cache_files(&cache_dir()?)?;
+
+    let Some(project_path) = project_path else {
+        return Ok(cache_files);
+    };
+    let cache_files: Vec<PathBuf> = cache_files
+        .into_iter()
+        .filter(|cache_file| is_project_cache_file(cache_file, project_path))
+        .collect();
+    if cache_files.is_empty() {
+        info!("No cache file found for project: {project_path}");
+    }
+
+    Ok(cache_files)
+}
+
+/// Lists the job cache files in the cache directory, skipping test report caches and
+/// uncompressed files that were already migrated.
+fn list_cache_files(cache_dir: &Path) -> Result<Vec<PathBuf>> {
+    if !cache_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut cache_files: Vec<PathBuf> = fs::read_dir(cache_dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            let file_name = path.to_string_lossy();
+            let test_reports = storage::file_stem(path).ends_with("-tests");
+            let legacy =
+                file_name.ends_with(".json") && !Path::new(&format!("{file_name}.gz")).exists();
+            path.is_file() && !test_reports && (file_name.ends_with(".json.gz") || legacy)
+        })
+        .collect();
+    cache_files.sort();
+
+    Ok(cache_files)
+}
+
+fn verify_cache_file(cache_file: &Path) -> CacheVerification {
+    match read_cache_file(cache_file) {
+        Ok(loaded) => CacheVerification {
+            cache_file: cache_file.to_path_buf(),
+            schema_version: loaded.schema_version,
+            cilens_version: loaded.file.cilens_version,
+            base_url: loaded.file.base_url,
+            project: loaded.file.project,
+            pipelines: loaded.file.pipelines.len(),
+            corrupt: loaded.corrupt,
+            error: None,
+        },
+        Err(e) => CacheVerification {
+            cache_file: cache_file.to_path_buf(),
+            schema_version: 0,
+            cilens_version: None,
+            base_url: None,
+            project: None,
+            pipelines: 0,
+            corrupt: Vec::new(),
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+fn detect_schema_version(value: &Value) -> u32 {
+    match value.get("schema_version").and_then(Value::as_u64) {
+        Some(version) => u32::try_from(version).unwrap_or(u32::MAX),
+        None if value.get("pipelines").is_some_and(Value::is_object) => 1,
+        None => 0,
     }
 }
 
@@ -200,6 +1074,9 @@ mod tests {
             status: "SUCCESS".to_string(),
             retried: false,
             needs: None,
+            tags: vec![],
+            runner: None,
+            test_cases: vec![],
         }
     }
 
@@ -207,9 +1084,14 @@ mod tests {
         GitLabPipeline {
             id: id.to_string(),
             ref_: "main".to_string(),
+            sha: "abc123".to_string(),
+            weight: 1.0,
             source: "push".to_string(),
             status: status.to_string(),
             duration: 100,
+            created_at: None,
+            finished_at: None,
+            merge_request: None,
             jobs,
             stages: vec![],
         }
@@ -217,7 +1099,13 @@ mod tests {
 
     #[test]
     fn test_cache_disabled() {
-        let cache = JobCache::new("group/project", false).unwrap();
+        let cache = JobCache::new(
+            "group/project",
+            "https://gitlab.com",
+            false,
+            CacheRetention::default(),
+        )
+        .unwrap();
         assert!(!cache.enabled);
 
         // Cache should not be used when disabled
@@ -227,7 +1115,7 @@ mod tests {
         // save_pipelines should do nothing when disabled
         let jobs = vec![create_test_job("1", "test")];
         let pipelines = vec![create_test_pipeline("pipeline-1", "success", jobs)];
-        let result = cache.save_pipelines(&pipelines);
+        let result = cache.save_pipelines(&pipelines, None);
         assert!(result.is_ok());
     }
 
@@ -244,7 +1132,7 @@ mod tests {
         ];
 
         // Save pipelines
-        cache.save_pipelines(&pipelines).unwrap();
+        cache.save_pipelines(&pipelines, None).unwrap();
 
         // Reload cache to verify what was persisted
         let reloaded_cache = create_cache_with_dir(temp_dir.path(), "group/project");
@@ -272,7 +1160,7 @@ mod tests {
         )];
 
         // Save pipelines to cache
-        cache.save_pipelines(&pipelines).unwrap();
+        cache.save_pipelines(&pipelines, None).unwrap();
 
         // Reload cache from disk
         let reloaded_cache = create_cache_with_dir(temp_dir.path(), "group/project");
@@ -298,7 +1186,7 @@ mod tests {
         let pipelines = vec![create_test_pipeline("pipeline-1", "success", jobs)];
 
         // Save pipeline
-        cache.save_pipelines(&pipelines).unwrap();
+        cache.save_pipelines(&pipelines, None).unwrap();
 
         // Reload cache
         let reloaded_cache = create_cache_with_dir(temp_dir.path(), "group/project");
@@ -327,7 +1215,7 @@ mod tests {
         ];
 
         // Save pipelines to cache
-        cache.save_pipelines(&pipelines).unwrap();
+        cache.save_pipelines(&pipelines, None).unwrap();
 
         // Verify cache file exists
         let cache_file = cache.cache_file.clone();
@@ -349,18 +1237,18 @@ mod tests {
         let cache1 = create_cache_with_dir(temp_dir.path(), "group/project1");
         let jobs1 = vec![create_test_job("1", "test1")];
         let pipelines1 = vec![create_test_pipeline("pipeline-1", "success", jobs1)];
-        cache1.save_pipelines(&pipelines1).unwrap();
+        cache1.save_pipelines(&pipelines1, None).unwrap();
 
         // Create cache for second project
         let cache2 = create_cache_with_dir(temp_dir.path(), "group/project2");
         let jobs2 = vec![create_test_job("2", "test2")];
         let pipelines2 = vec![create_test_pipeline("pipeline-2", "success", jobs2)];
-        cache2.save_pipelines(&pipelines2).unwrap();
+        cache2.save_pipelines(&pipelines2, None).unwrap();
 
         // Verify both cache files exist with correct names
         let cache_dir = temp_dir.path().join("cilens").join("gitlab");
-        assert!(cache_dir.join("group-project1.json").exists());
-        assert!(cache_dir.join("group-project2.json").exists());
+        assert!(cache_dir.join("group-project1@gitlab.com.json.gz").exists());
+        assert!(cache_dir.join("group-project2@gitlab.com.json.gz").exists());
 
         // Verify each cache contains only its own data
         let reloaded1 = create_cache_with_dir(temp_dir.path(), "group/project1");
@@ -372,28 +1260,596 @@ mod tests {
         assert!(reloaded2.get("pipeline-1").is_none());
     }
 
-    // Helper function to create cache with custom directory for testing
-    fn create_cache_with_dir(dir: &std::path::Path, project_path: &str) -> JobCache {
-        let cache_dir = dir.join("cilens").join("gitlab");
+    fn create_scope(ref_: &str) -> FetchScope {
+        FetchScope {
+            ref_: Some(ref_.to_string()),
+            sampling: Sampling::Chronological,
+            limit: 100,
+            updated_after: None,
+            updated_before: None,
+        }
+    }
+
+    #[test]
+    fn test_cache_restores_pipelines_and_high_water_mark() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = create_cache_with_dir(temp_dir.path(), "group/project");
+        let mut pipeline =
+            create_test_pipeline("pipeline-1", "failed", vec![create_test_job("1", "test")]);
+        pipeline.stages = vec!["test".to_string()];
+        let mark = HighWaterMark {
+            scope: create_scope("main"),
+            updated_at: Utc::now(),
+        };
+
+        cache.save_pipelines(&[pipeline], Some(&mark)).unwrap();
+        let reloaded = create_cache_with_dir(temp_dir.path(), "group/project");
+
+        assert_eq!(
+            reloaded.high_water_mark(&create_scope("main")),
+            Some(mark.updated_at)
+        );
+        assert_eq!(reloaded.high_water_mark(&create_scope("develop")), None);
+        let pipelines = reloaded.pipelines();
+        assert_eq!(pipelines.len(), 1);
+        assert_eq!(pipelines[0].status, "failed");
+        assert_eq!(pipelines[0].stages, vec!["test"]);
+        assert_eq!(pipelines[0].jobs.len(), 1);
+    }
+
+    #[test]
+    fn test_cache_loads_job_only_layout_without_high_water_mark() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache_dir = temp_dir.path().join("cilens").join("gitlab");
         fs::create_dir_all(&cache_dir).unwrap();
+        let job = serde_json::to_value(create_test_job("1", "test")).unwrap();
+        fs::write(
+            cache_dir.join("group-project.json"),
+            serde_json::json!({ "pipeline-1": { "jobs": [job] } }).to_string(),
+        )
+        .unwrap();
 
-        let cache_filename = project_path.replace('/', "-") + ".json";
-        let cache_file = cache_dir.join(cache_filename);
+        let cache = create_cache_with_dir(temp_dir.path(), "group/project");
 
-        // Load existing cache from disk if it exists
-        let pipelines = if cache_file.exists() {
-            fs::read_to_string(&cache_file)
-                .ok()
-                .and_then(|content| serde_json::from_str(&content).ok())
-                .unwrap_or_default()
-        } else {
-            HashMap::new()
+        assert!(cache.get("pipeline-1").is_some());
+        assert!(cache.pipelines().is_empty());
+        assert_eq!(cache.high_water_mark(&create_scope("main")), None);
+    }
+
+    #[test]
+    fn test_cache_writes_versioned_envelope() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = create_cache_with_dir(temp_dir.path(), "group/project");
+        let pipelines = vec![create_test_pipeline(
+            "pipeline-1",
+            "success",
+            vec![create_test_job("1", "test")],
+        )];
+
+        cache.save_pipelines(&pipelines, None).unwrap();
+
+        let content = storage::read_to_string(&cache.cache_file).unwrap();
+        let value: Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["cilens_version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(value["base_url"], "https://gitlab.com");
+        assert_eq!(value["project"], "group/project");
+    }
+
+    #[test]
+    fn test_cache_migrates_schema_1_layout() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache_dir = temp_dir.path().join("cilens").join("gitlab");
+        fs::create_dir_all(&cache_dir).unwrap();
+        let job = serde_json::to_value(create_test_job("1", "test")).unwrap();
+        fs::write(
+            cache_dir.join("group-project.json"),
+            serde_json::json!({ "pipelines": { "pipeline-1": { "jobs": [job] } } }).to_string(),
+        )
+        .unwrap();
+
+        let loaded = read_cache_file(&cache_dir.join("group-project.json")).unwrap();
+
+        assert_eq!(loaded.schema_version, 1);
+        assert_eq!(loaded.file.schema_version, SCHEMA_VERSION);
+        assert_eq!(loaded.file.base_url, None);
+        assert!(loaded.file.pipelines.contains_key("pipeline-1"));
+    }
+
+    #[test]
+    fn test_cache_ignores_other_gitlab_instance() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = create_cache_for_instance(
+            temp_dir.path(),
+            "group/project",
+            "https://gitlab.example.com",
+        );
+        let pipelines = vec![create_test_pipeline(
+            "pipeline-1",
+            "success",
+            vec![create_test_job("1", "test")],
+        )];
+        cache.save_pipelines(&pipelines, None).unwrap();
+
+        let same = create_cache_for_instance(
+            temp_dir.path(),
+            "group/project",
+            "https://gitlab.example.com",
+        );
+        let other = create_cache_with_dir(temp_dir.path(), "group/project");
+
+        assert!(same.get("pipeline-1").is_some());
+        assert!(other.get("pipeline-1").is_none());
+    }
+
+    #[test]
+    fn test_cache_drops_and_reports_corrupt_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache_dir = temp_dir.path().join("cilens").join("gitlab");
+        fs::create_dir_all(&cache_dir).unwrap();
+        let cache_file = cache_dir.join("group-project.json");
+        let job = serde_json::to_value(create_test_job("1", "test")).unwrap();
+        fs::write(
+            &cache_file,
+            serde_json::json!({
+                "schema_version": 2,
+                "pipelines": {
+                    "pipeline-1": { "jobs": [job] },
+                    "pipeline-2": { "jobs": "truncated" }
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let cache = create_cache_with_dir(temp_dir.path(), "group/project");
+        let verification = verify_cache_file(&cache_file);
+
+        assert!(cache.get("pipeline-1").is_some());
+        assert!(cache.get("pipeline-2").is_none());
+        assert!(!verification.is_ok());
+        assert_eq!(verification.pipelines, 1);
+        assert_eq!(verification.corrupt.len(), 1);
+        assert_eq!(verification.corrupt[0].0, "pipeline-2");
+    }
+
+    #[test]
+    fn test_cache_rejects_newer_schema_and_invalid_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let newer = temp_dir.path().join("newer.json");
+        let invalid = temp_dir.path().join("invalid.json");
+        fs::write(&newer, r#"{"schema_version": 99, "pipelines": {}}"#).unwrap();
+        fs::write(&invalid, "{\"pipelines\": ").unwrap();
+
+        assert!(read_cache_file(&newer).is_err());
+        assert!(verify_cache_file(&invalid).error.is_some());
+    }
+
+    #[test]
+    fn test_list_cache_files_skips_test_report_caches() {
+        let temp_dir = TempDir::new().unwrap();
+        for file_name in [
+            "group-project.json",
+            "group-project-tests.json",
+            "other-project-tests.json.gz",
+            "notes.txt",
+        ] {
+            fs::write(temp_dir.path().join(file_name), "{}").unwrap();
+        }
+
+        let cache_files = list_cache_files(temp_dir.path()).unwrap();
+
+        assert_eq!(
+            cache_files,
+            vec![temp_dir.path().join("group-project.json")]
+        );
+    }
+
+    #[test]
+    fn test_list_cache_files_prefers_compressed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        for file_name in [
+            "group-project.json",
+            "group-project.json.gz",
+            "group-project.lock",
+            "group-legacy.json",
+        ] {
+            fs::write(temp_dir.path().join(file_name), "{}").unwrap();
+        }
+
+        let cache_files = list_cache_files(temp_dir.path()).unwrap();
+
+        assert_eq!(
+            cache_files,
+            vec![
+                temp_dir.path().join("group-legacy.json"),
+                temp_dir.path().join("group-project.json.gz"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cache_compresses_uncompressed_file_on_save() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache_dir = temp_dir.path().join("cilens").join("gitlab");
+        fs::create_dir_all(&cache_dir).unwrap();
+        let job = serde_json::to_value(create_test_job("1", "test")).unwrap();
+        fs::write(
+            cache_dir.join("group-project@gitlab.com.json"),
+            serde_json::json!({ "pipeline-1": { "jobs": [job] } }).to_string(),
+        )
+        .unwrap();
+
+        let cache = create_cache_with_dir(temp_dir.path(), "group/project");
+        let mut keeping = create_cache_with_dir(temp_dir.path(), "group/project");
+        keeping.retention.keep = true;
+        keeping
+            .save_pipelines(
+                &[create_test_pipeline(
+                    "pipeline-2",
+                    "success",
+                    vec![create_test_job("2", "test")],
+                )],
+                None,
+            )
+            .unwrap();
+
+        assert!(!cache_dir.join("group-project@gitlab.com.json").exists());
+        assert_eq!(
+            cache.cache_file,
+            cache_dir.join("group-project@gitlab.com.json.gz")
+        );
+        let reloaded = create_cache_with_dir(temp_dir.path(), "group/project");
+        assert!(reloaded.get("pipeline-1").is_some());
+        assert!(reloaded.get("pipeline-2").is_some());
+    }
+
+    #[test]
+    fn test_cache_separates_gitlab_instances() {
+        let temp_dir = TempDir::new().unwrap();
+        let pipeline = |id: &str| create_test_pipeline(id, "success", Vec::new());
+        let example = create_cache_for_instance(
+            temp_dir.path(),
+            "group/project",
+            "https://gitlab.example.com:8443",
+        );
+        example
+            .save_pipelines(&[pipeline("pipeline-1")], None)
+            .unwrap();
+        let gitlab_com = create_cache_with_dir(temp_dir.path(), "group/project");
+        gitlab_com
+            .save_pipelines(&[pipeline("pipeline-2")], None)
+            .unwrap();
+
+        let cache_dir = temp_dir.path().join("cilens").join("gitlab");
+        assert_eq!(
+            example.cache_file,
+            cache_dir.join("group-project@gitlab.example.com-8443.json.gz")
+        );
+        assert!(create_cache_with_dir(temp_dir.path(), "group/project")
+            .get("pipeline-1")
+            .is_none());
+        assert!(create_cache_for_instance(
+            temp_dir.path(),
+            "group/project",
+            "https://gitlab.example.com:8443"
+        )
+        .get("pipeline-1")
+        .is_some());
+    }
+
+    #[test]
+    fn test_cache_migrates_file_named_after_project() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache_dir = temp_dir.path().join("cilens").join("gitlab");
+        fs::create_dir_all(&cache_dir).unwrap();
+        let unscoped_file = cache_dir.join("group-project.json.gz");
+        let job = serde_json::to_value(create_test_job("1", "test")).unwrap();
+        fs::write(
+            &unscoped_file,
+            storage::compress(
+                serde_json::json!({
+                    "schema_version": 2,
+                    "base_url": "https://gitlab.com",
+                    "project": "group/project",
+                    "pipelines": { "pipeline-1": { "jobs": [job] } }
+                })
+                .to_string()
+                .as_bytes(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        // A file of another instance is left to it
+        let other = create_cache_for_instance(
+            temp_dir.path(),
+            "group/project",
+            "https://gitlab.example.com",
+        );
+        other.save_pipelines(&[], None).unwrap();
+        assert!(other.get("pipeline-1").is_none());
+        assert!(unscoped_file.exists());
+
+        let cache = create_cache_with_dir(temp_dir.path(), "group/project");
+        assert!(cache.get("pipeline-1").is_some());
+        let mut keeping = create_cache_with_dir(temp_dir.path(), "group/project");
+        keeping.retention.keep = true;
+        keeping.save_pipelines(&[], None).unwrap();
+
+        assert!(!unscoped_file.exists());
+        assert!(create_cache_with_dir(temp_dir.path(), "group/project")
+            .get("pipeline-1")
+            .is_some());
+    }
+
+    #[test]
+    fn test_remove_cache_file_removes_lock_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = create_cache_with_dir(temp_dir.path(), "group/project");
+        cache.save_pipelines(&[], None).unwrap();
+        let lock_file = storage::lock_file(&cache.cache_file);
+        assert!(lock_file.exists());
+
+        remove_cache_file(&cache.cache_file).unwrap();
+
+        assert!(!cache.cache_file.exists());
+        assert!(!lock_file.exists());
+    }
+
+    #[test]
+    fn test_is_project_cache_file_matches_all_instances() {
+        for (file_name, expected) in [
+            ("group-project@gitlab.com.json.gz", true),
+            ("group-project@gitlab.example.com-8443.json.gz", true),
+            ("group-project.json.gz", true),
+            ("group-project.json", true),
+            ("group-project2@gitlab.com.json.gz", false),
+            ("group-project-tests.json", false),
+            ("other-group-project@gitlab.com.json.gz", false),
+        ] {
+            assert_eq!(
+                is_project_cache_file(Path::new(file_name), "group/project"),
+                expected,
+                "{file_name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_cache_keeps_entries_saved_by_parallel_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut first = create_cache_with_dir(temp_dir.path(), "group/project");
+        let mut second = create_cache_with_dir(temp_dir.path(), "group/project");
+        first.retention.keep = true;
+        second.retention.keep = true;
+
+        first
+            .save_pipelines(
+                &[create_test_pipeline(
+                    "pipeline-1",
+                    "success",
+                    vec![create_test_job("1", "test")],
+                )],
+                None,
+            )
+            .unwrap();
+        second
+            .save_pipelines(
+                &[create_test_pipeline(
+                    "pipeline-2",
+                    "success",
+                    vec![create_test_job("2", "test")],
+                )],
+                None,
+            )
+            .unwrap();
+
+        let reloaded = create_cache_with_dir(temp_dir.path(), "group/project");
+        assert!(reloaded.get("pipeline-1").is_some());
+        assert!(reloaded.get("pipeline-2").is_some());
+    }
+
+    #[test]
+    fn test_cache_keeps_entries_of_earlier_runs() {
+        let temp_dir = TempDir::new().unwrap();
+        let jobs = vec![create_test_job("1", "test")];
+        let cache = create_cache_with_dir(temp_dir.path(), "group/project");
+        cache
+            .save_pipelines(
+                &[create_test_pipeline("pipeline-1", "success", jobs.clone())],
+                None,
+            )
+            .unwrap();
+
+        let mut keeping = create_cache_with_dir(temp_dir.path(), "group/project");
+        keeping.retention.keep = true;
+        keeping
+            .save_pipelines(
+                &[create_test_pipeline("pipeline-2", "success", jobs.clone())],
+                None,
+            )
+            .unwrap();
+        let reloaded = create_cache_with_dir(temp_dir.path(), "group/project");
+        assert!(reloaded.get("pipeline-1").is_some());
+        assert!(reloaded.get("pipeline-2").is_some());
+
+        reloaded
+            .save_pipelines(&[create_test_pipeline("pipeline-3", "success", jobs)], None)
+            .unwrap();
+        let replaced = create_cache_with_dir(temp_dir.path(), "group/project");
+        assert!(replaced.get("pipeline-1").is_none());
+        assert!(replaced.get("pipeline-3").is_some());
+    }
+
+    #[test]
+    fn test_cache_saves_hit_rate_of_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let pipelines = vec![create_test_pipeline(
+            "pipeline-1",
+            "success",
+            vec![create_test_job("1", "test")],
+        )];
+        create_cache_with_dir(temp_dir.path(), "group/project")
+            .save_pipelines(&pipelines, None)
+            .unwrap();
+
+        let cache = create_cache_with_dir(temp_dir.path(), "group/project");
+        assert!(cache.get("pipeline-1").is_some());
+        assert!(cache.get("pipeline-2").is_none());
+        cache.record_lookups(2, 0);
+        cache.save_pipelines(&pipelines, None).unwrap();
+
+        let last_run = read_cache_file(&cache.cache_file)
+            .unwrap()
+            .file
+            .last_run
+            .unwrap();
+        assert_eq!((last_run.hits, last_run.misses), (3, 1));
+        assert_eq!(last_run.hit_rate(), Some(75.0));
+    }
+
+    #[test]
+    fn test_cache_drops_high_water_mark_when_retention_drops_current_pipelines() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut cache = create_cache_with_dir(temp_dir.path(), "group/project");
+        cache.retention.max_entries = Some(1);
+        let jobs = vec![create_test_job("1", "test")];
+        let pipelines = vec![
+            create_test_pipeline("pipeline-1", "success", jobs.clone()),
+            create_test_pipeline("pipeline-2", "success", jobs),
+        ];
+        let mark = HighWaterMark {
+            scope: create_scope("main"),
+            updated_at: Utc::now(),
         };
 
-        JobCache {
-            cache_file,
-            pipelines,
-            enabled: true,
+        cache.save_pipelines(&pipelines, Some(&mark)).unwrap();
+        let reloaded = create_cache_with_dir(temp_dir.path(), "group/project");
+
+        assert_eq!(reloaded.pipelines.len(), 1);
+        assert!(reloaded.get("pipeline-2").is_some());
+        assert_eq!(reloaded.high_water_mark(&create_scope("main")), None);
+    }
+
+    mod cache_retention {
+        use super::*;
+
+        fn cached_at(at: Option<DateTime<Utc>>) -> CachedPipeline {
+            CachedPipeline {
+                jobs: vec![],
+                metadata: None,
+                cached_at: at,
+            }
+        }
+
+        #[test]
+        fn drops_entries_past_max_age() {
+            let now = Utc::now();
+            let mut pipelines = HashMap::from([
+                (
+                    "fresh".to_string(),
+                    cached_at(Some(now - Duration::days(1))),
+                ),
+                (
+                    "stale".to_string(),
+                    cached_at(Some(now - Duration::days(40))),
+                ),
+                ("unknown".to_string(), cached_at(None)),
+            ]);
+            let retention = CacheRetention {
+                max_age: Some(Duration::days(30)),
+                ..CacheRetention::default()
+            };
+
+            assert_eq!(retention.apply(&mut pipelines, now), 2);
+            assert!(pipelines.contains_key("fresh"));
+        }
+
+        #[test]
+        fn keeps_most_recently_used_entries_up_to_max_entries() {
+            let now = Utc::now();
+            let mut pipelines = HashMap::from([
+                ("a".to_string(), cached_at(Some(now - Duration::days(3)))),
+                ("b".to_string(), cached_at(Some(now))),
+                ("c".to_string(), cached_at(Some(now - Duration::days(1)))),
+                ("d".to_string(), cached_at(None)),
+            ]);
+            let retention = CacheRetention {
+                max_entries: Some(2),
+                ..CacheRetention::default()
+            };
+
+            assert_eq!(retention.apply(&mut pipelines, now), 2);
+            let mut kept: Vec<_> = pipelines.keys().cloned().collect();
+            kept.sort();
+            assert_eq!(kept, vec!["b", "c"]);
         }
     }
+
+    mod fetch_scope {
+        use super::*;
+
+        #[test]
+        fn contains_pipelines_matching_ref_and_start_date() {
+            let now = Utc::now();
+            let scope = FetchScope {
+                updated_after: Some(now - Duration::days(7)),
+                ..create_scope("main")
+            };
+            let mut pipeline = create_test_pipeline("pipeline-1", "success", vec![]);
+            pipeline.finished_at = Some(now - Duration::days(1));
+
+            assert!(scope.contains(&pipeline));
+
+            pipeline.finished_at = Some(now - Duration::days(10));
+            assert!(!scope.contains(&pipeline));
+
+            pipeline.finished_at = Some(now);
+            pipeline.ref_ = "develop".to_string();
+            assert!(!scope.contains(&pipeline));
+        }
+
+        #[test]
+        fn excludes_pipelines_after_end_date() {
+            let now = Utc::now();
+            let scope = FetchScope {
+                updated_after: Some(now - Duration::days(7)),
+                updated_before: Some(now - Duration::days(2)),
+                ..create_scope("main")
+            };
+            let mut pipeline = create_test_pipeline("pipeline-1", "success", vec![]);
+            pipeline.finished_at = Some(now - Duration::days(3));
+
+            assert!(scope.contains(&pipeline));
+
+            pipeline.finished_at = Some(now - Duration::days(1));
+            assert!(!scope.contains(&pipeline));
+
+            pipeline.finished_at = None;
+            pipeline.created_at = Some(now - Duration::days(8));
+            assert!(!scope.contains(&pipeline));
+        }
+    }
+
+    // Helper function to create cache with custom directory for testing
+    fn create_cache_with_dir(dir: &std::path::Path, project_path: &str) -> JobCache {
+        create_cache_for_instance(dir, project_path, "https://gitlab.com")
+    }
+
+    fn create_cache_for_instance(
+        dir: &std::path::Path,
+        project_path: &str,
+        base_url: &str,
+    ) -> JobCache {
+        let cache_dir = dir.join("cilens").join("gitlab");
+        fs::create_dir_all(&cache_dir).unwrap();
+
+        JobCache::open(
+            &cache_dir,
+            base_url.to_string(),
+            project_path,
+            CacheRetention::default(),
+        )
+    }
 }
@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+
+use super::job_costs::{calculate_job_costs, CostRates};
+use super::links::pipeline_id_to_url;
+use super::pipeline_metrics::sum_weights;
+use super::types::GitLabPipeline;
+use crate::insights::CanceledPipelines;
+use crate::providers::stats::cmp_f64;
+
+/// Number of canceled pipeline links kept in the report.
+const MAX_LINKS: usize = 5;
+
+/// Summarizes the compute spent on canceled pipelines.
+///
+/// Canceled pipelines are fetched for this report whatever the sampling strategy, and
+/// their share comes from the number of finished pipelines of each status in the range.
+/// Without those counts (offline), the share is that of the analyzed pipelines.
+///
+/// # Arguments
+///
+/// * `pipelines` - Pipelines to summarize; only canceled ones are considered
+/// * `counts` - Number of finished pipelines per status in the range, if known
+/// * `base_url` - GitLab instance base URL for pipeline links
+/// * `project_path` - Project path for pipeline links
+/// * `cost_rates` - Optional cost rates by runner, to price the wasted minutes
+///
+/// # Returns
+///
+/// Number and share of canceled pipelines, the runner minutes and cost of their jobs,
+/// and links to the most expensive ones.
+pub(super) fn summarize_canceled_pipelines(
+    pipelines: &[GitLabPipeline],
+    counts: Option<&BTreeMap<String, usize>>,
+    base_url: &str,
+    project_path: &str,
+    cost_rates: Option<&CostRates>,
+) -> Option<CanceledPipelines> {
+    let mut canceled: Vec<&GitLabPipeline> = pipelines
+        .iter()
+        .filter(|p| p.status == "canceled")
+        .collect();
+    if canceled.is_empty() {
+        return None;
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let percentage = match counts {
+        Some(counts) => {
+            let total: usize = counts.values().sum();
+            counts.get("canceled").copied().unwrap_or(0) as f64 / total.max(1) as f64 * 100.0
+        }
+        None => {
+            let all: Vec<&GitLabPipeline> = pipelines.iter().collect();
+            sum_weights(&canceled) / sum_weights(&all) * 100.0
+        }
+    };
+
+    canceled.sort_by(|a, b| cmp_f64(runner_minutes(b), runner_minutes(a)));
+    let cost = cost_rates.map(|rates| {
+        calculate_job_costs(&canceled, rates)
+            .values()
+            .map(|cost| cost.breakdown.canceled_pipelines)
+            .sum()
+    });
+
+    Some(CanceledPipelines {
+        total_pipelines: canceled.len(),
+        percentage,
+        runner_minutes: canceled.iter().map(|p| runner_minutes(p)).sum(),
+        cost,
+        links: canceled
+            .iter()
+            .take(MAX_LINKS)
+            .map(|p| pipeline_id_to_url(base_url, project_path, &p.id))
+            .collect(),
+    })
+}
+
+fn runner_minutes(pipeline: &GitLabPipeline) -> f64 {
+    pipeline.jobs.iter().map(|job| job.duration / 60.0).sum()
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use crate::providers::gitlab::types::GitLabJob;
+    use std::collections::HashMap;
+
+    fn create_job(duration: f64) -> GitLabJob {
+        GitLabJob {
+            id: "gid://gitlab/Ci::Job/1".to_string(),
+            name: "test".to_string(),
+            stage: "test".to_string(),
+            duration,
+            status: "CANCELED".to_string(),
+            retried: false,
+            needs: None,
+            tags: vec![],
+            runner: None,
+            test_cases: vec![],
+        }
+    }
+
+    fn create_pipeline(id: u32, status: &str, durations: &[f64]) -> GitLabPipeline {
+        GitLabPipeline {
+            id: format!("gid://gitlab/Ci::Pipeline/{id}"),
+            ref_: "main".to_string(),
+            sha: format!("sha{id}"),
+            weight: 1.0,
+            source: "push".to_string(),
+            status: status.to_string(),
+            duration: 60,
+            created_at: None,
+            finished_at: None,
+            merge_request: None,
+            stages: vec!["test".to_string()],
+            jobs: durations.iter().map(|&d| create_job(d)).collect(),
+        }
+    }
+
+    mod summarize_canceled_pipelines {
+        use super::*;
+
+        #[test]
+        fn returns_none_without_canceled_pipelines() {
+            let pipelines = vec![create_pipeline(1, "success", &[60.0])];
+
+            assert!(summarize_canceled_pipelines(
+                &pipelines,
+                None,
+                "https://gitlab.com",
+                "org/repo",
+                None
+            )
+            .is_none());
+        }
+
+        #[test]
+        fn sums_runner_minutes_and_cost_of_canceled_pipelines() {
+            let pipelines = vec![
+                create_pipeline(1, "success", &[600.0]),
+                create_pipeline(2, "canceled", &[60.0]),
+                create_pipeline(3, "canceled", &[120.0, 60.0]),
+                create_pipeline(4, "failed", &[60.0]),
+            ];
+            let rates = CostRates::from_rates(Some(0.5), &HashMap::new(), &HashMap::new());
+
+            let canceled = summarize_canceled_pipelines(
+                &pipelines,
+                None,
+                "https://gitlab.com",
+                "org/repo",
+                rates.as_ref(),
+            )
+            .unwrap();
+
+            assert_eq!(canceled.total_pipelines, 2);
+            assert_eq!(canceled.percentage, 50.0);
+            assert_eq!(canceled.runner_minutes, 4.0);
+            assert_eq!(canceled.cost, Some(2.0));
+            assert_eq!(
+                canceled.links,
+                vec![
+                    "https://gitlab.com/org/repo/-/pipelines/3",
+                    "https://gitlab.com/org/repo/-/pipelines/2"
+                ]
+            );
+        }
+
+        #[test]
+        fn takes_share_from_status_counts() {
+            let pipelines = vec![create_pipeline(1, "canceled", &[60.0])];
+            let counts = BTreeMap::from([
+                ("success".to_string(), 15),
+                ("failed".to_string(), 4),
+                ("canceled".to_string(), 1),
+            ]);
+
+            let canceled = summarize_canceled_pipelines(
+                &pipelines,
+                Some(&counts),
+                "https://gitlab.com",
+                "org/repo",
+                None,
+            )
+            .unwrap();
+
+            assert_eq!(canceled.total_pipelines, 1);
+            assert_eq!(canceled.percentage, 5.0);
+        }
+    }
+}
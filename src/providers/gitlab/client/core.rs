@@ -1,23 +1,21 @@
+use chrono::Utc;
 use graphql_client::Response as GraphQLResponse;
 use log::warn;
 use reqwest::Client;
-use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::Semaphore;
 use url::Url;
 
+use super::throttle::{self, AdaptiveLimiter, LimiterPermit, RateLimits};
 use crate::auth::Token;
 use crate::error::{CILensError, Result};
+use crate::providers::http::HttpSettings;
 
-const MAX_RETRIES: u32 = 30;
-const RETRY_DELAY_SECONDS: u64 = 10;
-const MAX_CONCURRENT_REQUESTS: usize = 500;
 pub(super) const PAGE_SIZE: usize = 50;
 
 /// GitLab GraphQL API client with built-in retry logic and concurrency control.
 ///
 /// Handles authentication, rate limiting, and automatic retries for transient failures.
-/// Limits concurrent requests to 500 to avoid overwhelming the GitLab API.
+/// Retries back off exponentially and honour the `Retry-After` and `RateLimit-*` headers,
+/// and the number of concurrent requests adapts to how the GitLab API copes with the load.
 pub struct GitLabClient {
     /// HTTP client for making requests
     pub client: Client,
@@ -25,8 +23,10 @@ pub struct GitLabClient {
     pub graphql_url: Url,
     /// Optional authentication token
     pub token: Option<Token>,
-    /// Semaphore to limit concurrent requests
-    semaphore: Arc<Semaphore>,
+    /// Retry and concurrency limits
+    rate_limits: RateLimits,
+    /// Adaptive limit on concurrent requests
+    limiter: AdaptiveLimiter,
 }
 
 impl GitLabClient {
@@ -36,6 +36,8 @@ impl GitLabClient {
     ///
     /// * `base_url` - GitLab instance base URL (e.g., <https://gitlab.com>)
     /// * `token` - Optional authentication token for API access
+    /// * `http` - Timeout, proxy and TLS settings for the HTTP client
+    /// * `rate_limits` - Retry and concurrency limits for API requests
     ///
     /// # Returns
     ///
@@ -43,12 +45,15 @@ impl GitLabClient {
     ///
     /// # Errors
     ///
-    /// Returns an error if the base URL is invalid or cannot be parsed.
-    pub fn new(base_url: &str, token: Option<Token>) -> Result<Self> {
-        let client = Client::builder()
-            .user_agent("CILens/0.1.0")
-            .build()
-            .map_err(|e| CILensError::Config(format!("Failed to create HTTP client: {e}")))?;
+    /// Returns an error if the base URL is invalid or cannot be parsed, or the HTTP
+    /// settings are invalid.
+    pub fn new(
+        base_url: &str,
+        token: Option<Token>,
+        http: &HttpSettings,
+        rate_limits: RateLimits,
+    ) -> Result<Self> {
+        let client = http.build_client(Client::builder().user_agent("CILens/0.1.0"))?;
 
         let base = Url::parse(base_url)
             .map_err(|e| CILensError::Config(format!("Invalid base URL: {e}")))?;
@@ -61,7 +66,8 @@ impl GitLabClient {
             client,
             graphql_url,
             token,
-            semaphore: Arc::new(Semaphore::new(MAX_CONCURRENT_REQUESTS)),
+            rate_limits,
+            limiter: AdaptiveLimiter::new(rate_limits.max_concurrent_requests),
         })
     }
 
@@ -91,55 +97,131 @@ impl GitLabClient {
     where
         T: serde::de::DeserializeOwned,
     {
-        // Acquire semaphore permit to limit concurrent requests (one permit per logical request)
-        let _permit = self.semaphore.acquire().await.unwrap();
+        let (response, _permit) = self
+            .send_with_retry(|| {
+                self.client
+                    .post(self.graphql_url.clone())
+                    .json(request_body)
+            })
+            .await?;
+
+        // Parse GraphQL response and check for errors
+        let response_body: GraphQLResponse<T> = response.json().await?;
+
+        if let Some(errors) = response_body.errors {
+            return Err(CILensError::GraphQLError {
+                query_type: std::any::type_name::<T>().to_string(),
+                errors: errors
+                    .iter()
+                    .map(|e| &e.message)
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            });
+        }
+
+        response_body
+            .data
+            .ok_or_else(|| CILensError::NoResponseData)
+    }
+
+    /// Execute a REST API GET request with the same retry logic and concurrency limit
+    /// Returns the response body as text
+    pub(super) async fn execute_rest_request(&self, url: Url) -> Result<String> {
+        let (response, _permit) = self
+            .send_with_retry(|| self.client.get(url.clone()))
+            .await?;
 
+        Ok(response.text().await?)
+    }
+
+    /// Sends an authenticated request, retrying on network errors, rate limits and
+    /// server errors. Returns the response once it has a success status, together with
+    /// the concurrency permit to hold while its body is read.
+    ///
+    /// Throttled and failed requests lower the concurrency limit and successful ones
+    /// raise it again. Retries wait for as long as `Retry-After` or `RateLimit-Reset`
+    /// asks, or back off exponentially with jitter; a server-given wait also holds back
+    /// all other requests. A request asked to wait longer than
+    /// [`RateLimits::max_server_wait`] fails instead. When `RateLimit-Remaining` reaches
+    /// 0, new requests wait for the rate limit window to reset, for at most as long.
+    async fn send_with_retry(
+        &self,
+        build_request: impl Fn() -> reqwest::RequestBuilder,
+    ) -> Result<(reqwest::Response, LimiterPermit<'_>)> {
+        let max_retries = self.rate_limits.max_retries;
         let mut retry_count = 0;
         loop {
-            let request = self.auth_request(
-                self.client
-                    .post(self.graphql_url.clone())
-                    .json(request_body),
-            );
+            let permit = self.limiter.acquire().await;
+            let request = self.auth_request(build_request());
 
             let response = match request.send().await {
                 Ok(resp) => resp,
                 Err(e) if e.is_connect() || e.is_timeout() || e.is_request() => {
-                    if retry_count >= MAX_RETRIES {
+                    if retry_count >= max_retries {
                         return Err(e.into());
                     }
+                    if e.is_timeout() {
+                        self.limiter.record_throttled(permit.started_at);
+                    }
+                    drop(permit);
+
+                    let delay = self.rate_limits.backoff(retry_count);
                     warn!(
-                        "Network error ({}), retrying in {}s ({}/{})...",
+                        "Network error ({}), retrying in {:.1}s ({}/{})...",
                         e,
-                        RETRY_DELAY_SECONDS,
+                        delay.as_secs_f64(),
                         retry_count + 1,
-                        MAX_RETRIES
+                        max_retries
                     );
-                    tokio::time::sleep(Duration::from_secs(RETRY_DELAY_SECONDS)).await;
+                    tokio::time::sleep(delay).await;
                     retry_count += 1;
                     continue;
                 }
                 Err(e) => return Err(e.into()),
             };
 
-            // Check for rate limiting or other HTTP errors before parsing JSON
+            // Check for rate limiting or other HTTP errors before parsing the body
             let status = response.status();
 
             if status == 429 || status.is_server_error() {
-                if retry_count >= MAX_RETRIES {
+                self.limiter.record_throttled(permit.started_at);
+                drop(permit);
+
+                if retry_count >= max_retries {
                     return Err(CILensError::ApiErrorAfterRetries {
                         status: status.as_u16(),
-                        retries: MAX_RETRIES,
+                        retries: max_retries,
                     });
                 }
 
+                let delay = match throttle::retry_after(response.headers(), Utc::now()) {
+                    Some(delay) if delay > self.rate_limits.max_server_wait() => {
+                        warn!(
+                            "GitLab API asks to wait {}s before retrying, more than the {}s allowed",
+                            delay.as_secs(),
+                            self.rate_limits.max_server_wait().as_secs()
+                        );
+                        return Err(CILensError::ApiErrorAfterRetries {
+                            status: status.as_u16(),
+                            retries: retry_count,
+                        });
+                    }
+                    Some(delay) => {
+                        self.limiter.pause(delay);
+                        delay
+                    }
+                    None => self.rate_limits.backoff(retry_count),
+                };
+
                 warn!(
-                    "GitLab API error (status {status}). Waiting {RETRY_DELAY_SECONDS} seconds before retry {}/{}...",
+                    "GitLab API error (status {status}). Waiting {:.1} seconds before retry {}/{}...",
+                    delay.as_secs_f64(),
                     retry_count + 1,
-                    MAX_RETRIES
+                    max_retries
                 );
 
-                tokio::time::sleep(Duration::from_secs(RETRY_DELAY_SECONDS)).await;
+                tokio::time::sleep(delay).await;
                 retry_count += 1;
                 continue;
             }
@@ -155,24 +237,17 @@ impl GitLabClient {
                 });
             }
 
-            // Parse GraphQL response and check for errors
-            let response_body: GraphQLResponse<T> = response.json().await?;
-
-            if let Some(errors) = response_body.errors {
-                return Err(CILensError::GraphQLError {
-                    query_type: std::any::type_name::<T>().to_string(),
-                    errors: errors
-                        .iter()
-                        .map(|e| &e.message)
-                        .cloned()
-                        .collect::<Vec<_>>()
-                        .join(", "),
-                });
+            self.limiter.record_success();
+            if let Some(reset) = throttle::rate_limit_reset(response.headers(), Utc::now()) {
+                let reset = reset.min(self.rate_limits.max_server_wait());
+                warn!(
+                    "GitLab API rate limit reached. Pausing requests for {}s until it resets...",
+                    reset.as_secs()
+                );
+                self.limiter.pause(reset);
             }
 
-            return response_body
-                .data
-                .ok_or_else(|| CILensError::NoResponseData);
+            return Ok((response, permit));
         }
     }
 }
@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+use graphql_client::GraphQLQuery;
+
+use super::core::{GitLabClient, PAGE_SIZE};
+use crate::error::{CILensError, Result};
+
+pub type Time = DateTime<Utc>;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/providers/gitlab/client/schema.json",
+    query_path = "src/providers/gitlab/client/deployments.graphql",
+    response_derives = "Debug,PartialEq,Clone"
+)]
+pub struct FetchDeployments;
+
+impl GitLabClient {
+    /// Fetches the deployments to an environment created within a period, newest first.
+    ///
+    /// GitLab only filters deployments by update time when they are also ordered by it,
+    /// which the GraphQL API doesn't offer. So deployments are paged in creation order
+    /// until one was created before `created_after`, and those created after
+    /// `created_before` are dropped. At most `limit` deployments are returned.
+    ///
+    /// Returns an empty list if the environment does not exist.
+    pub async fn fetch_deployments(
+        &self,
+        project_path: &str,
+        environment_name: &str,
+        limit: usize,
+        created_after: Option<DateTime<Utc>>,
+        created_before: Option<DateTime<Utc>>,
+    ) -> Result<Vec<fetch_deployments::FetchDeploymentsProjectEnvironmentDeploymentsNodes>> {
+        let mut all_deployments = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            #[allow(clippy::cast_possible_wrap)]
+            let variables = fetch_deployments::Variables {
+                project_path: project_path.to_string(),
+                environment_name: environment_name.to_string(),
+                first: PAGE_SIZE as i64,
+                after: cursor.clone(),
+            };
+
+            let request_body = FetchDeployments::build_query(variables);
+
+            let data: fetch_deployments::ResponseData =
+                self.execute_graphql_request(&request_body).await?;
+
+            let project = data
+                .project
+                .ok_or_else(|| CILensError::ProjectNotFound(project_path.to_string()))?;
+
+            let Some(deployments) = project.environment.and_then(|env| env.deployments) else {
+                break;
+            };
+
+            let mut reached_start = false;
+            for deployment in deployments.nodes.into_iter().flatten().flatten() {
+                let created_at = deployment.created_at;
+                if created_at
+                    .zip(created_after)
+                    .is_some_and(|(at, after)| at < after)
+                {
+                    reached_start = true;
+                } else if created_at
+                    .zip(created_before)
+                    .is_none_or(|(at, before)| at <= before)
+                {
+                    all_deployments.push(deployment);
+                }
+            }
+
+            if reached_start
+                || all_deployments.len() >= limit
+                || !deployments.page_info.has_next_page
+            {
+                break;
+            }
+
+            cursor = deployments.page_info.end_cursor;
+        }
+
+        all_deployments.truncate(limit);
+
+        Ok(all_deployments)
+    }
+}
@@ -1,4 +1,9 @@
 mod core;
+pub mod deployments;
 pub mod pipelines;
+pub mod test_reports;
+mod throttle;
+pub mod traces;
 
 pub use core::GitLabClient;
+pub use throttle::RateLimits;
@@ -1,8 +1,14 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
 use chrono::{DateTime, Utc};
 use graphql_client::GraphQLQuery;
+use log::debug;
+use serde::Deserialize;
 
 use super::core::{GitLabClient, PAGE_SIZE};
 use crate::error::{CILensError, Result};
+use crate::providers::gitlab::sampling::Sampling;
 
 pub type JobID = String;
 pub type CiPipelineID = String;
@@ -25,16 +31,72 @@ pub struct FetchPipelines;
 )]
 pub struct FetchPipelineJobs;
 
+/// Job of a pipeline, as selected by the `PipelineJobs` fragment.
+pub type PipelineJobNode = fetch_pipeline_jobs::PipelineJobsJobsNodes;
+
+/// Most pipelines whose jobs are fetched in one query.
+const MAX_JOBS_BATCH_SIZE: usize = 20;
+
+/// Response of the batched jobs query: each aliased pipeline has the shape of a
+/// [`FetchPipelineJobs`] pipeline.
+#[derive(Deserialize)]
+struct PipelineJobsBatch {
+    project: Option<HashMap<String, Option<fetch_pipeline_jobs::PipelineJobs>>>,
+}
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/providers/gitlab/client/schema.json",
+    query_path = "src/providers/gitlab/client/pipelines.graphql",
+    query_name = "FetchPipelineCount",
+    response_derives = "Debug,PartialEq,Clone"
+)]
+pub struct FetchPipelineCount;
+
+/// Filters applied to a pipeline fetch.
+#[derive(Debug, Clone, Copy, Default)]
+struct PipelineFilters<'a> {
+    /// Git ref (branch/tag)
+    ref_: Option<&'a str>,
+    /// Pipeline source (e.g., `merge_request_event`)
+    source: Option<&'a str>,
+    /// Only pipelines updated at or after this time
+    updated_after: Option<DateTime<Utc>>,
+    /// Only pipelines updated at or before this time
+    updated_before: Option<DateTime<Utc>>,
+}
+
+/// Finished pipeline statuses that are sampled and counted.
+///
+/// SKIPPED pipelines are left out on purpose: none of their jobs ran, so they have no
+/// duration, success or cost to analyze.
+const FINISHED_STATUSES: [fetch_pipelines::PipelineStatusEnum; 3] = [
+    fetch_pipelines::PipelineStatusEnum::SUCCESS,
+    fetch_pipelines::PipelineStatusEnum::FAILED,
+    fetch_pipelines::PipelineStatusEnum::CANCELED,
+];
+
+/// [`FINISHED_STATUSES`] as the enum of the pipeline count query.
+const FINISHED_COUNT_STATUSES: [fetch_pipeline_count::PipelineStatusEnum; 3] = [
+    fetch_pipeline_count::PipelineStatusEnum::SUCCESS,
+    fetch_pipeline_count::PipelineStatusEnum::FAILED,
+    fetch_pipeline_count::PipelineStatusEnum::CANCELED,
+];
+
+/// Statuses of unfinished pipelines shown by the live view.
+const ACTIVE_STATUSES: [fetch_pipelines::PipelineStatusEnum; 2] = [
+    fetch_pipelines::PipelineStatusEnum::RUNNING,
+    fetch_pipelines::PipelineStatusEnum::PENDING,
+];
+
 impl GitLabClient {
     #[allow(clippy::too_many_lines)]
     async fn fetch_pipelines_with_status(
         &self,
         project_path: &str,
         limit: usize,
-        ref_: Option<&str>,
+        filters: PipelineFilters<'_>,
         status: Option<fetch_pipelines::PipelineStatusEnum>,
-        updated_after: Option<DateTime<Utc>>,
-        updated_before: Option<DateTime<Utc>>,
     ) -> Result<Vec<fetch_pipelines::FetchPipelinesProjectPipelinesNodes>> {
         let mut all_pipelines = Vec::new();
         let mut cursor: Option<String> = None;
@@ -52,10 +114,11 @@ impl GitLabClient {
                 project_path: project_path.to_string(),
                 first: fetch_count,
                 after: cursor.clone(),
-                ref_: ref_.map(ToString::to_string),
+                ref_: filters.ref_.map(ToString::to_string),
                 status: status.clone(),
-                updated_after,
-                updated_before,
+                source: filters.source.map(ToString::to_string),
+                updated_after: filters.updated_after,
+                updated_before: filters.updated_before,
             };
 
             let request_body = FetchPipelines::build_query(variables);
@@ -71,7 +134,15 @@ impl GitLabClient {
                 .pipelines
                 .ok_or_else(|| CILensError::NoPipelineData(project_path.to_string()))?;
 
-            all_pipelines.extend(pipelines.nodes.into_iter().flatten().flatten());
+            // Without a status filter, skip pipelines that are still running
+            all_pipelines.extend(
+                pipelines
+                    .nodes
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .filter(|node| status.is_some() || FINISHED_STATUSES.contains(&node.status)),
+            );
 
             // Stop if we have enough pipelines or no more pages
             if all_pipelines.len() >= limit || !pipelines.page_info.has_next_page {
@@ -86,6 +157,11 @@ impl GitLabClient {
         Ok(all_pipelines)
     }
 
+    /// Fetches up to `limit` finished pipelines, chosen according to the sampling strategy.
+    ///
+    /// - `Balanced`: `limit / 2` SUCCESS and `limit / 2` FAILED pipelines, fetched in parallel
+    /// - `Chronological`: the most recent `limit` pipelines of any finished status
+    /// - `Stratified`: `limit / 3` pipelines of each finished status, fetched in parallel
     pub async fn fetch_pipelines(
         &self,
         project_path: &str,
@@ -93,44 +169,348 @@ impl GitLabClient {
         ref_: Option<&str>,
         updated_after: Option<DateTime<Utc>>,
         updated_before: Option<DateTime<Utc>>,
+        sampling: Sampling,
     ) -> Result<Vec<fetch_pipelines::FetchPipelinesProjectPipelinesNodes>> {
-        // Fetch SUCCESS and FAILED pipelines in parallel with fixed 50/50 split
-        // This ensures deterministic results across runs
-        let half_limit = limit / 2;
-
-        let (success_result, failed_result) = tokio::join!(
-            self.fetch_pipelines_with_status(
-                project_path,
-                half_limit,
-                ref_,
-                Some(fetch_pipelines::PipelineStatusEnum::SUCCESS),
-                updated_after,
-                updated_before,
-            ),
-            self.fetch_pipelines_with_status(
-                project_path,
-                half_limit,
-                ref_,
-                Some(fetch_pipelines::PipelineStatusEnum::FAILED),
-                updated_after,
-                updated_before,
-            ),
-        );
+        let filters = PipelineFilters {
+            ref_,
+            updated_after,
+            updated_before,
+            ..PipelineFilters::default()
+        };
+        let statuses: &[fetch_pipelines::PipelineStatusEnum] = match sampling {
+            Sampling::Chronological => {
+                return self
+                    .fetch_pipelines_with_status(project_path, limit, filters, None)
+                    .await;
+            }
+            Sampling::Balanced => &FINISHED_STATUSES[..2],
+            Sampling::Stratified => &FINISHED_STATUSES,
+        };
+
+        // Fixed per-status quotas keep results deterministic across runs
+        let quota = limit / statuses.len();
+        let futures: Vec<_> = statuses
+            .iter()
+            .map(|status| {
+                self.fetch_pipelines_with_status(project_path, quota, filters, Some(status.clone()))
+            })
+            .collect();
 
-        let mut all_pipelines = success_result?;
-        all_pipelines.extend(failed_result?);
+        let mut all_pipelines = Vec::new();
+        for result in futures::future::join_all(futures).await {
+            all_pipelines.extend(result?);
+        }
 
         Ok(all_pipelines)
     }
 
-    #[allow(clippy::too_many_lines)]
+    /// Fetches all finished pipelines updated since `updated_after`, for incremental fetches.
+    ///
+    /// Unlike `fetch_pipelines`, there is no limit: every page is fetched so no update
+    /// since the last fetch is missed.
+    pub async fn fetch_updated_pipelines(
+        &self,
+        project_path: &str,
+        ref_: Option<&str>,
+        updated_after: DateTime<Utc>,
+    ) -> Result<Vec<fetch_pipelines::FetchPipelinesProjectPipelinesNodes>> {
+        let filters = PipelineFilters {
+            ref_,
+            updated_after: Some(updated_after),
+            ..PipelineFilters::default()
+        };
+
+        self.fetch_pipelines_with_status(project_path, usize::MAX, filters, None)
+            .await
+    }
+
+    /// Fetches the most recent `limit` canceled pipelines, whatever the sampling strategy.
+    pub async fn fetch_canceled_pipelines(
+        &self,
+        project_path: &str,
+        limit: usize,
+        ref_: Option<&str>,
+        updated_after: Option<DateTime<Utc>>,
+        updated_before: Option<DateTime<Utc>>,
+    ) -> Result<Vec<fetch_pipelines::FetchPipelinesProjectPipelinesNodes>> {
+        let filters = PipelineFilters {
+            ref_,
+            updated_after,
+            updated_before,
+            ..PipelineFilters::default()
+        };
+
+        self.fetch_pipelines_with_status(
+            project_path,
+            limit,
+            filters,
+            Some(fetch_pipelines::PipelineStatusEnum::CANCELED),
+        )
+        .await
+    }
+
+    /// Fetches the most recent `limit` finished merge request pipelines
+    /// (`merge_request_event` source).
+    ///
+    /// Merge request pipelines run on `refs/merge-requests/<iid>/head` refs, so no ref
+    /// filter applies.
+    pub async fn fetch_merge_request_pipelines(
+        &self,
+        project_path: &str,
+        limit: usize,
+        updated_after: Option<DateTime<Utc>>,
+        updated_before: Option<DateTime<Utc>>,
+    ) -> Result<Vec<fetch_pipelines::FetchPipelinesProjectPipelinesNodes>> {
+        let filters = PipelineFilters {
+            source: Some("merge_request_event"),
+            updated_after,
+            updated_before,
+            ..PipelineFilters::default()
+        };
+
+        self.fetch_pipelines_with_status(project_path, limit, filters, None)
+            .await
+    }
+
+    /// Fetches up to `limit` running and up to `limit` pending pipelines, in parallel.
+    ///
+    /// Date filters do not apply, since these pipelines are still in progress.
+    pub async fn fetch_active_pipelines(
+        &self,
+        project_path: &str,
+        limit: usize,
+        ref_: Option<&str>,
+    ) -> Result<Vec<fetch_pipelines::FetchPipelinesProjectPipelinesNodes>> {
+        let futures: Vec<_> = ACTIVE_STATUSES
+            .iter()
+            .map(|status| {
+                let filters = PipelineFilters {
+                    ref_,
+                    ..PipelineFilters::default()
+                };
+                self.fetch_pipelines_with_status(project_path, limit, filters, Some(status.clone()))
+            })
+            .collect();
+
+        let mut all_pipelines = Vec::new();
+        for result in futures::future::join_all(futures).await {
+            all_pipelines.extend(result?);
+        }
+
+        Ok(all_pipelines)
+    }
+
+    /// Counts the finished pipelines of each status matching the filters.
+    ///
+    /// # Returns
+    ///
+    /// Pipeline counts keyed by lowercase status (e.g., "success").
+    pub async fn fetch_pipeline_counts(
+        &self,
+        project_path: &str,
+        ref_: Option<&str>,
+        updated_after: Option<DateTime<Utc>>,
+        updated_before: Option<DateTime<Utc>>,
+    ) -> Result<BTreeMap<String, usize>> {
+        let futures: Vec<_> = FINISHED_COUNT_STATUSES
+            .iter()
+            .map(|status| async move {
+                let variables = fetch_pipeline_count::Variables {
+                    project_path: project_path.to_string(),
+                    ref_: ref_.map(ToString::to_string),
+                    status: Some(status.clone()),
+                    updated_after,
+                    updated_before,
+                };
+
+                let request_body = FetchPipelineCount::build_query(variables);
+
+                let data: fetch_pipeline_count::ResponseData =
+                    self.execute_graphql_request(&request_body).await?;
+
+                let count = data
+                    .project
+                    .ok_or_else(|| CILensError::ProjectNotFound(project_path.to_string()))?
+                    .pipelines
+                    .ok_or_else(|| CILensError::NoPipelineData(project_path.to_string()))?
+                    .count;
+
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                Ok((format!("{status:?}").to_lowercase(), count.max(0) as usize))
+            })
+            .collect();
+
+        futures::future::join_all(futures)
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    /// Fetches the jobs of many pipelines, several pipelines per query.
+    ///
+    /// Each query selects the first page of jobs of up to [`MAX_JOBS_BATCH_SIZE`]
+    /// pipelines through aliased `pipeline(id:)` fields. If GitLab rejects the query as
+    /// too complex or too deep, the first batch is retried with fewer pipelines until it
+    /// fits, and the remaining batches use that size. Pipelines with more than one page of jobs
+    /// are paged individually.
+    ///
+    /// # Returns
+    ///
+    /// Job nodes keyed by pipeline ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project or a pipeline is not found, or a request fails.
+    pub async fn fetch_jobs_of_pipelines(
+        &self,
+        project_path: &str,
+        pipeline_ids: &[&str],
+    ) -> Result<HashMap<String, Vec<PipelineJobNode>>> {
+        let mut all_jobs = HashMap::new();
+        let mut remaining = pipeline_ids;
+        let mut batch_size = MAX_JOBS_BATCH_SIZE;
+
+        // Find a batch size within GitLab's query complexity limit with the first batch
+        while batch_size > 1 && !remaining.is_empty() {
+            let batch = &remaining[..batch_size.min(remaining.len())];
+            match self.fetch_jobs_batch(project_path, batch).await {
+                Ok(jobs) => {
+                    all_jobs.extend(jobs);
+                    remaining = &remaining[batch.len()..];
+                    break;
+                }
+                Err(CILensError::GraphQLError { query_type, errors }) => {
+                    // Only a smaller query can get around its size limits
+                    let Some(reduced) = reduced_batch_size(batch.len(), &errors) else {
+                        return Err(CILensError::GraphQLError { query_type, errors });
+                    };
+                    debug!(
+                        "Jobs query for {} pipelines rejected ({errors}), retrying with {reduced}",
+                        batch.len()
+                    );
+                    batch_size = reduced;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        if batch_size == 1 {
+            // Not even two pipelines fit in one query
+            let futures: Vec<_> = remaining
+                .iter()
+                .map(|&pipeline_id| async move {
+                    let jobs = self.fetch_pipeline_jobs(project_path, pipeline_id).await?;
+                    Ok::<_, CILensError>((pipeline_id.to_string(), jobs))
+                })
+                .collect();
+            for result in futures::future::join_all(futures).await {
+                let (pipeline_id, jobs) = result?;
+                all_jobs.insert(pipeline_id, jobs);
+            }
+
+            return Ok(all_jobs);
+        }
+
+        let futures: Vec<_> = remaining
+            .chunks(batch_size)
+            .map(|batch| self.fetch_jobs_batch(project_path, batch))
+            .collect();
+        for result in futures::future::join_all(futures).await {
+            all_jobs.extend(result?);
+        }
+
+        Ok(all_jobs)
+    }
+
+    /// Fetches the first page of jobs of several pipelines in one query, then pages
+    /// through the remaining jobs of pipelines that have more.
+    async fn fetch_jobs_batch(
+        &self,
+        project_path: &str,
+        pipeline_ids: &[&str],
+    ) -> Result<HashMap<String, Vec<PipelineJobNode>>> {
+        let mut variables = serde_json::Map::new();
+        variables.insert("projectPath".into(), project_path.into());
+        variables.insert("first".into(), PAGE_SIZE.into());
+        for (index, pipeline_id) in pipeline_ids.iter().enumerate() {
+            variables.insert(format!("pipeline{index}"), (*pipeline_id).into());
+        }
+        let request_body = serde_json::json!({
+            "query": jobs_batch_query(pipeline_ids.len()),
+            "operationName": "FetchPipelineJobsBatch",
+            "variables": variables,
+        });
+
+        let data: PipelineJobsBatch = self.execute_graphql_request(&request_body).await?;
+
+        let mut pipelines = data
+            .project
+            .ok_or_else(|| CILensError::ProjectNotFound(project_path.to_string()))?;
+
+        let mut all_jobs = HashMap::new();
+        let mut next_pages = Vec::new();
+        for (index, &pipeline_id) in pipeline_ids.iter().enumerate() {
+            let jobs = pipelines
+                .remove(&format!("pipeline{index}"))
+                .flatten()
+                .ok_or_else(|| CILensError::PipelineNotFound(pipeline_id.to_string()))?
+                .jobs
+                .ok_or_else(|| CILensError::NoJobData(pipeline_id.to_string()))?;
+
+            if jobs.page_info.has_next_page {
+                next_pages.push((pipeline_id, jobs.page_info.end_cursor));
+            }
+            all_jobs.insert(
+                pipeline_id.to_string(),
+                jobs.nodes
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        let futures: Vec<_> = next_pages
+            .into_iter()
+            .map(|(pipeline_id, cursor)| async move {
+                let jobs = self
+                    .fetch_job_pages(project_path, pipeline_id, cursor)
+                    .await?;
+                Ok::<_, CILensError>((pipeline_id, jobs))
+            })
+            .collect();
+        for result in futures::future::join_all(futures).await {
+            let (pipeline_id, jobs) = result?;
+            all_jobs
+                .entry(pipeline_id.to_string())
+                .or_default()
+                .extend(jobs);
+        }
+
+        Ok(all_jobs)
+    }
+
+    /// Fetches all jobs of a pipeline, one page per query.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project or pipeline is not found, or a request fails.
     pub async fn fetch_pipeline_jobs(
         &self,
         project_path: &str,
         pipeline_id: &str,
-    ) -> Result<Vec<fetch_pipeline_jobs::FetchPipelineJobsProjectPipelineJobsNodes>> {
+    ) -> Result<Vec<PipelineJobNode>> {
+        self.fetch_job_pages(project_path, pipeline_id, None).await
+    }
+
+    /// Fetches the jobs of a pipeline page by page, starting after `cursor`.
+    async fn fetch_job_pages(
+        &self,
+        project_path: &str,
+        pipeline_id: &str,
+        mut cursor: Option<String>,
+    ) -> Result<Vec<PipelineJobNode>> {
         let mut all_jobs = Vec::new();
-        let mut cursor: Option<String> = None;
 
         loop {
             #[allow(clippy::cast_possible_wrap)]
@@ -170,3 +550,188 @@ impl GitLabClient {
         Ok(all_jobs)
     }
 }
+
+/// Builds a query selecting the first page of jobs of `count` pipelines.
+///
+/// Pipelines are aliased `pipeline0`, `pipeline1`, ... and identified by the variables
+/// of the same names. The selected fields come from the `PipelineJobs` fragment, so the
+/// response has the shape of [`FetchPipelineJobs`] for every pipeline.
+fn jobs_batch_query(count: usize) -> String {
+    let mut variables = String::from("$projectPath: ID!, $first: Int!, $after: String");
+    let mut pipelines = String::new();
+    for index in 0..count {
+        let _ = write!(variables, ", $pipeline{index}: CiPipelineID!");
+        let _ = writeln!(
+            pipelines,
+            "    pipeline{index}: pipeline(id: $pipeline{index}) {{ ...PipelineJobs }}"
+        );
+    }
+
+    format!(
+        "query FetchPipelineJobsBatch({variables}) {{\n  project(fullPath: $projectPath) {{\n\
+         {pipelines}  }}\n}}\n\n{PIPELINE_JOBS_FRAGMENT}"
+    )
+}
+
+/// The `PipelineJobs` fragment of `pipelines.graphql`, which the batched jobs query
+/// selects for every pipeline. A test keeps both identical.
+const PIPELINE_JOBS_FRAGMENT: &str = r"fragment PipelineJobs on Pipeline {
+  jobs(first: $first, after: $after) {
+    pageInfo {
+      hasNextPage
+      endCursor
+    }
+    nodes {
+      id
+      name
+      status
+      duration
+      retried
+      tags
+      runner {
+        runnerType
+        description
+      }
+      stage {
+        name
+      }
+      needs {
+        nodes {
+          name
+        }
+      }
+    }
+  }
+}";
+
+/// Returns the batch size to retry with after a batch of `batch_size` pipelines was
+/// rejected with `errors`, or `None` if the errors are not about the query's size.
+///
+/// GitLab reports "Query has complexity of X, which exceeds max complexity of Y", from
+/// which the largest batch within the limit is estimated. Queries exceeding the max
+/// depth halve the batch.
+fn reduced_batch_size(batch_size: usize, errors: &str) -> Option<usize> {
+    let number_after = |prefix: &str| -> Option<usize> {
+        let rest = &errors[errors.find(prefix)? + prefix.len()..];
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        rest[..digits].parse().ok()
+    };
+
+    let reduced = if errors.contains("exceeds max complexity") {
+        number_after("complexity of ")
+            .zip(number_after("max complexity of "))
+            .filter(|&(complexity, _)| complexity > 0)
+            .map_or(batch_size / 2, |(complexity, max)| {
+                batch_size * max / complexity
+            })
+    } else if errors.contains("exceeds max depth") {
+        batch_size / 2
+    } else {
+        return None;
+    };
+
+    Some(reduced.clamp(1, batch_size.saturating_sub(1).max(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jobs_batch_query_aliases_every_pipeline() {
+        let query = jobs_batch_query(2);
+
+        assert!(query.starts_with(
+            "query FetchPipelineJobsBatch($projectPath: ID!, $first: Int!, $after: String, \
+             $pipeline0: CiPipelineID!, $pipeline1: CiPipelineID!)"
+        ));
+        assert!(query.contains("pipeline0: pipeline(id: $pipeline0) { ...PipelineJobs }"));
+        assert!(query.contains("pipeline1: pipeline(id: $pipeline1) { ...PipelineJobs }"));
+        assert!(query.contains("fragment PipelineJobs on Pipeline {"));
+        assert!(!query.contains("query FetchPipelineCount"));
+        assert_eq!(query.matches('{').count(), query.matches('}').count());
+    }
+
+    #[test]
+    fn test_batch_response_deserializes_aliased_pipelines() {
+        let response = r#"{
+            "project": {
+                "pipeline0": {
+                    "jobs": {
+                        "pageInfo": { "hasNextPage": true, "endCursor": "abc" },
+                        "nodes": [{
+                            "id": "gid://gitlab/Ci::Build/1",
+                            "name": "test",
+                            "status": "SUCCESS",
+                            "duration": 30,
+                            "retried": false,
+                            "tags": [],
+                            "runner": null,
+                            "stage": { "name": "test" },
+                            "needs": { "nodes": [] }
+                        }]
+                    }
+                },
+                "pipeline1": null
+            }
+        }"#;
+
+        let mut pipelines = serde_json::from_str::<PipelineJobsBatch>(response)
+            .unwrap()
+            .project
+            .unwrap();
+        let jobs = pipelines
+            .remove("pipeline0")
+            .flatten()
+            .unwrap()
+            .jobs
+            .unwrap();
+
+        assert!(jobs.page_info.has_next_page);
+        assert_eq!(jobs.nodes.unwrap().len(), 1);
+        assert!(pipelines.remove("pipeline1").flatten().is_none());
+    }
+
+    #[test]
+    fn test_reduced_batch_size() {
+        let too_complex = "Query has complexity of 540, which exceeds max complexity of 250";
+
+        assert_eq!(reduced_batch_size(20, too_complex), Some(9));
+        assert_eq!(reduced_batch_size(2, too_complex), Some(1));
+        assert_eq!(
+            reduced_batch_size(
+                20,
+                "Query has complexity of 260, which exceeds max complexity of 250"
+            ),
+            Some(19)
+        );
+        assert_eq!(
+            reduced_batch_size(20, "Query has depth of 16, which exceeds max depth of 15"),
+            Some(10)
+        );
+    }
+
+    #[test]
+    fn test_reduced_batch_size_ignores_other_errors() {
+        assert_eq!(reduced_batch_size(20, "Internal server error"), None);
+        assert_eq!(
+            reduced_batch_size(20, "You don't have permission to access this pipeline"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_pipeline_jobs_fragment_matches_pipelines_graphql() {
+        assert!(include_str!("pipelines.graphql").contains(&format!("{PIPELINE_JOBS_FRAGMENT}\n")));
+    }
+
+    #[test]
+    fn test_finished_count_statuses_match_finished_statuses() {
+        assert_eq!(
+            FINISHED_COUNT_STATUSES.map(|status| format!("{status:?}")),
+            FINISHED_STATUSES.map(|status| format!("{status:?}"))
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use graphql_client::GraphQLQuery;
+
+use super::core::{GitLabClient, PAGE_SIZE};
+use crate::error::{CILensError, Result};
+
+pub type CiPipelineID = String;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/providers/gitlab/client/schema.json",
+    query_path = "src/providers/gitlab/client/test_reports.graphql",
+    response_derives = "Debug,PartialEq,Clone"
+)]
+pub struct FetchTestReportSummary;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/providers/gitlab/client/schema.json",
+    query_path = "src/providers/gitlab/client/test_reports.graphql",
+    query_name = "FetchTestCases",
+    response_derives = "Debug,PartialEq,Clone"
+)]
+pub struct FetchTestCases;
+
+impl GitLabClient {
+    /// Fetches the numeric IDs of the jobs in a pipeline that uploaded a JUnit report.
+    pub async fn fetch_test_report_build_ids(
+        &self,
+        project_path: &str,
+        pipeline_id: &str,
+    ) -> Result<Vec<String>> {
+        let variables = fetch_test_report_summary::Variables {
+            project_path: project_path.to_string(),
+            pipeline_id: pipeline_id.to_string(),
+        };
+
+        let request_body = FetchTestReportSummary::build_query(variables);
+
+        let data: fetch_test_report_summary::ResponseData =
+            self.execute_graphql_request(&request_body).await?;
+
+        let pipeline = data
+            .project
+            .ok_or_else(|| CILensError::ProjectNotFound(project_path.to_string()))?
+            .pipeline
+            .ok_or_else(|| CILensError::PipelineNotFound(pipeline_id.to_string()))?;
+
+        Ok(pipeline
+            .test_report_summary
+            .test_suites
+            .nodes
+            .into_iter()
+            .flatten()
+            .flatten()
+            .flat_map(|suite| suite.build_ids.unwrap_or_default())
+            .collect())
+    }
+
+    /// Fetches all test cases reported by a single job.
+    pub async fn fetch_test_cases(
+        &self,
+        project_path: &str,
+        pipeline_id: &str,
+        build_id: &str,
+    ) -> Result<Vec<fetch_test_cases::FetchTestCasesProjectPipelineTestSuiteTestCasesNodes>> {
+        let mut all_test_cases = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            #[allow(clippy::cast_possible_wrap)]
+            let variables = fetch_test_cases::Variables {
+                project_path: project_path.to_string(),
+                pipeline_id: pipeline_id.to_string(),
+                build_ids: vec![build_id.to_string()],
+                first: PAGE_SIZE as i64,
+                after: cursor.clone(),
+            };
+
+            let request_body = FetchTestCases::build_query(variables);
+
+            let data: fetch_test_cases::ResponseData =
+                self.execute_graphql_request(&request_body).await?;
+
+            let pipeline = data
+                .project
+                .ok_or_else(|| CILensError::ProjectNotFound(project_path.to_string()))?
+                .pipeline
+                .ok_or_else(|| CILensError::PipelineNotFound(pipeline_id.to_string()))?;
+
+            let Some(test_cases) = pipeline.test_suite.and_then(|suite| suite.test_cases) else {
+                break;
+            };
+
+            all_test_cases.extend(test_cases.nodes.into_iter().flatten().flatten());
+
+            if !test_cases.page_info.has_next_page {
+                break;
+            }
+
+            cursor = test_cases.page_info.end_cursor;
+        }
+
+        Ok(all_test_cases)
+    }
+}
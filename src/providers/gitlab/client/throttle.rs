@@ -0,0 +1,429 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use log::debug;
+use reqwest::header::HeaderMap;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// Retry and concurrency limits for GitLab API requests.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimits {
+    /// Retries of a request after network errors, rate limiting (429) or server errors (5xx)
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for every further retry
+    pub retry_delay: Duration,
+    /// Upper bound for the retry delay; the server may ask for waits of up to five
+    /// times as long
+    pub max_retry_delay: Duration,
+    /// Upper bound for concurrent requests; the limit in use adapts below it
+    pub max_concurrent_requests: usize,
+}
+
+/// Longest wait a server may ask for with `Retry-After` or `RateLimit-Reset`, in
+/// multiples of [`RateLimits::max_retry_delay`].
+const MAX_SERVER_WAIT_FACTOR: u32 = 5;
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            max_retries: 10,
+            retry_delay: Duration::from_secs(1),
+            max_retry_delay: Duration::from_secs(60),
+            max_concurrent_requests: 500,
+        }
+    }
+}
+
+impl RateLimits {
+    /// Returns the delay before retry number `attempt` (starting at 0).
+    ///
+    /// The delay doubles with every attempt up to `max_retry_delay`, and is spread over
+    /// its upper half at random so requests that failed together don't retry together.
+    pub(super) fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .retry_delay
+            .saturating_mul(2_u32.saturating_pow(attempt))
+            .min(self.max_retry_delay);
+
+        delay / 2 + delay.mul_f64(random_fraction() / 2.0)
+    }
+
+    /// Returns the longest wait honoured when the server asks for one.
+    ///
+    /// Longer waits would stall all requests for an unbounded time, so a request asked
+    /// to wait longer fails instead.
+    pub(super) fn max_server_wait(&self) -> Duration {
+        self.max_retry_delay.saturating_mul(MAX_SERVER_WAIT_FACTOR)
+    }
+}
+
+/// Returns a pseudo-random number in `[0, 1)`.
+///
+/// Every `RandomState` is seeded differently, which is plenty for retry jitter.
+#[allow(clippy::cast_precision_loss)]
+fn random_fraction() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+
+    (random >> 11) as f64 / (1_u64 << 53) as f64
+}
+
+/// Returns how long a throttled response asks clients to wait.
+///
+/// Uses `Retry-After` (seconds or an HTTP date), falling back to [`rate_limit_reset`].
+pub(super) fn retry_after(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let Some(value) = header(headers, "retry-after") else {
+        return rate_limit_reset(headers, now);
+    };
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+
+    Some(until(at.with_timezone(&Utc), now))
+}
+
+/// Returns how long until the rate limit window resets, if no requests are left in it.
+///
+/// `RateLimit-Reset` is a Unix timestamp on GitLab and a number of seconds in the IETF
+/// draft; values too small to be a recent timestamp are read as seconds.
+pub(super) fn rate_limit_reset(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let remaining: u64 = header(headers, "ratelimit-remaining")?.parse().ok()?;
+    if remaining > 0 {
+        return None;
+    }
+
+    let reset: u64 = header(headers, "ratelimit-reset")?.parse().ok()?;
+    if reset < 1_000_000_000 {
+        return Some(Duration::from_secs(reset));
+    }
+
+    Some(until(
+        DateTime::from_timestamp(i64::try_from(reset).ok()?, 0)?,
+        now,
+    ))
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name)?.to_str().ok().map(str::trim)
+}
+
+fn until(at: DateTime<Utc>, now: DateTime<Utc>) -> Duration {
+    (at - now).to_std().unwrap_or_default()
+}
+
+/// Concurrency limit that adapts to how the server copes with the load.
+///
+/// The limit starts at the maximum, is halved when a request is throttled or fails
+/// with a server error, and grows by one after as many successful requests as the
+/// current limit (additive increase, multiplicative decrease). Requests can also be
+/// paused altogether until a rate limit window resets.
+pub(super) struct AdaptiveLimiter {
+    semaphore: Semaphore,
+    max: usize,
+    state: Mutex<LimiterState>,
+}
+
+struct LimiterState {
+    /// Concurrent requests currently allowed
+    limit: usize,
+    /// Successful requests since the limit last changed
+    successes: usize,
+    /// Permits still to be withdrawn after the limit was lowered
+    surplus: usize,
+    /// When the limit was last lowered
+    lowered_at: Option<Instant>,
+    /// No request is sent before this time
+    paused_until: Option<Instant>,
+}
+
+/// Permission to send one request, returned to the limiter when dropped.
+pub(super) struct LimiterPermit<'a> {
+    permit: Option<SemaphorePermit<'a>>,
+    limiter: &'a AdaptiveLimiter,
+    /// When the request was allowed to start
+    pub(super) started_at: Instant,
+}
+
+impl Drop for LimiterPermit<'_> {
+    fn drop(&mut self) {
+        let Some(permit) = self.permit.take() else {
+            return;
+        };
+
+        let mut state = self.limiter.lock();
+        if state.surplus > 0 {
+            state.surplus -= 1;
+            permit.forget();
+        }
+    }
+}
+
+impl AdaptiveLimiter {
+    /// Creates a limiter allowing up to `max` concurrent requests (at least one).
+    pub(super) fn new(max: usize) -> Self {
+        let max = max.max(1);
+
+        Self {
+            semaphore: Semaphore::new(max),
+            max,
+            state: Mutex::new(LimiterState {
+                limit: max,
+                successes: 0,
+                surplus: 0,
+                lowered_at: None,
+                paused_until: None,
+            }),
+        }
+    }
+
+    /// Waits for a pause to end and for a free slot under the current limit.
+    pub(super) async fn acquire(&self) -> LimiterPermit<'_> {
+        loop {
+            let paused_until = self.lock().paused_until;
+            match paused_until {
+                Some(until) if until > Instant::now() => {
+                    tokio::time::sleep_until(until.into()).await;
+                }
+                _ => break,
+            }
+        }
+
+        let permit = self
+            .semaphore
+            .acquire()
+            .await
+            .expect("limiter semaphore is never closed");
+
+        LimiterPermit {
+            permit: Some(permit),
+            limiter: self,
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Records a successful request, raising the limit after enough of them.
+    pub(super) fn record_success(&self) {
+        let mut state = self.lock();
+        state.successes += 1;
+        if state.successes < state.limit || state.limit >= self.max {
+            return;
+        }
+
+        state.limit += 1;
+        state.successes = 0;
+        if state.surplus > 0 {
+            state.surplus -= 1;
+        } else {
+            self.semaphore.add_permits(1);
+        }
+        debug!("Raised concurrent GitLab requests to {}", state.limit);
+    }
+
+    /// Records a throttled or failed request, halving the limit.
+    ///
+    /// Requests started before the last reduction don't reduce it again, so a burst
+    /// of failures from the same overload only halves the limit once.
+    pub(super) fn record_throttled(&self, started_at: Instant) {
+        let mut state = self.lock();
+        if state
+            .lowered_at
+            .is_some_and(|lowered_at| started_at < lowered_at)
+        {
+            return;
+        }
+
+        let limit = (state.limit / 2).max(1);
+        state.surplus += state.limit - limit;
+        state.limit = limit;
+        state.successes = 0;
+        state.lowered_at = Some(Instant::now());
+
+        // Withdraw idle permits right away, the others when they are returned
+        while state.surplus > 0 {
+            match self.semaphore.try_acquire() {
+                Ok(permit) => {
+                    permit.forget();
+                    state.surplus -= 1;
+                }
+                Err(_) => break,
+            }
+        }
+        debug!("Lowered concurrent GitLab requests to {limit}");
+    }
+
+    /// Holds back all new requests for `delay`.
+    pub(super) fn pause(&self, delay: Duration) {
+        let until = Instant::now() + delay;
+        let mut state = self.lock();
+        if state
+            .paused_until
+            .is_none_or(|paused_until| paused_until < until)
+        {
+            state.paused_until = Some(until);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LimiterState> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    #[cfg(test)]
+    fn limit(&self) -> usize {
+        self.lock().limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    mod backoff {
+        use super::*;
+
+        #[test]
+        fn doubles_up_to_max_delay_with_jitter() {
+            let limits = RateLimits::default();
+
+            for (attempt, expected) in [(0, 1), (1, 2), (3, 8), (10, 60)] {
+                let delay = limits.backoff(attempt);
+                let expected = Duration::from_secs(expected);
+
+                assert!(delay >= expected / 2, "attempt {attempt}: {delay:?}");
+                assert!(delay <= expected, "attempt {attempt}: {delay:?}");
+            }
+        }
+    }
+
+    mod max_server_wait {
+        use super::*;
+
+        #[test]
+        fn is_a_multiple_of_max_retry_delay() {
+            let limits = RateLimits {
+                max_retry_delay: Duration::from_secs(30),
+                ..RateLimits::default()
+            };
+
+            assert_eq!(limits.max_server_wait(), Duration::from_secs(150));
+        }
+    }
+
+    mod retry_after {
+        use super::*;
+
+        #[test]
+        fn reads_seconds_and_http_dates() {
+            let now = DateTime::parse_from_rfc2822("Tue, 05 Jan 2021 11:00:00 GMT")
+                .unwrap()
+                .with_timezone(&Utc);
+
+            assert_eq!(
+                retry_after(&headers(&[("retry-after", "30")]), now),
+                Some(Duration::from_secs(30))
+            );
+            assert_eq!(
+                retry_after(
+                    &headers(&[("retry-after", "Tue, 05 Jan 2021 11:01:00 GMT")]),
+                    now
+                ),
+                Some(Duration::from_secs(60))
+            );
+        }
+
+        #[test]
+        fn falls_back_to_rate_limit_reset() {
+            let now = Utc::now();
+            let reset = (now.timestamp() + 120).to_string();
+
+            let delay = retry_after(
+                &headers(&[("ratelimit-remaining", "0"), ("ratelimit-reset", &reset)]),
+                now,
+            )
+            .unwrap();
+
+            assert!(delay > Duration::from_secs(118) && delay <= Duration::from_secs(120));
+            assert_eq!(retry_after(&HeaderMap::new(), now), None);
+        }
+    }
+
+    mod rate_limit_reset {
+        use super::*;
+
+        #[test]
+        fn waits_only_when_no_requests_remain() {
+            let now = Utc::now();
+
+            assert_eq!(
+                rate_limit_reset(
+                    &headers(&[("ratelimit-remaining", "5"), ("ratelimit-reset", "30")]),
+                    now
+                ),
+                None
+            );
+            assert_eq!(
+                rate_limit_reset(
+                    &headers(&[("ratelimit-remaining", "0"), ("ratelimit-reset", "30")]),
+                    now
+                ),
+                Some(Duration::from_secs(30))
+            );
+        }
+    }
+
+    mod adaptive_limiter {
+        use super::*;
+
+        #[tokio::test]
+        async fn halves_once_per_overload_and_ramps_up_on_success() {
+            let limiter = AdaptiveLimiter::new(8);
+
+            let first = limiter.acquire().await;
+            let second = limiter.acquire().await;
+            let started_at = first.started_at;
+            limiter.record_throttled(started_at);
+            limiter.record_throttled(second.started_at);
+            assert_eq!(limiter.limit(), 4);
+
+            drop(first);
+            drop(second);
+            assert_eq!(limiter.semaphore.available_permits(), 4);
+
+            for _ in 0..4 {
+                limiter.record_success();
+            }
+            assert_eq!(limiter.limit(), 5);
+            assert_eq!(limiter.semaphore.available_permits(), 5);
+
+            let later = limiter.acquire().await;
+            limiter.record_throttled(later.started_at);
+            assert_eq!(limiter.limit(), 2);
+        }
+
+        #[tokio::test]
+        async fn never_drops_below_one_request() {
+            let limiter = AdaptiveLimiter::new(1);
+            let permit = limiter.acquire().await;
+
+            limiter.record_throttled(permit.started_at);
+            drop(permit);
+
+            assert_eq!(limiter.limit(), 1);
+            assert_eq!(limiter.semaphore.available_permits(), 1);
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use super::core::GitLabClient;
+use crate::error::{CILensError, Result};
+
+impl GitLabClient {
+    /// Downloads the raw log (trace) of a job through the REST API.
+    ///
+    /// # Arguments
+    ///
+    /// * `project_path` - Project path (e.g., "group/project")
+    /// * `job_id` - Numeric job ID
+    pub async fn fetch_job_trace(&self, project_path: &str, job_id: &str) -> Result<String> {
+        let project: String =
+            url::form_urlencoded::byte_serialize(project_path.as_bytes()).collect();
+
+        // graphql_url ends in `api/graphql`, so this resolves to `api/v4/...`
+        let url = self
+            .graphql_url
+            .join(&format!("v4/projects/{project}/jobs/{job_id}/trace"))
+            .map_err(|e| CILensError::Config(format!("Invalid job trace URL: {e}")))?;
+
+        self.execute_rest_request(url).await
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+
+use super::types::GitLabDeployment;
+use crate::insights::DeploymentMetrics;
+use crate::providers::stats::calculate_percentiles;
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+#[derive(Clone, Copy, PartialEq)]
+enum DeploymentKind {
+    /// First successful deployment of a commit
+    Change,
+    /// Successful deployment of an earlier commit
+    Rollback,
+    /// Successful deployment of the commit that is already deployed
+    Redeploy,
+    Failed,
+}
+
+/// Calculates DORA-style delivery metrics for one environment.
+///
+/// Deployments are ordered by creation time and only successful and failed ones count.
+/// The first successful deployment of a commit is a change. Deploying an earlier commit
+/// again is a rollback, and deploying the current commit again is a redeploy.
+/// A change fails if the next deployment failed or was a rollback. Lead time runs from
+/// the commit date to the end of a change's deployment.
+///
+/// # Arguments
+///
+/// * `environment` - Environment name
+/// * `deployments` - Deployments to the environment, in any order
+/// * `period_start` - Start of the analyzed period; the oldest deployment if `None`
+/// * `period_end` - End of the analyzed period
+///
+/// # Returns
+///
+/// Deployment metrics, or `None` if the environment has no finished deployments.
+pub(super) fn calculate_deployment_metrics(
+    environment: &str,
+    deployments: &[GitLabDeployment],
+    period_start: Option<DateTime<Utc>>,
+    period_end: DateTime<Utc>,
+) -> Option<DeploymentMetrics> {
+    let mut finished: Vec<&GitLabDeployment> = deployments
+        .iter()
+        .filter(|d| d.status == "success" || d.status == "failed")
+        .collect();
+    if finished.is_empty() {
+        return None;
+    }
+    finished.sort_by_key(|d| d.created_at);
+
+    let mut deployed: HashSet<&str> = HashSet::new();
+    let mut current: Option<&str> = None;
+    let mut kinds = Vec::with_capacity(finished.len());
+    let mut lead_times = Vec::new();
+
+    for deployment in &finished {
+        if deployment.status != "success" {
+            kinds.push(DeploymentKind::Failed);
+            continue;
+        }
+
+        let sha = deployment.sha.as_str();
+        let kind = if current == Some(sha) {
+            DeploymentKind::Redeploy
+        } else if deployed.contains(sha) {
+            DeploymentKind::Rollback
+        } else {
+            DeploymentKind::Change
+        };
+        kinds.push(kind);
+
+        if kind == DeploymentKind::Change {
+            if let Some(committed_at) = deployment.committed_at {
+                let deployed_at = deployment.finished_at.unwrap_or(deployment.created_at);
+                #[allow(clippy::cast_precision_loss)]
+                lead_times.push(((deployed_at - committed_at).num_seconds() as f64).max(0.0));
+            }
+        }
+
+        deployed.insert(sha);
+        current = Some(sha);
+    }
+
+    let mut changes: u32 = 0;
+    let mut failed_changes: u32 = 0;
+    for (i, kind) in kinds.iter().enumerate() {
+        if *kind != DeploymentKind::Change {
+            continue;
+        }
+        changes += 1;
+
+        let followed_by_failure = kinds
+            .get(i + 1)
+            .is_some_and(|next| matches!(next, DeploymentKind::Failed | DeploymentKind::Rollback));
+        if followed_by_failure {
+            failed_changes += 1;
+        }
+    }
+
+    let successful_deployments = finished.iter().filter(|d| d.status == "success").count();
+    let rollbacks = kinds
+        .iter()
+        .filter(|&&kind| kind == DeploymentKind::Rollback)
+        .count();
+
+    let start = period_start.unwrap_or(finished[0].created_at);
+    #[allow(clippy::cast_precision_loss)]
+    let days = ((period_end - start).num_seconds() as f64 / SECONDS_PER_DAY).max(1.0);
+
+    #[allow(clippy::cast_precision_loss)]
+    let deployments_per_day = successful_deployments as f64 / days;
+
+    let change_failure_rate = if changes > 0 {
+        (f64::from(failed_changes) / f64::from(changes)) * 100.0
+    } else {
+        0.0
+    };
+
+    let (lead_time_p50, lead_time_p95, _) = calculate_percentiles(&lead_times);
+
+    Some(DeploymentMetrics {
+        environment: environment.to_string(),
+        total_deployments: finished.len(),
+        successful_deployments,
+        failed_deployments: finished.len() - successful_deployments,
+        rollbacks,
+        deployments_per_day,
+        period_start: start,
+        period_end,
+        change_failure_rate,
+        lead_time_p50,
+        lead_time_p95,
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn at(hours: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap() + Duration::hours(hours)
+    }
+
+    fn create_deployment(sha: &str, status: &str, hour: i64) -> GitLabDeployment {
+        GitLabDeployment {
+            sha: sha.to_string(),
+            status: status.to_string(),
+            created_at: at(hour),
+            finished_at: Some(at(hour)),
+            committed_at: Some(at(hour - 2)),
+        }
+    }
+
+    mod calculate_deployment_metrics {
+        use super::*;
+
+        #[test]
+        fn returns_none_without_finished_deployments() {
+            let deployments = vec![create_deployment("a", "running", 0)];
+
+            assert!(
+                calculate_deployment_metrics("production", &deployments, None, at(24)).is_none()
+            );
+        }
+
+        #[test]
+        fn measures_frequency_and_lead_time() {
+            let deployments = vec![
+                create_deployment("b", "success", 24),
+                create_deployment("a", "success", 0),
+            ];
+
+            let metrics =
+                calculate_deployment_metrics("production", &deployments, Some(at(0)), at(48))
+                    .unwrap();
+
+            assert_eq!(metrics.total_deployments, 2);
+            assert_eq!(metrics.deployments_per_day, 1.0);
+            assert_eq!((metrics.period_start, metrics.period_end), (at(0), at(48)));
+            assert_eq!(metrics.lead_time_p50, 7200.0);
+            assert_eq!(metrics.change_failure_rate, 0.0);
+        }
+
+        #[test]
+        fn counts_changes_followed_by_failure_or_rollback() {
+            let deployments = vec![
+                create_deployment("a", "success", 0),
+                // "b" is followed by a failed deployment
+                create_deployment("b", "success", 1),
+                create_deployment("c", "failed", 2),
+                // "d" is rolled back to "b"
+                create_deployment("d", "success", 3),
+                create_deployment("b", "success", 4),
+                // Redeploying the current commit is not a rollback
+                create_deployment("b", "success", 5),
+            ];
+
+            let metrics =
+                calculate_deployment_metrics("production", &deployments, None, at(24)).unwrap();
+
+            assert_eq!(metrics.failed_deployments, 1);
+            assert_eq!(metrics.rollbacks, 1);
+            assert_eq!(metrics.period_start, at(0));
+            // Changes: a, b, d; failed: b, d
+            assert!((metrics.change_failure_rate - 66.67).abs() < 0.01);
+        }
+    }
+}
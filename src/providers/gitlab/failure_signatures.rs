@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use crate::config::SignatureRule;
+use crate::error::{CILensError, Result};
+use crate::insights::FailureSignature;
+
+/// Number of example job links kept per signature.
+const MAX_EXAMPLE_LINKS: usize = 5;
+
+/// Maximum length of a normalized error line used as a signature.
+const MAX_SIGNATURE_LENGTH: usize = 120;
+
+/// Signature of failures whose logs contain no recognizable error line.
+const UNCLASSIFIED: &str = "Unclassified";
+
+/// Built-in signatures, tried after the configured ones.
+const BUILT_IN_SIGNATURES: &[(&str, &str)] = &[
+    (
+        "Docker pull rate limit",
+        r"(?i)toomanyrequests|pull rate limit|too many requests.*registry",
+    ),
+    (
+        "Out of memory",
+        r"(?i)out of memory|oomkilled|cannot allocate memory|std::bad_alloc|memoryerror|exit code 137",
+    ),
+    (
+        "Segmentation fault",
+        r"(?i)segmentation fault|sigsegv|core dumped|exit code 139",
+    ),
+    (
+        "Network error",
+        r"(?i)connection (refused|reset|timed out)|could not resolve host|temporary failure in name resolution|network is unreachable|econnreset|econnrefused|eai_again|tls handshake timeout|502 bad gateway|503 service unavailable",
+    ),
+    (
+        "Timeout",
+        r"(?i)execution took longer than|timed out|timeout exceeded|deadline exceeded|etimedout",
+    ),
+];
+
+/// Compiled failure signatures from the `[[signatures]]` config tables plus the
+/// built-in ones, used to classify failed job logs.
+///
+/// Configured signatures are tried in order before the built-in ones, and the first
+/// signature matching any line of a log wins. Logs that match no signature are
+/// clustered by their last error line with numbers and hashes masked.
+#[derive(Debug)]
+pub struct SignatureRules {
+    signatures: Vec<(String, Regex)>,
+    ansi: Regex,
+    timestamp: Regex,
+    error_line: Regex,
+    variable: Regex,
+}
+
+/// Signature of a single failed job execution.
+pub(super) struct ClassifiedFailure {
+    pub job_name: String,
+    pub job_url: String,
+    pub signature: String,
+    pub error_line: String,
+}
+
+impl SignatureRules {
+    /// Compiles configured failure signatures together with the built-in ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CILensError::Config` if a signature has no name or an invalid pattern.
+    pub fn new(rules: &[SignatureRule]) -> Result<Self> {
+        let configured = rules.iter().map(|rule| {
+            if rule.name.trim().is_empty() {
+                return Err(CILensError::Config(
+                    "failure signatures require a non-empty `name`".to_string(),
+                ));
+            }
+            let pattern = Regex::new(&rule.pattern).map_err(|e| {
+                CILensError::Config(format!(
+                    "invalid failure signature pattern '{}': {e}",
+                    rule.pattern
+                ))
+            })?;
+            Ok((rule.name.clone(), pattern))
+        });
+        let built_in = BUILT_IN_SIGNATURES
+            .iter()
+            .map(|(name, pattern)| Ok((name.to_string(), compile(pattern))));
+
+        Ok(Self {
+            signatures: configured.chain(built_in).collect::<Result<_>>()?,
+            ansi: compile(r"\x1b\[[0-9;?]*[A-Za-z]|\x1b\][^\x07]*\x07"),
+            timestamp: compile(
+                r"^(\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:?\d{2})?\s+(\d{2}[OE]\+?\s?)?|\[\d{4}-\d{2}-\d{2}[T ][^\]]*\]\s*)",
+            ),
+            error_line: compile(r"(?i)\b(error|failed|failure|fatal|exception|panicked)\b"),
+            variable: compile(r"\b[0-9a-f]{7,40}\b|\d+"),
+        })
+    }
+
+    /// Strips ANSI escape codes, GitLab section markers and line timestamps from a job log.
+    pub(super) fn clean_trace(&self, trace: &str) -> Vec<String> {
+        trace
+            .lines()
+            .map(|line| {
+                let line = self.ansi.replace_all(line, "");
+                // Carriage returns redraw the line: keep what was drawn last
+                let line = line.rsplit('\r').find(|part| !part.trim().is_empty());
+                let line = line.unwrap_or_default();
+                self.timestamp.replace(line, "").trim().to_string()
+            })
+            .filter(|line| !line.is_empty() && !line.starts_with("section_"))
+            .collect()
+    }
+
+    /// Classifies a job log, returning its signature and the error line that matched.
+    pub(super) fn classify(&self, trace: &str) -> (String, String) {
+        let lines = self.clean_trace(trace);
+
+        for (name, pattern) in &self.signatures {
+            if let Some(line) = lines.iter().rev().find(|line| pattern.is_match(line)) {
+                return (name.clone(), line.clone());
+            }
+        }
+
+        // GitLab closes every failed log with the exit code, which says little by itself
+        lines
+            .iter()
+            .rev()
+            .filter(|line| !line.starts_with("ERROR: Job failed: exit code"))
+            .find(|line| self.error_line.is_match(line))
+            .map_or_else(
+                || (UNCLASSIFIED.to_string(), String::new()),
+                |line| (self.normalize(line), line.clone()),
+            )
+    }
+
+    /// Masks numbers and hashes in an error line so similar failures share a signature.
+    fn normalize(&self, line: &str) -> String {
+        let normalized = self.variable.replace_all(line, "N");
+        normalized.chars().take(MAX_SIGNATURE_LENGTH).collect()
+    }
+}
+
+fn compile(pattern: &str) -> Regex {
+    Regex::new(pattern).expect("built-in pattern is valid")
+}
+
+/// Clusters classified job failures by signature.
+///
+/// # Returns
+///
+/// Signatures ordered by number of failures, with the affected jobs ordered the same way.
+pub(super) fn cluster_failures(failures: Vec<ClassifiedFailure>) -> Vec<FailureSignature> {
+    let mut clusters: HashMap<String, Vec<ClassifiedFailure>> = HashMap::new();
+    for failure in failures {
+        clusters
+            .entry(failure.signature.clone())
+            .or_default()
+            .push(failure);
+    }
+
+    let mut signatures: Vec<FailureSignature> = clusters
+        .into_iter()
+        .map(|(signature, failures)| {
+            let mut job_counts: HashMap<&str, usize> = HashMap::new();
+            for failure in &failures {
+                *job_counts.entry(failure.job_name.as_str()).or_insert(0) += 1;
+            }
+            let mut jobs: Vec<(&str, usize)> = job_counts.into_iter().collect();
+            jobs.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+            FailureSignature {
+                count: failures.len(),
+                jobs: jobs.into_iter().map(|(name, _)| name.to_string()).collect(),
+                example_line: failures[0].error_line.clone(),
+                example_links: failures
+                    .iter()
+                    .take(MAX_EXAMPLE_LINKS)
+                    .map(|failure| failure.job_url.clone())
+                    .collect(),
+                signature,
+            }
+        })
+        .collect();
+
+    signatures.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.signature.cmp(&b.signature))
+    });
+    signatures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> SignatureRules {
+        SignatureRules::new(&[SignatureRule {
+            name: "Flaky database".to_string(),
+            pattern: r"could not connect to server: .*5432".to_string(),
+        }])
+        .unwrap()
+    }
+
+    fn failure(job_name: &str, job_id: u32, signature: &str) -> ClassifiedFailure {
+        ClassifiedFailure {
+            job_name: job_name.to_string(),
+            job_url: format!("https://gitlab.com/org/repo/-/jobs/{job_id}"),
+            signature: signature.to_string(),
+            error_line: format!("{signature} line"),
+        }
+    }
+
+    mod clean_trace {
+        use super::*;
+
+        #[test]
+        fn strips_ansi_codes_sections_and_timestamps() {
+            let trace =
+                "\x1b[0Ksection_start:1700000000:step_script\r\x1b[0K\x1b[32;1mRunning\x1b[0;m\n\
+                         2025-01-01T10:00:00.123456Z 01E \x1b[31mError: boom\x1b[0m\n\
+                         [2025-01-01 10:00:01] done\n\
+                         progress 10%\rprogress 100%\n";
+
+            let lines = rules().clean_trace(trace);
+
+            assert_eq!(
+                lines,
+                vec!["Running", "Error: boom", "done", "progress 100%"]
+            );
+        }
+    }
+
+    mod classify {
+        use super::*;
+
+        #[test]
+        fn matches_configured_before_built_in_signatures() {
+            let trace = "psql: could not connect to server: Connection refused on port 5432\n\
+                         ERROR: Job failed: exit code 1\n";
+
+            let (signature, line) = rules().classify(trace);
+
+            assert_eq!(signature, "Flaky database");
+            assert!(line.starts_with("psql:"));
+        }
+
+        #[test]
+        fn matches_built_in_signatures() {
+            let rules = rules();
+
+            assert_eq!(
+                rules
+                    .classify("toomanyrequests: You have reached your pull rate limit")
+                    .0,
+                "Docker pull rate limit"
+            );
+            assert_eq!(
+                rules
+                    .classify("FATAL ERROR: JavaScript heap out of memory")
+                    .0,
+                "Out of memory"
+            );
+            assert_eq!(
+                rules
+                    .classify("ERROR: Job failed: execution took longer than 1h0m0s seconds")
+                    .0,
+                "Timeout"
+            );
+        }
+
+        #[test]
+        fn falls_back_to_normalized_last_error_line() {
+            let first = "error[E0308]: mismatched types at src/main.rs:12\n\
+                         ERROR: Job failed: exit code 1\n";
+            let second = "error[E0308]: mismatched types at src/main.rs:40\n";
+
+            let rules = rules();
+            let (signature, line) = rules.classify(first);
+
+            assert_eq!(signature, "error[EN]: mismatched types at src/main.rs:N");
+            assert_eq!(line, "error[E0308]: mismatched types at src/main.rs:12");
+            assert_eq!(rules.classify(second).0, signature);
+            assert_eq!(rules.classify("all good\n").0, UNCLASSIFIED);
+        }
+    }
+
+    mod cluster_failures {
+        use super::*;
+
+        #[test]
+        fn groups_by_signature_most_frequent_first() {
+            let failures = vec![
+                failure("e2e", 1, "Timeout"),
+                failure("unit", 2, "Out of memory"),
+                failure("e2e", 3, "Out of memory"),
+                failure("unit", 4, "Out of memory"),
+            ];
+
+            let signatures = cluster_failures(failures);
+
+            assert_eq!(signatures.len(), 2);
+            assert_eq!(signatures[0].signature, "Out of memory");
+            assert_eq!(signatures[0].count, 3);
+            assert_eq!(signatures[0].jobs, vec!["unit", "e2e"]);
+            assert_eq!(signatures[0].example_links.len(), 3);
+            assert_eq!(signatures[1].signature, "Timeout");
+        }
+    }
+}
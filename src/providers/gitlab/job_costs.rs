@@ -0,0 +1,364 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::types::{GitLabJob, GitLabPipeline};
+use crate::insights::{CostBreakdown, CostClassTotal};
+
+/// Cost class used when no tag or runner rate matches a job.
+const DEFAULT_CLASS: &str = "default";
+
+/// Per-minute compute rates keyed by the runner a job ran on.
+///
+/// A job is priced by the first match of:
+/// 1. its runner tags (the highest configured rate wins if several tags match),
+/// 2. its runner type (`instance`, `group`, `project`) or a machine type contained in the
+///    runner description (e.g., `saas-linux-xlarge`); the longest matching key wins,
+/// 3. the default rate.
+#[derive(Debug, Clone, Default)]
+pub struct CostRates {
+    default: Option<f64>,
+    tags: HashMap<String, f64>,
+    runner_types: HashMap<String, f64>,
+}
+
+impl CostRates {
+    /// Builds cost rates from configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `default` - Rate for jobs that match no tag or runner type
+    /// * `tags` - Rates keyed by runner tag
+    /// * `runner_types` - Rates keyed by runner type or machine type
+    ///
+    /// # Returns
+    ///
+    /// `None` if no rate is configured at all, so cost analysis stays disabled.
+    pub fn from_rates(
+        default: Option<f64>,
+        tags: &HashMap<String, f64>,
+        runner_types: &HashMap<String, f64>,
+    ) -> Option<Self> {
+        if default.is_none() && tags.is_empty() && runner_types.is_empty() {
+            return None;
+        }
+
+        let lowercase = |rates: &HashMap<String, f64>| {
+            rates
+                .iter()
+                .map(|(key, rate)| (key.to_lowercase(), *rate))
+                .collect()
+        };
+
+        Some(Self {
+            default,
+            tags: lowercase(tags),
+            runner_types: lowercase(runner_types),
+        })
+    }
+
+    /// Returns the cost class and per-minute rate for a job.
+    fn rate_for(&self, job: &GitLabJob) -> (String, f64) {
+        let tag_rate = job
+            .tags
+            .iter()
+            .filter_map(|tag| {
+                let tag = tag.to_lowercase();
+                self.tags.get(&tag).map(|rate| (tag, *rate))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1).then_with(|| b.0.cmp(&a.0)));
+
+        if let Some((tag, rate)) = tag_rate {
+            return (format!("tag:{tag}"), rate);
+        }
+
+        let runner_rate = job.runner.as_ref().and_then(|runner| {
+            let description = runner
+                .description
+                .as_deref()
+                .unwrap_or_default()
+                .to_lowercase();
+            self.runner_types
+                .iter()
+                .filter(|(key, _)| {
+                    **key == runner.runner_type || description.contains(key.as_str())
+                })
+                .max_by(|a, b| a.0.len().cmp(&b.0.len()).then_with(|| b.0.cmp(a.0)))
+        });
+
+        if let Some((key, rate)) = runner_rate {
+            return (format!("runner:{key}"), *rate);
+        }
+
+        (DEFAULT_CLASS.to_string(), self.default.unwrap_or(0.0))
+    }
+}
+
+/// Cost of all executions of one job.
+#[derive(Debug, Clone, Default)]
+pub(super) struct JobCost {
+    /// Cost split into useful and wasted compute
+    pub breakdown: CostBreakdown,
+    /// Runner minutes and cost per cost class
+    pub by_class: BTreeMap<String, CostClassTotal>,
+}
+
+/// Calculates per-job compute cost from the actual duration of every execution.
+///
+/// Each execution is charged `duration * rate / 60`, where the rate depends on the runner
+/// it ran on (see [`CostRates`]), and assigned to a bucket:
+/// - `canceled_pipelines`: any execution in a pipeline that was canceled
+/// - `failed_pipelines`: any execution in another pipeline that did not succeed
+/// - `flaky_retries`: retried executions in a successful pipeline
+/// - `useful`: the final execution of each job in a successful pipeline
+///
+/// # Arguments
+///
+/// * `pipelines` - All pipelines of a type, successful, failed and canceled
+/// * `rates` - Cost rates by runner tag and runner type
+///
+/// # Returns
+///
+/// Cost breakdown and per-class totals keyed by job name.
+pub(super) fn calculate_job_costs(
+    pipelines: &[&GitLabPipeline],
+    rates: &CostRates,
+) -> HashMap<String, JobCost> {
+    let mut costs: HashMap<String, JobCost> = HashMap::new();
+
+    for pipeline in pipelines {
+        let pipeline_succeeded = pipeline.status == "success";
+        let pipeline_canceled = pipeline.status == "canceled";
+
+        for job in &pipeline.jobs {
+            let (class, rate) = rates.rate_for(job);
+            let minutes = job.duration / 60.0;
+            let cost = minutes * rate;
+            let job_cost = costs.entry(job.name.clone()).or_default();
+
+            let class_total = job_cost.by_class.entry(class).or_default();
+            class_total.minutes += minutes;
+            class_total.cost += cost;
+
+            if pipeline_canceled {
+                job_cost.breakdown.canceled_pipelines += cost;
+            } else if !pipeline_succeeded {
+                job_cost.breakdown.failed_pipelines += cost;
+            } else if job.retried {
+                job_cost.breakdown.flaky_retries += cost;
+            } else {
+                job_cost.breakdown.useful += cost;
+            }
+        }
+    }
+
+    costs
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use crate::providers::gitlab::types::GitLabRunner;
+
+    fn create_job(name: &str, duration: f64, retried: bool) -> GitLabJob {
+        GitLabJob {
+            id: format!("gid://gitlab/Ci::Job/{name}"),
+            name: name.to_string(),
+            stage: "test".to_string(),
+            duration,
+            status: if retried { "FAILED" } else { "SUCCESS" }.to_string(),
+            retried,
+            needs: None,
+            tags: vec![],
+            runner: None,
+            test_cases: vec![],
+        }
+    }
+
+    fn create_pipeline(status: &str, jobs: Vec<GitLabJob>) -> GitLabPipeline {
+        GitLabPipeline {
+            id: "gid://gitlab/Ci::Pipeline/1".to_string(),
+            ref_: "main".to_string(),
+            sha: "abc123".to_string(),
+            weight: 1.0,
+            source: "push".to_string(),
+            status: status.to_string(),
+            duration: 0,
+            created_at: None,
+            finished_at: None,
+            merge_request: None,
+            stages: vec!["test".to_string()],
+            jobs,
+        }
+    }
+
+    fn flat_rate(rate: f64) -> CostRates {
+        CostRates::from_rates(Some(rate), &HashMap::new(), &HashMap::new()).unwrap()
+    }
+
+    mod calculate_job_costs {
+        use super::*;
+
+        #[test]
+        fn sums_actual_durations_of_every_execution() {
+            let fast = create_pipeline("success", vec![create_job("test", 60.0, false)]);
+            let slow = create_pipeline("success", vec![create_job("test", 600.0, false)]);
+            let pipelines = vec![&fast, &slow];
+
+            let costs = calculate_job_costs(&pipelines, &flat_rate(1.0));
+
+            assert_eq!(
+                costs["test"].breakdown.useful, 11.0,
+                "Long-tail runs should be charged at their real duration"
+            );
+        }
+
+        #[test]
+        fn charges_retries_in_successful_pipelines_as_flaky() {
+            let pipeline = create_pipeline(
+                "success",
+                vec![
+                    create_job("test", 120.0, true),
+                    create_job("test", 60.0, false),
+                ],
+            );
+            let pipelines = vec![&pipeline];
+
+            let costs = calculate_job_costs(&pipelines, &flat_rate(1.0));
+
+            assert_eq!(costs["test"].breakdown.flaky_retries, 2.0);
+            assert_eq!(costs["test"].breakdown.useful, 1.0);
+            assert_eq!(costs["test"].breakdown.total(), 3.0);
+        }
+
+        #[test]
+        fn charges_every_execution_in_failed_pipelines_as_failed() {
+            let pipeline = create_pipeline(
+                "failed",
+                vec![
+                    create_job("build", 60.0, false),
+                    create_job("test", 30.0, true),
+                    create_job("test", 30.0, false),
+                ],
+            );
+            let pipelines = vec![&pipeline];
+
+            let costs = calculate_job_costs(&pipelines, &flat_rate(2.0));
+
+            assert_eq!(costs["build"].breakdown.failed_pipelines, 2.0);
+            assert_eq!(costs["test"].breakdown.failed_pipelines, 2.0);
+            assert_eq!(costs["test"].breakdown.useful, 0.0);
+            assert_eq!(costs["test"].breakdown.wasted(), 2.0);
+        }
+
+        #[test]
+        fn charges_executions_in_canceled_pipelines_as_canceled() {
+            let pipeline = create_pipeline("canceled", vec![create_job("build", 60.0, false)]);
+            let pipelines = vec![&pipeline];
+
+            let costs = calculate_job_costs(&pipelines, &flat_rate(2.0));
+
+            assert_eq!(costs["build"].breakdown.canceled_pipelines, 2.0);
+            assert_eq!(costs["build"].breakdown.failed_pipelines, 0.0);
+            assert_eq!(costs["build"].breakdown.wasted(), 2.0);
+        }
+
+        #[test]
+        fn reports_totals_per_cost_class() {
+            let mut gpu_job = create_job("train", 120.0, false);
+            gpu_job.tags = vec!["docker".to_string(), "GPU".to_string()];
+            let pipeline =
+                create_pipeline("success", vec![gpu_job, create_job("lint", 60.0, false)]);
+            let pipelines = vec![&pipeline];
+            let rates = CostRates::from_rates(
+                Some(1.0),
+                &HashMap::from([("gpu".to_string(), 10.0)]),
+                &HashMap::new(),
+            )
+            .unwrap();
+
+            let costs = calculate_job_costs(&pipelines, &rates);
+
+            let train = &costs["train"].by_class["tag:gpu"];
+            assert_eq!(train.minutes, 2.0);
+            assert_eq!(train.cost, 20.0);
+            assert_eq!(costs["lint"].by_class["default"].cost, 1.0);
+        }
+    }
+
+    mod cost_rates {
+        use super::*;
+
+        #[test]
+        fn returns_none_when_nothing_is_configured() {
+            assert!(CostRates::from_rates(None, &HashMap::new(), &HashMap::new()).is_none());
+        }
+
+        #[test]
+        fn prefers_highest_matching_tag_rate() {
+            let mut job = create_job("build", 60.0, false);
+            job.tags = vec!["docker".to_string(), "gpu".to_string()];
+            let rates = CostRates::from_rates(
+                Some(1.0),
+                &HashMap::from([("docker".to_string(), 2.0), ("gpu".to_string(), 8.0)]),
+                &HashMap::new(),
+            )
+            .unwrap();
+
+            assert_eq!(rates.rate_for(&job), ("tag:gpu".to_string(), 8.0));
+        }
+
+        #[test]
+        fn matches_machine_type_in_runner_description() {
+            let mut job = create_job("build", 60.0, false);
+            job.runner = Some(GitLabRunner {
+                runner_type: "instance".to_string(),
+                description: Some("3-blue.saas-linux-xlarge-amd64.runners-manager".to_string()),
+            });
+            let rates = CostRates::from_rates(
+                Some(1.0),
+                &HashMap::new(),
+                &HashMap::from([
+                    ("instance".to_string(), 0.5),
+                    ("saas-linux-xlarge".to_string(), 4.0),
+                ]),
+            )
+            .unwrap();
+
+            assert_eq!(
+                rates.rate_for(&job),
+                ("runner:saas-linux-xlarge".to_string(), 4.0),
+                "The most specific (longest) runner key should win"
+            );
+        }
+
+        #[test]
+        fn matches_runner_type() {
+            let mut job = create_job("build", 60.0, false);
+            job.runner = Some(GitLabRunner {
+                runner_type: "project".to_string(),
+                description: Some("my-shell-runner".to_string()),
+            });
+            let rates = CostRates::from_rates(
+                None,
+                &HashMap::new(),
+                &HashMap::from([("project".to_string(), 0.2)]),
+            )
+            .unwrap();
+
+            assert_eq!(rates.rate_for(&job), ("runner:project".to_string(), 0.2));
+        }
+
+        #[test]
+        fn falls_back_to_default_rate() {
+            let job = create_job("build", 60.0, false);
+            let rates = CostRates::from_rates(
+                None,
+                &HashMap::from([("gpu".to_string(), 8.0)]),
+                &HashMap::new(),
+            )
+            .unwrap();
+
+            assert_eq!(rates.rate_for(&job), ("default".to_string(), 0.0));
+        }
+    }
+}
@@ -1,8 +1,8 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use super::pipeline_metrics::cmp_f64;
 use super::types::{GitLabJob, GitLabPipeline};
 use crate::insights::{JobCountWithLinks, JobMetrics, PredecessorJob};
+use crate::providers::stats::cmp_f64;
 
 /// Calculates metrics for all jobs in a single pipeline.
 ///
@@ -29,26 +29,7 @@ pub fn calculate_job_metrics(pipeline: &GitLabPipeline) -> Vec<JobMetrics> {
 
     let job_map: HashMap<&str, &GitLabJob> =
         pipeline.jobs.iter().map(|j| (j.name.as_str(), j)).collect();
-
-    let stage_index: HashMap<&str, usize> = pipeline
-        .stages
-        .iter()
-        .enumerate()
-        .map(|(i, s)| (s.as_str(), i))
-        .collect();
-
-    let mut finish_times = HashMap::new();
-    let mut predecessors = HashMap::new();
-
-    for &job_name in job_map.keys() {
-        calculate_finish_time(
-            job_name,
-            &job_map,
-            &stage_index,
-            &mut finish_times,
-            &mut predecessors,
-        );
-    }
+    let (finish_times, predecessors) = calculate_finish_times(pipeline, &job_map);
 
     let mut metrics: Vec<JobMetrics> = job_map
         .iter()
@@ -76,6 +57,10 @@ pub fn calculate_job_metrics(pipeline: &GitLabPipeline) -> Vec<JobMetrics> {
                 total_executions: 0,
                 cost_per_execution: None,
                 total_cost: None,
+                cost_breakdown: None,
+                cost_by_class: BTreeMap::new(),
+                billable_minutes: None,
+                tests: vec![],
             }
         })
         .collect();
@@ -85,6 +70,47 @@ pub fn calculate_job_metrics(pipeline: &GitLabPipeline) -> Vec<JobMetrics> {
     metrics
 }
 
+/// Calculates when each job finishes relative to the pipeline start.
+///
+/// Uses the same dependency model as time-to-feedback: a job starts once its slowest
+/// dependency (explicit `needs`, or all jobs in previous stages) has finished.
+///
+/// # Returns
+///
+/// Finish time in seconds keyed by job name.
+pub(super) fn job_finish_times(pipeline: &GitLabPipeline) -> HashMap<&str, f64> {
+    let job_map: HashMap<&str, &GitLabJob> =
+        pipeline.jobs.iter().map(|j| (j.name.as_str(), j)).collect();
+    calculate_finish_times(pipeline, &job_map).0
+}
+
+fn calculate_finish_times<'a>(
+    pipeline: &'a GitLabPipeline,
+    job_map: &HashMap<&'a str, &'a GitLabJob>,
+) -> (HashMap<&'a str, f64>, HashMap<&'a str, &'a str>) {
+    let stage_index: HashMap<&str, usize> = pipeline
+        .stages
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (s.as_str(), i))
+        .collect();
+
+    let mut finish_times = HashMap::new();
+    let mut predecessors = HashMap::new();
+
+    for &job_name in job_map.keys() {
+        calculate_finish_time(
+            job_name,
+            job_map,
+            &stage_index,
+            &mut finish_times,
+            &mut predecessors,
+        );
+    }
+
+    (finish_times, predecessors)
+}
+
 fn build_predecessor_list(
     job_name: &str,
     predecessors: &HashMap<&str, &str>,
@@ -186,6 +212,9 @@ mod tests {
             status: "SUCCESS".to_string(),
             retried: false,
             needs,
+            tags: vec![],
+            runner: None,
+            test_cases: vec![],
         }
     }
 
@@ -194,9 +223,14 @@ mod tests {
         GitLabPipeline {
             id: "test-pipeline".to_string(),
             ref_: "main".to_string(),
+            sha: "abc123".to_string(),
+            weight: 1.0,
             source: "push".to_string(),
             status: "success".to_string(),
             duration: 100,
+            created_at: None,
+            finished_at: None,
+            merge_request: None,
             stages,
             jobs,
         }
@@ -1,17 +1,25 @@
 use std::collections::HashMap;
 
-use super::links::job_id_to_url;
+use super::links::{job_id_to_url, pipeline_id_to_url};
 use super::types::{GitLabJob, GitLabPipeline};
 
-#[allow(clippy::cast_precision_loss)]
-fn calculate_rate(count: usize, total: usize) -> f64 {
-    if total > 0 {
-        (count as f64 / total as f64) * 100.0
+fn calculate_rate(count: f64, total: f64) -> f64 {
+    if total > 0.0 {
+        (count / total) * 100.0
     } else {
         0.0
     }
 }
 
+/// Execution, flaky retry and failure counts weighted by pipeline sampling weight,
+/// so rates reflect the pipeline population rather than the sample.
+#[derive(Default)]
+struct WeightedCounts {
+    executions: f64,
+    flaky: f64,
+    failed: f64,
+}
+
 pub(super) struct JobReliabilityMetrics {
     pub total_executions: usize,
     pub flakiness_rate: f64,
@@ -22,6 +30,7 @@ pub(super) struct JobReliabilityMetrics {
     pub failed_job_links: Vec<String>,
 }
 
+#[allow(clippy::cast_precision_loss)]
 pub(super) fn calculate_job_reliability(
     pipelines: &[&GitLabPipeline],
     base_url: &str,
@@ -32,12 +41,16 @@ pub(super) fn calculate_job_reliability(
     let mut flaky_job_links: HashMap<String, Vec<String>> = HashMap::new();
     let mut failed_executions: HashMap<String, usize> = HashMap::new();
     let mut failed_job_links: HashMap<String, Vec<String>> = HashMap::new();
+    let mut weighted: HashMap<String, WeightedCounts> = HashMap::new();
+    let cross_pipeline_flakes = find_cross_pipeline_flakes(pipelines);
 
     for pipeline in pipelines {
         let jobs_by_name = group_jobs_by_name(&pipeline.jobs);
 
         for (name, jobs) in jobs_by_name {
             *execution_counts.entry(name.to_string()).or_insert(0) += jobs.len();
+            let weighted = weighted.entry(name.to_string()).or_default();
+            weighted.executions += jobs.len() as f64 * pipeline.weight;
 
             if is_job_flaky(&jobs) {
                 let retry_links: Vec<String> = jobs
@@ -46,12 +59,26 @@ pub(super) fn calculate_job_reliability(
                     .map(|j| job_id_to_url(base_url, project_path, &j.id))
                     .collect();
                 *flaky_retries.entry(name.to_string()).or_insert(0) += retry_links.len();
+                weighted.flaky += retry_links.len() as f64 * pipeline.weight;
                 flaky_job_links
                     .entry(name.to_string())
                     .or_default()
                     .extend(retry_links);
+            } else if let Some(passed_in) = cross_pipeline_flakes.get(&(pipeline.id.as_str(), name))
+            {
+                // Failed here but passed in a rerun of the same commit: link both pipelines
+                *flaky_retries.entry(name.to_string()).or_insert(0) += 1;
+                weighted.flaky += pipeline.weight;
+                flaky_job_links
+                    .entry(name.to_string())
+                    .or_default()
+                    .extend([
+                        pipeline_id_to_url(base_url, project_path, &pipeline.id),
+                        pipeline_id_to_url(base_url, project_path, passed_in),
+                    ]);
             } else if is_job_failed(&jobs) {
                 *failed_executions.entry(name.to_string()).or_insert(0) += 1;
+                weighted.failed += pipeline.weight;
                 // Get the final non-retried job (the one that failed)
                 if let Some(final_job) = jobs.iter().find(|j| !j.retried) {
                     failed_job_links
@@ -69,6 +96,7 @@ pub(super) fn calculate_job_reliability(
         &failed_executions,
         &failed_job_links,
         &execution_counts,
+        &weighted,
     )
 }
 
@@ -78,6 +106,7 @@ fn compute_reliability_metrics(
     failure_counts: &HashMap<String, usize>,
     failure_job_links: &HashMap<String, Vec<String>>,
     execution_counts: &HashMap<String, usize>,
+    weighted_counts: &HashMap<String, WeightedCounts>,
 ) -> HashMap<String, JobReliabilityMetrics> {
     execution_counts
         .iter()
@@ -86,15 +115,19 @@ fn compute_reliability_metrics(
             let failed_executions = *failure_counts.get(name).unwrap_or(&0);
             let flaky_job_links = retry_job_links.get(name).cloned().unwrap_or_default();
             let failed_job_links = failure_job_links.get(name).cloned().unwrap_or_default();
+            let weighted = weighted_counts.get(name);
+            let rate = |count: fn(&WeightedCounts) -> f64| {
+                weighted.map_or(0.0, |w| calculate_rate(count(w), w.executions))
+            };
 
             (
                 name.clone(),
                 JobReliabilityMetrics {
                     total_executions,
-                    flakiness_rate: calculate_rate(flaky_retries, total_executions),
+                    flakiness_rate: rate(|w| w.flaky),
                     flaky_retries,
                     flaky_job_links,
-                    failure_rate: calculate_rate(failed_executions, total_executions),
+                    failure_rate: rate(|w| w.failed),
                     failed_executions,
                     failed_job_links,
                 },
@@ -103,6 +136,47 @@ fn compute_reliability_metrics(
         .collect()
 }
 
+/// Finds jobs that failed in one pipeline but passed in another pipeline for the same
+/// commit SHA and ref, e.g. after the pipeline was run again instead of retrying the job.
+///
+/// Returns the ID of a pipeline where the job passed, keyed by the ID of the pipeline
+/// where it failed and the job name.
+fn find_cross_pipeline_flakes<'a>(
+    pipelines: &[&'a GitLabPipeline],
+) -> HashMap<(&'a str, &'a str), &'a str> {
+    let mut by_commit: HashMap<(&str, &str), Vec<&GitLabPipeline>> = HashMap::new();
+    for pipeline in pipelines.iter().filter(|p| !p.sha.is_empty()) {
+        by_commit
+            .entry((pipeline.sha.as_str(), pipeline.ref_.as_str()))
+            .or_default()
+            .push(pipeline);
+    }
+
+    let mut flakes = HashMap::new();
+    for same_commit in by_commit.values().filter(|group| group.len() > 1) {
+        let mut passed_in: HashMap<&str, &str> = HashMap::new();
+        let mut failed_in: Vec<(&str, &str)> = Vec::new();
+
+        for pipeline in same_commit {
+            for (name, jobs) in group_jobs_by_name(&pipeline.jobs) {
+                if is_job_failed(&jobs) {
+                    failed_in.push((pipeline.id.as_str(), name));
+                } else {
+                    passed_in.entry(name).or_insert(pipeline.id.as_str());
+                }
+            }
+        }
+
+        for (pipeline_id, name) in failed_in {
+            if let Some(passed_pipeline_id) = passed_in.get(name) {
+                flakes.insert((pipeline_id, name), *passed_pipeline_id);
+            }
+        }
+    }
+
+    flakes
+}
+
 fn group_jobs_by_name(jobs: &[GitLabJob]) -> HashMap<&str, Vec<&GitLabJob>> {
     jobs.iter().fold(HashMap::new(), |mut grouped, job| {
         grouped.entry(job.name.as_str()).or_default().push(job);
@@ -144,6 +218,9 @@ mod tests {
             status: status.to_string(),
             retried,
             needs: None,
+            tags: vec![],
+            runner: None,
+            test_cases: vec![],
         }
     }
 
@@ -153,37 +230,37 @@ mod tests {
 
         #[test]
         fn returns_zero_when_total_is_zero() {
-            let result = calculate_rate(5, 0);
+            let result = calculate_rate(5.0, 0.0);
             assert_eq!(result, 0.0, "Should return 0.0 when total is 0");
         }
 
         #[test]
         fn returns_zero_when_count_is_zero() {
-            let result = calculate_rate(0, 100);
+            let result = calculate_rate(0.0, 100.0);
             assert_eq!(result, 0.0, "Should return 0.0 when count is 0");
         }
 
         #[test]
         fn calculates_percentage_correctly() {
-            let result = calculate_rate(25, 100);
+            let result = calculate_rate(25.0, 100.0);
             assert_eq!(result, 25.0, "Should calculate 25% correctly");
         }
 
         #[test]
         fn calculates_fifty_percent() {
-            let result = calculate_rate(50, 100);
+            let result = calculate_rate(50.0, 100.0);
             assert_eq!(result, 50.0, "Should calculate 50% correctly");
         }
 
         #[test]
         fn calculates_one_hundred_percent() {
-            let result = calculate_rate(100, 100);
+            let result = calculate_rate(100.0, 100.0);
             assert_eq!(result, 100.0, "Should calculate 100% correctly");
         }
 
         #[test]
         fn handles_fractional_percentages() {
-            let result = calculate_rate(1, 3);
+            let result = calculate_rate(1.0, 3.0);
             assert!(
                 (result - 33.333_333).abs() < 0.001,
                 "Should handle fractional percentages, got {result}",
@@ -192,19 +269,19 @@ mod tests {
 
         #[test]
         fn handles_small_numbers() {
-            let result = calculate_rate(1, 1);
+            let result = calculate_rate(1.0, 1.0);
             assert_eq!(result, 100.0, "Should handle 1/1 = 100%");
         }
 
         #[test]
         fn handles_large_numbers() {
-            let result = calculate_rate(999, 1000);
+            let result = calculate_rate(999.0, 1000.0);
             assert_eq!(result, 99.9, "Should handle large numbers correctly");
         }
 
         #[test]
         fn returns_zero_for_zero_count_and_total() {
-            let result = calculate_rate(0, 0);
+            let result = calculate_rate(0.0, 0.0);
             assert_eq!(result, 0.0, "Should return 0.0 when both are 0");
         }
     }
@@ -548,9 +625,14 @@ mod tests {
             GitLabPipeline {
                 id: id.to_string(),
                 ref_: "main".to_string(),
+                sha: format!("sha-{id}"),
+                weight: 1.0,
                 source: "push".to_string(),
                 status: "SUCCESS".to_string(),
                 duration: 100,
+                created_at: None,
+                finished_at: None,
+                merge_request: None,
                 stages: vec!["test".to_string()],
                 jobs,
             }
@@ -652,6 +734,63 @@ mod tests {
             );
         }
 
+        #[test]
+        fn counts_failure_passing_in_rerun_of_same_commit_as_flaky() {
+            let failed = GitLabPipeline {
+                sha: "abc123".to_string(),
+                weight: 1.0,
+                ..create_pipeline("1", vec![create_job("1", "test-job", "FAILED", false)])
+            };
+            let rerun = GitLabPipeline {
+                sha: "abc123".to_string(),
+                weight: 1.0,
+                ..create_pipeline("2", vec![create_job("2", "test-job", "SUCCESS", false)])
+            };
+            let pipelines = vec![&failed, &rerun];
+
+            let result = calculate_job_reliability(&pipelines, "https://gitlab.com", "owner/repo");
+
+            let metrics = result.get("test-job").unwrap();
+            assert_eq!(metrics.flaky_retries, 1);
+            assert_eq!(metrics.failed_executions, 0, "Rerun flake is not a failure");
+            assert_eq!(
+                metrics.flaky_job_links,
+                vec![
+                    "https://gitlab.com/owner/repo/-/pipelines/1",
+                    "https://gitlab.com/owner/repo/-/pipelines/2"
+                ]
+            );
+        }
+
+        #[test]
+        fn keeps_failures_on_different_commits() {
+            let failed = create_pipeline("1", vec![create_job("1", "test-job", "FAILED", false)]);
+            let fixed = create_pipeline("2", vec![create_job("2", "test-job", "SUCCESS", false)]);
+            let pipelines = vec![&failed, &fixed];
+
+            let result = calculate_job_reliability(&pipelines, "https://gitlab.com", "owner/repo");
+
+            let metrics = result.get("test-job").unwrap();
+            assert_eq!(metrics.flaky_retries, 0);
+            assert_eq!(metrics.failed_executions, 1);
+        }
+
+        #[test]
+        fn weights_rates_by_pipeline_weight() {
+            let passing = GitLabPipeline {
+                weight: 9.0,
+                ..create_pipeline("1", vec![create_job("1", "test-job", "SUCCESS", false)])
+            };
+            let failing = create_pipeline("2", vec![create_job("2", "test-job", "FAILED", false)]);
+            let pipelines = vec![&passing, &failing];
+
+            let result = calculate_job_reliability(&pipelines, "https://gitlab.com", "owner/repo");
+
+            let metrics = result.get("test-job").unwrap();
+            assert_eq!(metrics.failed_executions, 1, "Counts stay unweighted");
+            assert!((metrics.failure_rate - 10.0).abs() < 0.01);
+        }
+
         #[test]
         fn handles_pipeline_with_no_jobs() {
             let pipeline = create_pipeline("1", vec![]);
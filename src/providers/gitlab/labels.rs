@@ -0,0 +1,260 @@
+use regex::Regex;
+
+use crate::config::LabelRule;
+use crate::error::{CILensError, Result};
+
+/// Prefix marking a pattern as a regular expression instead of a glob.
+const REGEX_PREFIX: &str = "regex:";
+
+/// Compiled pipeline type labelling rules from the `[[labels]]` config tables.
+///
+/// Rules are tried in order and the first match wins. Types that match no rule fall
+/// back to the keyword heuristics in `pipeline_types.rs`.
+#[derive(Debug, Default)]
+pub struct LabelRules {
+    rules: Vec<CompiledRule>,
+}
+
+#[derive(Debug)]
+struct CompiledRule {
+    label: String,
+    refs: Vec<Regex>,
+    sources: Vec<String>,
+    jobs: Vec<Regex>,
+}
+
+impl LabelRules {
+    /// Compiles labelling rules from configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CILensError::Config` if a rule has no label, no criteria, or an invalid pattern.
+    pub fn new(rules: &[LabelRule]) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .map(CompiledRule::new)
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { rules })
+    }
+
+    /// Returns the label of the first rule matching a pipeline type.
+    ///
+    /// # Arguments
+    ///
+    /// * `refs` - Git refs of the type's pipelines
+    /// * `sources` - Trigger sources of the type's pipelines
+    /// * `job_names` - Names of all jobs in the type
+    pub(super) fn label_for(
+        &self,
+        refs: &[String],
+        sources: &[String],
+        job_names: &[String],
+    ) -> Option<String> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(refs, sources, job_names))
+            .map(|rule| rule.label.clone())
+    }
+}
+
+impl CompiledRule {
+    fn new(rule: &LabelRule) -> Result<Self> {
+        if rule.label.trim().is_empty() {
+            return Err(CILensError::Config(
+                "label rules require a non-empty `label`".to_string(),
+            ));
+        }
+
+        if rule.refs.is_empty() && rule.sources.is_empty() && rule.jobs.is_empty() {
+            return Err(CILensError::Config(format!(
+                "label rule '{}' needs at least one of `refs`, `sources` or `jobs`",
+                rule.label
+            )));
+        }
+
+        let compile_all = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|pattern| compile_pattern(pattern))
+                .collect::<Result<Vec<_>>>()
+        };
+
+        Ok(Self {
+            label: rule.label.clone(),
+            refs: compile_all(&rule.refs)?,
+            sources: rule.sources.iter().map(|s| s.to_lowercase()).collect(),
+            jobs: compile_all(&rule.jobs)?,
+        })
+    }
+
+    /// Every ref and every source must match; at least one job name must match.
+    fn matches(&self, refs: &[String], sources: &[String], job_names: &[String]) -> bool {
+        let matches_any = |patterns: &[Regex], value: &str| {
+            patterns.iter().any(|pattern| pattern.is_match(value))
+        };
+
+        let refs_match = self.refs.is_empty()
+            || (!refs.is_empty() && refs.iter().all(|r| matches_any(&self.refs, r)));
+        let sources_match = self.sources.is_empty()
+            || (!sources.is_empty()
+                && sources
+                    .iter()
+                    .all(|source| self.sources.contains(&source.to_lowercase())));
+        let jobs_match =
+            self.jobs.is_empty() || job_names.iter().any(|name| matches_any(&self.jobs, name));
+
+        refs_match && sources_match && jobs_match
+    }
+}
+
+/// Compiles a glob or `regex:` prefixed pattern into an anchored regular expression.
+///
+/// Regexes are wrapped in `^(?:...)$`, so like globs they must match the whole value.
+fn compile_pattern(pattern: &str) -> Result<Regex> {
+    let source = match pattern.strip_prefix(REGEX_PREFIX) {
+        Some(regex) => format!("^(?:{regex})$"),
+        None => glob_to_regex(pattern),
+    };
+
+    Regex::new(&source)
+        .map_err(|e| CILensError::Config(format!("invalid label pattern '{pattern}': {e}")))
+}
+
+/// Translates a glob into an anchored regex: `*` matches any characters (including `/`)
+/// and `?` matches exactly one.
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            _ => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(label: &str, refs: &[&str], sources: &[&str], jobs: &[&str]) -> LabelRule {
+        LabelRule {
+            label: label.to_string(),
+            refs: strings(refs),
+            sources: strings(sources),
+            jobs: strings(jobs),
+        }
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| (*v).to_string()).collect()
+    }
+
+    mod label_for {
+        use super::*;
+
+        #[test]
+        fn matches_sources() {
+            let rules = LabelRules::new(&[rule("Nightly", &[], &["schedule"], &[])]).unwrap();
+
+            assert_eq!(
+                rules.label_for(&strings(&["main"]), &strings(&["schedule"]), &[]),
+                Some("Nightly".to_string())
+            );
+            assert_eq!(
+                rules.label_for(&strings(&["main"]), &strings(&["schedule", "push"]), &[]),
+                None,
+                "Every source of the type should match"
+            );
+        }
+
+        #[test]
+        fn matches_ref_globs_and_regexes() {
+            let rules = LabelRules::new(&[
+                rule("Release", &["release/*"], &[], &[]),
+                rule("Release tag", &[r"regex:^v\d+\.\d+\.\d+$"], &[], &[]),
+            ])
+            .unwrap();
+
+            assert_eq!(
+                rules.label_for(&strings(&["release/1.2", "release/1.3"]), &[], &[]),
+                Some("Release".to_string())
+            );
+            assert_eq!(
+                rules.label_for(&strings(&["v1.4.0"]), &[], &[]),
+                Some("Release tag".to_string())
+            );
+            assert_eq!(rules.label_for(&strings(&["v1.4"]), &[], &[]), None);
+        }
+
+        #[test]
+        fn anchors_regexes_to_the_whole_value() {
+            let rules =
+                LabelRules::new(&[rule("Release", &["regex:release|hotfix"], &[], &[])]).unwrap();
+
+            assert_eq!(
+                rules.label_for(&strings(&["hotfix"]), &[], &[]),
+                Some("Release".to_string())
+            );
+            assert_eq!(
+                rules.label_for(&strings(&["pre-release-fix"]), &[], &[]),
+                None,
+                "A regex should match the whole ref, not a substring"
+            );
+        }
+
+        #[test]
+        fn matches_any_job_name() {
+            let rules = LabelRules::new(&[rule("Deploy", &[], &[], &["deploy-*"])]).unwrap();
+
+            assert_eq!(
+                rules.label_for(&[], &[], &strings(&["build", "deploy-prod"])),
+                Some("Deploy".to_string())
+            );
+        }
+
+        #[test]
+        fn requires_every_criterion_and_uses_first_match() {
+            let rules = LabelRules::new(&[
+                rule("Main MR", &["main"], &["merge_request_event"], &[]),
+                rule("MR pipeline", &[], &["merge_request_event"], &[]),
+            ])
+            .unwrap();
+
+            assert_eq!(
+                rules.label_for(&strings(&["feature"]), &strings(&["merge_request_event"]), &[]),
+                Some("MR pipeline".to_string())
+            );
+            assert_eq!(
+                rules.label_for(&strings(&["main"]), &strings(&["merge_request_event"]), &[]),
+                Some("Main MR".to_string())
+            );
+        }
+    }
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn rejects_invalid_regex() {
+            let result = LabelRules::new(&[rule("Broken", &["regex:("], &[], &[])]);
+
+            assert!(matches!(result, Err(CILensError::Config(_))));
+        }
+
+        #[test]
+        fn rejects_rule_without_criteria() {
+            let result = LabelRules::new(&[rule("Everything", &[], &[], &[])]);
+
+            assert!(matches!(result, Err(CILensError::Config(_))));
+        }
+
+        #[test]
+        fn escapes_regex_characters_in_globs() {
+            assert_eq!(glob_to_regex("v1.*"), r"^v1\..*$");
+        }
+    }
+}
@@ -36,7 +36,7 @@ pub fn job_id_to_url(base_url: &str, project_path: &str, gid: &str) -> String {
     format!("{base_url}/{project_path}/-/jobs/{id}")
 }
 
-fn extract_numeric_id(gid: &str) -> &str {
+pub(super) fn extract_numeric_id(gid: &str) -> &str {
     // GitLab GIDs format: gid://gitlab/Ci::Pipeline/123 or gid://gitlab/Ci::Job/456
     // Extract the numeric ID after the last slash
     gid.rsplit('/').next().unwrap_or(gid)
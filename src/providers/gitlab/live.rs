@@ -0,0 +1,130 @@
+use chrono::{DateTime, Utc};
+
+use super::links::pipeline_id_to_url;
+use super::types::GitLabActivePipeline;
+use crate::insights::{LivePipeline, LivePipelines};
+use crate::providers::stats::{calculate_percentiles, cmp_f64};
+
+/// Number of live pipelines listed in the report.
+const MAX_LISTED: usize = 10;
+
+/// Summarizes the pipelines that are running or pending right now.
+///
+/// A pipeline is queued from its creation until its first job starts. Pending pipelines
+/// are still queued, so their queue time runs until `now`.
+///
+/// # Arguments
+///
+/// * `pipelines` - Running and pending pipelines
+/// * `now` - When the pipelines were fetched
+/// * `base_url` - GitLab instance base URL for pipeline links
+/// * `project_path` - Project path for pipeline links
+///
+/// # Returns
+///
+/// Running and pending counts, queue time P50/P95 and the pipelines that waited longest.
+pub(super) fn summarize_live_pipelines(
+    pipelines: &[GitLabActivePipeline],
+    now: DateTime<Utc>,
+    base_url: &str,
+    project_path: &str,
+) -> LivePipelines {
+    let mut live: Vec<LivePipeline> = pipelines
+        .iter()
+        .map(|pipeline| LivePipeline {
+            url: pipeline_id_to_url(base_url, project_path, &pipeline.id),
+            ref_: pipeline.ref_.clone(),
+            source: pipeline.source.clone(),
+            status: pipeline.status.clone(),
+            created_at: pipeline.created_at,
+            queued: seconds_between(pipeline.created_at, pipeline.started_at.unwrap_or(now)),
+            running_for: pipeline
+                .started_at
+                .map_or(0.0, |started_at| seconds_between(started_at, now)),
+        })
+        .collect();
+
+    let queued: Vec<f64> = live.iter().map(|pipeline| pipeline.queued).collect();
+    let (queued_p50, queued_p95, _) = calculate_percentiles(&queued);
+
+    live.sort_by(|a, b| cmp_f64(b.queued, a.queued));
+    let running = live.iter().filter(|p| p.status == "running").count();
+
+    LivePipelines {
+        observed_at: now,
+        running,
+        pending: live.len() - running,
+        queued_p50,
+        queued_p95,
+        pipelines: live.into_iter().take(MAX_LISTED).collect(),
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn seconds_between(start: DateTime<Utc>, end: DateTime<Utc>) -> f64 {
+    (end - start).num_seconds().max(0) as f64
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn create_pipeline(
+        id: u32,
+        status: &str,
+        created_at: DateTime<Utc>,
+        started_at: Option<DateTime<Utc>>,
+    ) -> GitLabActivePipeline {
+        GitLabActivePipeline {
+            id: format!("gid://gitlab/Ci::Pipeline/{id}"),
+            ref_: "main".to_string(),
+            source: "push".to_string(),
+            status: status.to_string(),
+            created_at,
+            started_at,
+        }
+    }
+
+    mod summarize_live_pipelines {
+        use super::*;
+
+        #[test]
+        fn measures_queue_time_until_first_job_or_now() {
+            let now = Utc::now();
+            let pipelines = vec![
+                create_pipeline(
+                    1,
+                    "running",
+                    now - Duration::minutes(10),
+                    Some(now - Duration::minutes(8)),
+                ),
+                create_pipeline(2, "pending", now - Duration::minutes(5), None),
+            ];
+
+            let live = summarize_live_pipelines(&pipelines, now, "https://gitlab.com", "org/repo");
+
+            assert_eq!(live.running, 1);
+            assert_eq!(live.pending, 1);
+            assert_eq!(
+                live.pipelines[0].url,
+                "https://gitlab.com/org/repo/-/pipelines/2"
+            );
+            assert_eq!(live.pipelines[0].queued, 300.0);
+            assert_eq!(live.pipelines[0].running_for, 0.0);
+            assert_eq!(live.pipelines[1].queued, 120.0);
+            assert_eq!(live.pipelines[1].running_for, 480.0);
+        }
+
+        #[test]
+        fn handles_no_active_pipelines() {
+            let live = summarize_live_pipelines(&[], Utc::now(), "https://gitlab.com", "org/repo");
+
+            assert_eq!(live.running, 0);
+            assert_eq!(live.pending, 0);
+            assert_eq!(live.queued_p95, 0.0);
+            assert!(live.pipelines.is_empty());
+        }
+    }
+}
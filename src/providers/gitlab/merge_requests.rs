@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use super::types::GitLabPipeline;
+use crate::insights::MergeRequestMetrics;
+use crate::providers::stats::calculate_percentiles;
+
+/// Pipelines observed for a single merge request.
+#[derive(Default)]
+struct MergeRequestPipelines<'a> {
+    merged_at: Option<DateTime<Utc>>,
+    pipelines: Vec<&'a GitLabPipeline>,
+}
+
+/// Calculates merge request lead-time metrics from merge request pipelines.
+///
+/// Pipelines are grouped by their merge request. For every merged merge request, the
+/// pipelines created before the merge give the number of pipelines until merge, the CI
+/// wait (sum of their durations) and the time from the first pipeline to the merge.
+/// The retry rate covers all merge requests, merged or not, and counts any retried job;
+/// GitLab does not distinguish manual retries from automatic `retry:` retries.
+///
+/// Only the given pipelines are considered, so merge requests whose first pipelines fall
+/// outside the fetched range report lower counts and wait times.
+///
+/// # Arguments
+///
+/// * `pipelines` - Merge request pipelines (fetched by source, or the analyzed sample
+///   when offline); pipelines without a merge request are ignored
+///
+/// # Returns
+///
+/// Merge request metrics, or `None` if no pipeline belongs to a merge request.
+pub(super) fn calculate_merge_request_metrics(
+    pipelines: &[GitLabPipeline],
+) -> Option<MergeRequestMetrics> {
+    let mut merge_requests: HashMap<&str, MergeRequestPipelines> = HashMap::new();
+    for pipeline in pipelines {
+        let Some(mr) = &pipeline.merge_request else {
+            continue;
+        };
+        let entry = merge_requests.entry(mr.iid.as_str()).or_default();
+        entry.merged_at = entry.merged_at.or(mr.merged_at);
+        entry.pipelines.push(pipeline);
+    }
+
+    if merge_requests.is_empty() {
+        return None;
+    }
+
+    let mut pipelines_until_merge = Vec::new();
+    let mut ci_waits = Vec::new();
+    let mut first_pipeline_to_merge = Vec::new();
+    let mut retried: u32 = 0;
+
+    for mr in merge_requests.values() {
+        let needed_retry = mr
+            .pipelines
+            .iter()
+            .any(|pipeline| pipeline.jobs.iter().any(|job| job.retried));
+        if needed_retry {
+            retried += 1;
+        }
+
+        let Some(merged_at) = mr.merged_at else {
+            continue;
+        };
+
+        let before_merge: Vec<_> = mr
+            .pipelines
+            .iter()
+            .filter(|pipeline| {
+                pipeline
+                    .created_at
+                    .is_some_and(|created| created <= merged_at)
+            })
+            .collect();
+        let Some(first_created) = before_merge.iter().filter_map(|p| p.created_at).min() else {
+            continue;
+        };
+
+        #[allow(clippy::cast_precision_loss)]
+        {
+            pipelines_until_merge.push(before_merge.len() as f64);
+            ci_waits.push(before_merge.iter().map(|p| p.duration as f64).sum());
+            first_pipeline_to_merge.push((merged_at - first_created).num_seconds() as f64);
+        }
+    }
+
+    let (pipelines_until_merge_p50, pipelines_until_merge_p95, _) =
+        calculate_percentiles(&pipelines_until_merge);
+    let (ci_wait_p50, ci_wait_p95, _) = calculate_percentiles(&ci_waits);
+    let (first_pipeline_to_merge_p50, first_pipeline_to_merge_p95, _) =
+        calculate_percentiles(&first_pipeline_to_merge);
+
+    #[allow(clippy::cast_precision_loss)]
+    let retry_rate = (f64::from(retried) / merge_requests.len() as f64) * 100.0;
+
+    Some(MergeRequestMetrics {
+        total_merge_requests: merge_requests.len(),
+        merged_merge_requests: pipelines_until_merge.len(),
+        pipelines_until_merge_p50,
+        pipelines_until_merge_p95,
+        ci_wait_p50,
+        ci_wait_p95,
+        first_pipeline_to_merge_p50,
+        first_pipeline_to_merge_p95,
+        retry_rate,
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use crate::providers::gitlab::types::{GitLabJob, GitLabMergeRequest};
+    use chrono::{Duration, TimeZone};
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap() + Duration::minutes(minutes)
+    }
+
+    fn create_job(retried: bool) -> GitLabJob {
+        GitLabJob {
+            id: "gid://gitlab/Ci::Job/1".to_string(),
+            name: "test".to_string(),
+            stage: "test".to_string(),
+            duration: 60.0,
+            status: "SUCCESS".to_string(),
+            retried,
+            needs: None,
+            tags: vec![],
+            runner: None,
+            test_cases: vec![],
+        }
+    }
+
+    fn create_pipeline(
+        iid: Option<&str>,
+        merged_at: Option<DateTime<Utc>>,
+        created_at: DateTime<Utc>,
+        duration: usize,
+        jobs: Vec<GitLabJob>,
+    ) -> GitLabPipeline {
+        GitLabPipeline {
+            id: "gid://gitlab/Ci::Pipeline/1".to_string(),
+            ref_: "refs/merge-requests/1/head".to_string(),
+            sha: "abc123".to_string(),
+            weight: 1.0,
+            source: "merge_request_event".to_string(),
+            status: "success".to_string(),
+            duration,
+            created_at: Some(created_at),
+            finished_at: None,
+            merge_request: iid.map(|iid| GitLabMergeRequest {
+                iid: iid.to_string(),
+                merged_at,
+            }),
+            stages: vec!["test".to_string()],
+            jobs,
+        }
+    }
+
+    mod calculate_merge_request_metrics {
+        use super::*;
+
+        #[test]
+        fn returns_none_without_merge_request_pipelines() {
+            let pipelines = vec![create_pipeline(None, None, at(0), 60, vec![])];
+
+            assert!(calculate_merge_request_metrics(&pipelines).is_none());
+        }
+
+        #[test]
+        fn measures_pipelines_and_time_until_merge() {
+            // MR 1: two pipelines, merged an hour after the first one
+            let merged_at = Some(at(60));
+            let pipelines = vec![
+                create_pipeline(Some("1"), merged_at, at(0), 300, vec![create_job(false)]),
+                create_pipeline(Some("1"), merged_at, at(30), 600, vec![create_job(false)]),
+                // Post-merge pipeline is not part of the lead time
+                create_pipeline(Some("1"), merged_at, at(90), 900, vec![create_job(false)]),
+            ];
+
+            let metrics = calculate_merge_request_metrics(&pipelines).unwrap();
+
+            assert_eq!(metrics.total_merge_requests, 1);
+            assert_eq!(metrics.merged_merge_requests, 1);
+            assert_eq!(metrics.pipelines_until_merge_p50, 2.0);
+            assert_eq!(metrics.ci_wait_p50, 900.0);
+            assert_eq!(metrics.first_pipeline_to_merge_p50, 3600.0);
+        }
+
+        #[test]
+        fn reports_retry_rate_across_all_merge_requests() {
+            let pipelines = vec![
+                create_pipeline(Some("1"), Some(at(10)), at(0), 60, vec![create_job(true)]),
+                create_pipeline(Some("2"), None, at(0), 60, vec![create_job(false)]),
+            ];
+
+            let metrics = calculate_merge_request_metrics(&pipelines).unwrap();
+
+            assert_eq!(metrics.total_merge_requests, 2);
+            assert_eq!(metrics.merged_merge_requests, 1);
+            assert_eq!(metrics.retry_rate, 50.0);
+        }
+    }
+}
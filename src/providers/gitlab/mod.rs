@@ -1,12 +1,35 @@
+mod branch_health;
 mod cache;
+mod canceled;
 mod client;
+mod deployments;
+mod failure_signatures;
+mod job_costs;
 mod job_metrics;
 mod job_reliability;
+mod labels;
 mod links;
+mod live;
+mod merge_requests;
 mod pipeline_metrics;
 mod pipeline_types;
 mod provider;
+mod raw;
+mod sampling;
+mod stage_metrics;
+mod storage;
+mod test_cache;
+mod test_reports;
+mod trace_cache;
 mod types;
 
-pub use cache::JobCache;
-pub use provider::GitLabProvider;
+pub use cache::{CacheRetention, CacheStats, CacheVerification, JobCache, PruneResult};
+pub use client::RateLimits;
+pub use failure_signatures::SignatureRules;
+pub use job_costs::CostRates;
+pub use labels::LabelRules;
+pub use pipeline_types::Clustering;
+pub use provider::{GitLabProvider, GitLabProviderOptions, PipelineSource};
+pub use sampling::Sampling;
+pub use test_cache::TestReportCache;
+pub use trace_cache::TraceCache;
@@ -1,7 +1,11 @@
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
+use super::job_costs::CostRates;
+use super::labels::LabelRules;
+use super::pipeline_metrics::sum_weights;
 use super::types::GitLabPipeline;
-use crate::insights::PipelineType;
+use crate::insights::{ClusteringSummary, ExcludedPipelines, PipelineType};
+use crate::providers::grouping::PipelineGrouping;
 
 fn extract_job_signature(pipeline: &GitLabPipeline) -> Vec<String> {
     pipeline
@@ -13,11 +17,29 @@ fn extract_job_signature(pipeline: &GitLabPipeline) -> Vec<String> {
         .collect()
 }
 
+/// How pipelines are grouped into pipeline types.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Clustering {
+    /// Pipelines with identical sets of job names share a type
+    #[default]
+    Exact,
+    /// Job sets whose Jaccard similarity to a type's most common job set is at least
+    /// the threshold (0.0-1.0) share a type
+    Similarity(f64),
+}
+
 /// Groups pipelines by their job signatures and filters by minimum percentage threshold.
 ///
-/// Pipelines with identical sets of job names are grouped into the same type. Each type
-/// receives a human-readable label (e.g., "Production", "Development")
-/// based on keywords found in job names, and comprehensive metrics are calculated.
+/// With [`Clustering::Exact`], pipelines with identical sets of job names are grouped
+/// into the same type. With [`Clustering::Similarity`], near-identical job sets (e.g., a
+/// pipeline that adds one `rules:changes` job) are merged into the type of the most
+/// common similar job set. Each type receives a human-readable label from the first
+/// matching label rule, falling back to keywords found in job names (e.g., "Production",
+/// "Development"), records its core and optional jobs, and comprehensive metrics are
+/// calculated.
+///
+/// The pipelines of types below the threshold are aggregated into one excluded group
+/// so the report's coverage stays visible.
 ///
 /// # Arguments
 ///
@@ -25,21 +47,28 @@ fn extract_job_signature(pipeline: &GitLabPipeline) -> Vec<String> {
 /// * `min_type_percentage` - Minimum percentage (0-100) required for a pipeline type to be included
 /// * `base_url` - GitLab instance base URL (e.g., <https://gitlab.com>) for generating pipeline/job URLs
 /// * `project_path` - Project path (e.g., "group/project") for generating URLs
+/// * `cost_rates` - Optional cost rates by runner tag and runner type
+/// * `clustering` - Exact or similarity-based clustering
+/// * `label_rules` - Configured labelling rules, tried before the keyword heuristics
 ///
 /// # Returns
 ///
-/// Vector of pipeline types sorted by frequency (most common first), filtered to only
-/// include types that represent at least `min_type_percentage` of total pipelines.
+/// Pipeline types sorted by frequency (most common first) that represent at least
+/// `min_type_percentage` of total pipelines, together with the excluded pipelines and a
+/// summary of what the threshold dropped.
 ///
 /// # Examples
 ///
 /// ```ignore
 /// // Group pipelines, excluding types that are less than 5% of total
-/// let pipeline_types = group_pipeline_types(
+/// let grouping = group_pipeline_types(
 ///     &pipelines,
 ///     5,  // min 5% threshold
 ///     "https://gitlab.com",
-///     "my-org/my-project"
+///     "my-org/my-project",
+///     None, // no cost analysis
+///     Clustering::Similarity(0.8),
+///     &LabelRules::default(),
 /// );
 /// ```
 pub fn group_pipeline_types(
@@ -47,70 +76,261 @@ pub fn group_pipeline_types(
     min_type_percentage: u8,
     base_url: &str,
     project_path: &str,
-    cost_per_minute: Option<f64>,
-) -> Vec<PipelineType> {
-    let total_pipelines = pipelines.len();
-
-    let mut clusters: HashMap<Vec<String>, Vec<&GitLabPipeline>> = HashMap::new();
-    for pipeline in pipelines {
-        let job_signature = extract_job_signature(pipeline);
-        clusters.entry(job_signature).or_default().push(pipeline);
-    }
-
-    let mut pipeline_types: Vec<PipelineType> = clusters
+    cost_rates: Option<&CostRates>,
+    clustering: Clustering,
+    label_rules: &LabelRules,
+) -> PipelineGrouping {
+    let total_weight: f64 = pipelines.iter().map(|p| p.weight).sum();
+
+    let mut pipeline_types = Vec::new();
+    let mut excluded_clusters = Vec::new();
+    for (index, cluster) in cluster_pipelines(pipelines, clustering)
         .into_iter()
         .enumerate()
-        .map(|(index, (job_names, cluster_pipelines))| {
-            create_pipeline_type(
+    {
+        if is_below_threshold(&cluster, total_weight, min_type_percentage) {
+            excluded_clusters.push(cluster);
+        } else {
+            pipeline_types.push(create_pipeline_type(
                 index,
-                &job_names,
-                &cluster_pipelines,
-                total_pipelines,
+                &cluster,
+                total_weight,
                 base_url,
                 project_path,
-                cost_per_minute,
-            )
-        })
-        .filter(|pt| pt.metrics.percentage >= f64::from(min_type_percentage))
-        .collect();
+                cost_rates,
+                label_rules,
+            ));
+        }
+    }
 
     pipeline_types.sort_by(|a, b| b.metrics.total_pipelines.cmp(&a.metrics.total_pipelines));
-    pipeline_types
+
+    let dropped_pipelines = excluded_clusters.iter().map(Vec::len).sum();
+    let clustering_summary = summarize_clustering(
+        pipelines,
+        total_weight,
+        min_type_percentage,
+        clustering,
+        dropped_pipelines,
+    );
+    let excluded = aggregate_excluded_pipelines(
+        excluded_clusters,
+        total_weight,
+        base_url,
+        project_path,
+        cost_rates,
+    );
+
+    PipelineGrouping {
+        pipeline_types,
+        excluded,
+        clustering: clustering_summary,
+    }
+}
+
+/// Reports how many pipelines `min_type_percentage` drops under each clustering mode.
+///
+/// `dropped_pipelines` is the count of the clustering that was used. The exact-clustering
+/// count is always reported so the effect of similarity clustering can be compared
+/// against it.
+fn summarize_clustering(
+    pipelines: &[GitLabPipeline],
+    total_weight: f64,
+    min_type_percentage: u8,
+    clustering: Clustering,
+    dropped_pipelines: usize,
+) -> ClusteringSummary {
+    match clustering {
+        Clustering::Exact => ClusteringSummary {
+            mode: "exact".to_string(),
+            similarity_threshold: None,
+            exact_dropped_pipelines: dropped_pipelines,
+            similarity_dropped_pipelines: None,
+        },
+        Clustering::Similarity(threshold) => ClusteringSummary {
+            mode: "similarity".to_string(),
+            similarity_threshold: Some(threshold),
+            exact_dropped_pipelines: count_dropped_pipelines(
+                &cluster_pipelines(pipelines, Clustering::Exact),
+                total_weight,
+                min_type_percentage,
+            ),
+            similarity_dropped_pipelines: Some(dropped_pipelines),
+        },
+    }
+}
+
+/// Aggregates the pipelines of all types below `min_type_percentage` into one group.
+///
+/// # Returns
+///
+/// Metrics over all excluded pipelines, or `None` if no pipeline was excluded.
+fn aggregate_excluded_pipelines(
+    excluded_clusters: Vec<Vec<&GitLabPipeline>>,
+    total_weight: f64,
+    base_url: &str,
+    project_path: &str,
+    cost_rates: Option<&CostRates>,
+) -> Option<ExcludedPipelines> {
+    if excluded_clusters.is_empty() {
+        return None;
+    }
+
+    let pipeline_types = excluded_clusters.len();
+    let excluded: Vec<&GitLabPipeline> = excluded_clusters.into_iter().flatten().collect();
+
+    let percentage = type_percentage(&excluded, total_weight);
+    let metrics = super::pipeline_metrics::calculate_type_metrics(
+        "other",
+        &excluded,
+        percentage,
+        base_url,
+        project_path,
+        cost_rates,
+    );
+
+    Some(ExcludedPipelines {
+        pipeline_types,
+        metrics,
+    })
+}
+
+/// Share (0-100) of all pipelines that a cluster stands for, using sampling weights.
+fn type_percentage(cluster: &[&GitLabPipeline], total_weight: f64) -> f64 {
+    if total_weight > 0.0 {
+        (sum_weights(cluster) / total_weight) * 100.0
+    } else {
+        0.0
+    }
+}
+
+fn is_below_threshold(
+    cluster: &[&GitLabPipeline],
+    total_weight: f64,
+    min_type_percentage: u8,
+) -> bool {
+    type_percentage(cluster, total_weight) < f64::from(min_type_percentage)
+}
+
+fn count_dropped_pipelines(
+    clusters: &[Vec<&GitLabPipeline>],
+    total_weight: f64,
+    min_type_percentage: u8,
+) -> usize {
+    clusters
+        .iter()
+        .filter(|cluster| is_below_threshold(cluster, total_weight, min_type_percentage))
+        .map(Vec::len)
+        .sum()
+}
+
+fn cluster_pipelines(
+    pipelines: &[GitLabPipeline],
+    clustering: Clustering,
+) -> Vec<Vec<&GitLabPipeline>> {
+    let mut signatures: HashMap<Vec<String>, Vec<&GitLabPipeline>> = HashMap::new();
+    for pipeline in pipelines {
+        let job_signature = extract_job_signature(pipeline);
+        signatures.entry(job_signature).or_default().push(pipeline);
+    }
+
+    let Clustering::Similarity(threshold) = clustering else {
+        return signatures.into_values().collect();
+    };
+
+    // Seed clusters with the most common job sets so rare variants merge into them
+    let mut signatures: Vec<_> = signatures.into_iter().collect();
+    signatures.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(&b.0)));
+
+    let mut clusters: Vec<(Vec<String>, Vec<&GitLabPipeline>)> = Vec::new();
+    for (signature, signature_pipelines) in signatures {
+        let closest = clusters
+            .iter_mut()
+            .map(|(seed, cluster)| (jaccard_similarity(seed, &signature), cluster))
+            .filter(|(similarity, _)| *similarity >= threshold)
+            .max_by(|a, b| a.0.total_cmp(&b.0));
+
+        match closest {
+            Some((_, cluster)) => cluster.extend(signature_pipelines),
+            None => clusters.push((signature, signature_pipelines)),
+        }
+    }
+
+    clusters.into_iter().map(|(_, cluster)| cluster).collect()
+}
+
+/// Jaccard similarity of two sorted, deduplicated job signatures.
+fn jaccard_similarity(a: &[String], b: &[String]) -> f64 {
+    let a: BTreeSet<&String> = a.iter().collect();
+    let b: BTreeSet<&String> = b.iter().collect();
+
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 1.0;
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let similarity = a.intersection(&b).count() as f64 / union as f64;
+    similarity
+}
+
+/// Splits the job names of a cluster into core jobs (in every pipeline) and optional jobs.
+fn split_core_jobs(pipelines: &[&GitLabPipeline]) -> (Vec<String>, Vec<String>) {
+    let mut occurrences: BTreeMap<String, usize> = BTreeMap::new();
+    for pipeline in pipelines {
+        for job_name in extract_job_signature(pipeline) {
+            *occurrences.entry(job_name).or_default() += 1;
+        }
+    }
+
+    let (core, optional): (Vec<_>, Vec<_>) = occurrences
+        .into_iter()
+        .partition(|(_, count)| *count == pipelines.len());
+
+    (
+        core.into_iter().map(|(name, _)| name).collect(),
+        optional.into_iter().map(|(name, _)| name).collect(),
+    )
 }
 
 fn create_pipeline_type(
     index: usize,
-    job_names: &[String],
     pipelines: &[&GitLabPipeline],
-    total_pipelines: usize,
+    total_weight: f64,
     base_url: &str,
     project_path: &str,
-    cost_per_minute: Option<f64>,
+    cost_rates: Option<&CostRates>,
+    label_rules: &LabelRules,
 ) -> PipelineType {
-    let count = pipelines.len();
-    #[allow(clippy::cast_precision_loss)]
-    let percentage = (count as f64 / total_pipelines.max(1) as f64) * 100.0;
+    let percentage = type_percentage(pipelines, total_weight);
 
     // Generate deterministic ID based on job signature
     let id = format!("type-{index}");
 
-    let label = generate_label(job_names);
+    let (core_jobs, optional_jobs) = split_core_jobs(pipelines);
+    let job_names: Vec<String> = core_jobs.iter().chain(&optional_jobs).cloned().collect();
     let (stages, ref_patterns, sources) = extract_characteristics(pipelines);
+    let label = label_rules
+        .label_for(&ref_patterns, &sources, &job_names)
+        .unwrap_or_else(|| generate_label(&job_names));
     let metrics = super::pipeline_metrics::calculate_type_metrics(
         &id,
         pipelines,
         percentage,
         base_url,
         project_path,
-        cost_per_minute,
+        cost_rates,
     );
 
     PipelineType {
         id,
         label,
+        workflow: None,
         stages,
         ref_patterns,
         sources,
+        core_jobs,
+        optional_jobs,
         metrics,
     }
 }
@@ -174,6 +394,9 @@ mod tests {
             status: "success".to_string(),
             retried: false,
             needs: None,
+            tags: vec![],
+            runner: None,
+            test_cases: vec![],
         }
     }
 
@@ -189,9 +412,14 @@ mod tests {
         GitLabPipeline {
             id: id.to_string(),
             ref_: ref_.to_string(),
+            sha: format!("sha-{id}"),
+            weight: 1.0,
             source: source.to_string(),
             status: "success".to_string(),
             duration: 100,
+            created_at: None,
+            finished_at: None,
+            merge_request: None,
             stages,
             jobs,
         }
@@ -606,7 +834,8 @@ mod tests {
             let pipelines: Vec<GitLabPipeline> = vec![];
 
             // Act: Group pipeline types
-            let result = group_pipeline_types(&pipelines, 0, "https://gitlab.com", "org/repo");
+            let result = group_pipeline_types(&pipelines, 0, "https://gitlab.com", "org/repo")
+                .pipeline_types;
 
             // Assert: Should return empty vec
             assert!(result.is_empty());
@@ -636,7 +865,8 @@ mod tests {
             let pipelines = vec![pipeline1, pipeline2, pipeline3];
 
             // Act: Group pipeline types
-            let result = group_pipeline_types(&pipelines, 0, "https://gitlab.com", "org/repo");
+            let result = group_pipeline_types(&pipelines, 0, "https://gitlab.com", "org/repo")
+                .pipeline_types;
 
             // Assert: Should create only one pipeline type
             assert_eq!(result.len(), 1);
@@ -654,7 +884,8 @@ mod tests {
             let pipelines = vec![pipeline1, pipeline2, pipeline3];
 
             // Act: Group pipeline types
-            let result = group_pipeline_types(&pipelines, 0, "https://gitlab.com", "org/repo");
+            let result = group_pipeline_types(&pipelines, 0, "https://gitlab.com", "org/repo")
+                .pipeline_types;
 
             // Assert: Should create three different pipeline types
             assert_eq!(result.len(), 3);
@@ -683,7 +914,8 @@ mod tests {
             }
 
             // Act: Group with 25% minimum threshold
-            let result = group_pipeline_types(&pipelines, 25, "https://gitlab.com", "org/repo");
+            let result = group_pipeline_types(&pipelines, 25, "https://gitlab.com", "org/repo")
+                .pipeline_types;
 
             // Assert: Only the type with 80% (8/10) should be included
             assert_eq!(result.len(), 1);
@@ -720,7 +952,8 @@ mod tests {
             }
 
             // Act: Group pipeline types
-            let result = group_pipeline_types(&pipelines, 0, "https://gitlab.com", "org/repo");
+            let result = group_pipeline_types(&pipelines, 0, "https://gitlab.com", "org/repo")
+                .pipeline_types;
 
             // Assert: Should have correct percentages
             assert_eq!(result.len(), 3);
@@ -772,7 +1005,8 @@ mod tests {
             }
 
             // Act: Group pipeline types
-            let result = group_pipeline_types(&pipelines, 0, "https://gitlab.com", "org/repo");
+            let result = group_pipeline_types(&pipelines, 0, "https://gitlab.com", "org/repo")
+                .pipeline_types;
 
             // Assert: Should be sorted by total_pipelines descending
             assert_eq!(result.len(), 3);
@@ -790,7 +1024,8 @@ mod tests {
             let pipelines = vec![pipeline1, pipeline2];
 
             // Act: Group with 100% threshold
-            let result = group_pipeline_types(&pipelines, 100, "https://gitlab.com", "org/repo");
+            let result = group_pipeline_types(&pipelines, 100, "https://gitlab.com", "org/repo")
+                .pipeline_types;
 
             // Assert: Should return empty since no type is 100%
             assert!(result.is_empty());
@@ -814,11 +1049,199 @@ mod tests {
             let pipelines = vec![pipeline1, pipeline2];
 
             // Act: Group pipeline types
-            let result = group_pipeline_types(&pipelines, 0, "https://gitlab.com", "org/repo");
+            let result = group_pipeline_types(&pipelines, 0, "https://gitlab.com", "org/repo")
+                .pipeline_types;
 
             // Assert: Should group together since signatures are the same (BTreeSet sorts)
             assert_eq!(result.len(), 1);
             assert_eq!(result[0].metrics.total_pipelines, 2);
         }
     }
+
+    mod similarity_clustering_tests {
+        use super::*;
+
+        fn create_pipeline_with_jobs(id: &str, job_names: &[&str]) -> GitLabPipeline {
+            let jobs = job_names
+                .iter()
+                .map(|name| create_job(name, "test"))
+                .collect();
+            create_pipeline(id, "main", "push", jobs)
+        }
+
+        #[test]
+        fn computes_jaccard_similarity() {
+            let a = vec!["build".to_string(), "lint".to_string(), "test".to_string()];
+            let b = vec!["build".to_string(), "test".to_string()];
+
+            assert!((jaccard_similarity(&a, &b) - 2.0 / 3.0).abs() < f64::EPSILON);
+            assert!((jaccard_similarity(&a, &a) - 1.0).abs() < f64::EPSILON);
+            assert!(jaccard_similarity(&a, &["deploy".to_string()]).abs() < f64::EPSILON);
+        }
+
+        #[test]
+        fn merges_near_identical_job_sets_into_one_type() {
+            // Arrange: Three common pipelines and one that adds a `rules:changes` job
+            let pipelines = vec![
+                create_pipeline_with_jobs("1", &["build", "lint", "test"]),
+                create_pipeline_with_jobs("2", &["build", "lint", "test"]),
+                create_pipeline_with_jobs("3", &["build", "lint", "test"]),
+                create_pipeline_with_jobs("4", &["build", "docs", "lint", "test"]),
+            ];
+
+            // Act: Group with a 0.7 similarity threshold
+            let result = group_pipeline_types(
+                &pipelines,
+                0,
+                "https://gitlab.com",
+                "org/repo",
+                None,
+                Clustering::Similarity(0.7),
+                &LabelRules::default(),
+            )
+            .pipeline_types;
+
+            // Assert: The variant should be merged, with `docs` as an optional job
+            assert_eq!(result.len(), 1, "Variant should merge into the common type");
+            assert_eq!(result[0].metrics.total_pipelines, 4);
+            assert_eq!(result[0].core_jobs, vec!["build", "lint", "test"]);
+            assert_eq!(result[0].optional_jobs, vec!["docs"]);
+        }
+
+        #[test]
+        fn keeps_dissimilar_job_sets_apart() {
+            let pipelines = vec![
+                create_pipeline_with_jobs("1", &["build", "test"]),
+                create_pipeline_with_jobs("2", &["deploy-prod"]),
+            ];
+
+            let result = group_pipeline_types(
+                &pipelines,
+                0,
+                "https://gitlab.com",
+                "org/repo",
+                None,
+                Clustering::Similarity(0.5),
+                &LabelRules::default(),
+            )
+            .pipeline_types;
+
+            assert_eq!(result.len(), 2);
+            assert!(result.iter().all(|pt| pt.optional_jobs.is_empty()));
+        }
+
+        #[test]
+        fn exact_clustering_records_all_jobs_as_core() {
+            let pipelines = vec![create_pipeline_with_jobs("1", &["test", "build"])];
+
+            let result = group_pipeline_types(
+                &pipelines,
+                0,
+                "https://gitlab.com",
+                "org/repo",
+                None,
+                Clustering::Exact,
+                &LabelRules::default(),
+            )
+            .pipeline_types;
+
+            assert_eq!(result[0].core_jobs, vec!["build", "test"]);
+            assert!(result[0].optional_jobs.is_empty());
+        }
+
+        #[test]
+        fn reports_dropped_pipelines_for_both_modes() {
+            // Arrange: 8 common pipelines and 2 single-job variants (10% each)
+            let mut pipelines: Vec<GitLabPipeline> = (0..8)
+                .map(|i| create_pipeline_with_jobs(&i.to_string(), &["build", "lint", "test"]))
+                .collect();
+            pipelines.push(create_pipeline_with_jobs("8", &["build", "docs", "lint", "test"]));
+            pipelines.push(create_pipeline_with_jobs("9", &["build", "lint", "perf", "test"]));
+
+            // Act: Summarize with a 20% inclusion threshold
+            let summary = group_pipeline_types(
+                &pipelines,
+                20,
+                "https://gitlab.com",
+                "org/repo",
+                None,
+                Clustering::Similarity(0.7),
+                &LabelRules::default(),
+            )
+            .clustering;
+
+            // Assert: Exact clustering drops both variants, similarity clustering keeps them
+            assert_eq!(summary.mode, "similarity");
+            assert_eq!(summary.similarity_threshold, Some(0.7));
+            assert_eq!(summary.exact_dropped_pipelines, 2);
+            assert_eq!(summary.similarity_dropped_pipelines, Some(0));
+        }
+    }
+
+    mod excluded_pipelines_tests {
+        use super::*;
+
+        #[test]
+        fn returns_none_when_nothing_is_excluded() {
+            let pipelines = vec![create_pipeline(
+                "1",
+                "main",
+                "push",
+                vec![create_job("build", "build")],
+            )];
+
+            let excluded = group_pipeline_types(
+                &pipelines,
+                1,
+                "https://gitlab.com",
+                "org/repo",
+                None,
+                Clustering::Exact,
+                &LabelRules::default(),
+            )
+            .excluded;
+
+            assert!(excluded.is_none());
+        }
+
+        #[test]
+        fn aggregates_pipelines_of_types_below_threshold() {
+            // Arrange: 8 build pipelines, one test pipeline and one failed deploy pipeline
+            let mut pipelines: Vec<GitLabPipeline> = (0..8)
+                .map(|i| {
+                    create_pipeline(
+                        &i.to_string(),
+                        "main",
+                        "push",
+                        vec![create_job("build", "build")],
+                    )
+                })
+                .collect();
+            pipelines.push(create_pipeline("8", "main", "push", vec![create_job("test", "test")]));
+            let mut failed =
+                create_pipeline("9", "main", "push", vec![create_job("deploy", "deploy")]);
+            failed.status = "failed".to_string();
+            pipelines.push(failed);
+
+            // Act: Exclude types below 20%
+            let grouping = group_pipeline_types(
+                &pipelines,
+                20,
+                "https://gitlab.com",
+                "org/repo",
+                None,
+                Clustering::Exact,
+                &LabelRules::default(),
+            );
+            let excluded = grouping.excluded.expect("Small types should be excluded");
+
+            // Assert: Both small types are aggregated into one group
+            assert_eq!(excluded.pipeline_types, 2);
+            assert_eq!(excluded.metrics.total_pipelines, 2);
+            assert!((excluded.metrics.percentage - 20.0).abs() < f64::EPSILON);
+            assert!((excluded.metrics.success_rate - 50.0).abs() < f64::EPSILON);
+            assert_eq!(grouping.pipeline_types.len(), 1);
+            assert_eq!(grouping.clustering.exact_dropped_pipelines, 2);
+        }
+    }
 }
@@ -1,15 +1,62 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
 use chrono::{DateTime, Utc};
-use log::{info, warn};
+use log::{debug, info, warn};
 
 use crate::auth::Token;
-use crate::error::Result;
-use crate::insights::CIInsights;
+use crate::error::{CILensError, Result};
+use crate::insights::{CIInsights, DeploymentMetrics, FailureSignature, LivePipelines};
 use crate::output::PhaseProgress;
-use crate::providers::gitlab::client::pipelines::{fetch_pipeline_jobs, fetch_pipelines};
-use crate::providers::gitlab::client::GitLabClient;
+use crate::providers::gitlab::client::pipelines::{fetch_pipelines, PipelineJobNode};
+use crate::providers::gitlab::client::test_reports::fetch_test_cases;
+use crate::providers::gitlab::client::{GitLabClient, RateLimits};
+use crate::providers::http::HttpSettings;
+
+use super::cache::{CacheRetention, FetchScope, HighWaterMark, JobCache};
+use super::failure_signatures::{cluster_failures, ClassifiedFailure, SignatureRules};
+use super::job_costs::CostRates;
+use super::labels::LabelRules;
+use super::links::{extract_numeric_id, job_id_to_url};
+use super::pipeline_types::Clustering;
+use super::raw::{read_raw_pipelines, write_raw_pipelines};
+use super::sampling::{apply_stratified_weights, select_sample, summarize_sampling, Sampling};
+use super::test_cache::TestReportCache;
+use super::trace_cache::TraceCache;
+use super::types::{
+    GitLabActivePipeline, GitLabDeployment, GitLabJob, GitLabMergeRequest, GitLabPipeline,
+    GitLabRunner, GitLabTestCase,
+};
+
+/// Most recent deployments analyzed per environment when no start date is given.
+const MAX_DEPLOYMENTS: usize = 1000;
+
+/// Where the pipelines to analyze come from.
+#[derive(Debug, Clone, Default)]
+pub enum PipelineSource {
+    /// Fetch pipelines and jobs from the GitLab API
+    #[default]
+    Api,
+    /// Pipelines the job cache holds from a previous run, without API requests
+    Cache,
+    /// A raw data file written with `--export-raw`, without API requests
+    Raw(PathBuf),
+}
 
-use super::cache::JobCache;
-use super::types::{GitLabJob, GitLabPipeline};
+/// Settings of a [`GitLabProvider`] besides the instance and project it analyzes.
+#[derive(Debug, Clone, Default)]
+pub struct GitLabProviderOptions {
+    /// Timeout, proxy and TLS settings for API requests
+    pub http: HttpSettings,
+    /// Retry and concurrency limits for API requests
+    pub rate_limits: RateLimits,
+    /// Whether to enable job, test report and job log caching
+    pub use_cache: bool,
+    /// Which job cache entries to keep when saving
+    pub retention: CacheRetention,
+    /// Whether to fetch JUnit test reports for per-test metrics
+    pub test_reports: bool,
+}
 
 /// GitLab CI/CD insights provider.
 ///
@@ -20,6 +67,9 @@ pub struct GitLabProvider {
     pub client: GitLabClient,
     pub project_path: String,
     cache: JobCache,
+    /// Test report cache, present only when test reports are fetched
+    test_cache: Option<TestReportCache>,
+    trace_cache: TraceCache,
 }
 
 impl GitLabProvider {
@@ -30,95 +80,317 @@ impl GitLabProvider {
     /// * `base_url` - GitLab instance base URL (e.g., <https://gitlab.com>)
     /// * `project_path` - Project path (e.g., "group/project")
     /// * `token` - Optional authentication token
-    /// * `use_cache` - Whether to enable job caching for completed pipelines
+    /// * `options` - HTTP, rate limit, cache and test report settings
     ///
     /// # Errors
     ///
-    /// Returns an error if the GraphQL endpoint URL or cache directory cannot be created.
+    /// Returns an error if the GraphQL endpoint URL, HTTP client or cache directory cannot
+    /// be created.
     pub fn new(
         base_url: &str,
         project_path: String,
         token: Option<Token>,
-        use_cache: bool,
+        options: &GitLabProviderOptions,
     ) -> Result<Self> {
-        let client = GitLabClient::new(base_url, token)?;
-        let cache = JobCache::new(&project_path, use_cache)?;
+        let use_cache = options.use_cache;
+        let client = GitLabClient::new(base_url, token, &options.http, options.rate_limits)?;
+        let cache = JobCache::new(&project_path, base_url, use_cache, options.retention)?;
+        let test_cache = options
+            .test_reports
+            .then(|| TestReportCache::new(base_url, &project_path, use_cache))
+            .transpose()?;
+        let trace_cache = TraceCache::new(base_url, &project_path, use_cache)?;
 
         Ok(Self {
             client,
             project_path,
             cache,
+            test_cache,
+            trace_cache,
         })
     }
 
+    /// Fetches pipelines and their jobs.
+    ///
+    /// If the cache holds the pipelines of a previous fetch with the same filters, only
+    /// pipelines updated since its high-water mark are fetched. They replace their cached
+    /// versions, and the sample is chosen again from the merged set before any job is
+    /// fetched, so only the jobs of updated pipelines in the sample are fetched.
+    ///
+    /// # Returns
+    ///
+    /// The pipelines, the high-water mark to save with them (`None` when the fetch has an
+    /// end date and can't be continued incrementally), and the IDs of pipelines whose
+    /// test reports could not be fetched.
     async fn fetch_pipelines(
         &self,
         limit: usize,
         ref_: Option<&str>,
         updated_after: Option<DateTime<Utc>>,
         updated_before: Option<DateTime<Utc>>,
-    ) -> Result<Vec<GitLabPipeline>> {
-        info!("Fetching up to {limit} pipelines...");
+        sampling: Sampling,
+    ) -> Result<(Vec<GitLabPipeline>, Option<HighWaterMark>, HashSet<String>)> {
+        let scope = FetchScope {
+            ref_: ref_.map(ToString::to_string),
+            sampling,
+            limit,
+            updated_after,
+            updated_before,
+        };
+        let since = updated_before
+            .is_none()
+            .then(|| self.cache.high_water_mark(&scope))
+            .flatten();
 
-        let pipeline_nodes = self
-            .client
-            .fetch_pipelines(
-                &self.project_path,
-                limit,
-                ref_,
-                updated_after,
-                updated_before,
-            )
-            .await?;
+        let mut pipeline_nodes = if let Some(since) = since {
+            info!("Fetching pipelines updated since {since} (incremental)...");
+            self.client
+                .fetch_updated_pipelines(&self.project_path, ref_, since)
+                .await?
+        } else {
+            info!("Fetching up to {limit} pipelines ({sampling} sampling)...");
+            self.client
+                .fetch_pipelines(
+                    &self.project_path,
+                    limit,
+                    ref_,
+                    updated_after,
+                    updated_before,
+                    sampling,
+                )
+                .await?
+        };
 
-        info!(
-            "Fetching jobs for {} pipelines in parallel...",
-            pipeline_nodes.len()
-        );
+        let high_water_mark = pipeline_nodes
+            .iter()
+            .map(|node| node.updated_at)
+            .chain(since)
+            .max()
+            .filter(|_| updated_before.is_none())
+            .map(|updated_at| HighWaterMark {
+                scope: scope.clone(),
+                updated_at,
+            });
 
-        // Fetch jobs for all pipelines concurrently
-        let futures: Vec<_> = pipeline_nodes
-            .into_iter()
-            .map(|node| self.transform_pipeline_with_jobs(node))
-            .collect();
+        let mut cached = Vec::new();
+        if since.is_some() {
+            let fetched_ids: HashSet<String> =
+                pipeline_nodes.iter().map(|n| n.id.clone()).collect();
 
-        let results = futures::future::join_all(futures).await;
+            // The cache may keep pipelines of earlier runs with other filters
+            let mut candidates = self.cache.pipelines();
+            candidates
+                .retain(|pipeline| !fetched_ids.contains(&pipeline.id) && scope.contains(pipeline));
+            self.cache
+                .record_lookups(candidates.len(), fetched_ids.len());
 
-        // Collect successful results, filtering out pipelines without duration
-        let pipelines: Vec<_> = results
-            .into_iter()
-            .filter_map(Result::transpose)
-            .collect::<Result<_>>()?;
+            // Sample from the updated pipelines' metadata first, so only the jobs of
+            // updated pipelines that make it into the sample are fetched
+            candidates.extend(
+                pipeline_nodes
+                    .iter()
+                    .filter_map(|node| Self::transform_pipeline(node.clone(), Vec::new())),
+            );
+            let (updated, sampled_cached): (Vec<_>, Vec<_>) =
+                select_sample(candidates, limit, sampling)
+                    .into_iter()
+                    .partition(|pipeline| fetched_ids.contains(&pipeline.id));
+            let updated_ids: HashSet<String> = updated.into_iter().map(|p| p.id).collect();
+            pipeline_nodes.retain(|node| updated_ids.contains(&node.id));
+            cached = sampled_cached;
+
+            info!(
+                "Merging {} updated pipelines with {} cached pipelines",
+                pipeline_nodes.len(),
+                cached.len()
+            );
+        }
+
+        // Updated pipelines may have retried jobs, so their cached jobs are stale
+        let use_job_cache = since.is_none();
+        let mut pipelines = self
+            .pipelines_with_jobs(pipeline_nodes, &[], use_job_cache)
+            .await?;
+
+        let mut without_test_reports = self.attach_all_test_cases(&mut pipelines).await;
+
+        if since.is_some() {
+            without_test_reports.extend(self.attach_all_test_cases(&mut cached).await);
+
+            pipelines.extend(cached);
+            // Restores the sample's order
+            pipelines = select_sample(pipelines, limit, sampling);
+        }
 
         info!("Processed {} pipelines", pipelines.len());
 
-        Ok(pipelines)
+        Ok((pipelines, high_water_mark, without_test_reports))
     }
 
-    async fn transform_pipeline_with_jobs(
+    /// Loads the pipelines to analyze from their source.
+    ///
+    /// Fetched pipelines are weighted for stratified sampling and saved to the caches.
+    /// Cached pipelines and raw data are filtered by `ref_` and the date range and sampled
+    /// again like a fetch would. Raw data keeps the sampling weights it was exported with.
+    ///
+    /// # Returns
+    ///
+    /// The pipelines, and the number of pipelines per status in the queried range
+    /// (only known when fetching with `Sampling::Stratified`).
+    async fn load_pipelines(
         &self,
-        node: fetch_pipelines::FetchPipelinesProjectPipelinesNodes,
-    ) -> Result<Option<GitLabPipeline>> {
-        // Only include pipelines with duration
-        let Some(duration) = node.duration else {
-            return Ok(None);
+        source: &PipelineSource,
+        limit: usize,
+        ref_: Option<&str>,
+        updated_after: Option<DateTime<Utc>>,
+        updated_before: Option<DateTime<Utc>>,
+        sampling: Sampling,
+    ) -> Result<(Vec<GitLabPipeline>, BTreeMap<String, usize>)> {
+        let scope = FetchScope {
+            ref_: ref_.map(ToString::to_string),
+            sampling,
+            limit,
+            updated_after,
+            updated_before,
         };
 
-        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-        let duration = duration as usize;
+        match source {
+            PipelineSource::Api => {
+                let (mut pipelines, high_water_mark, without_test_reports) = self
+                    .fetch_pipelines(limit, ref_, updated_after, updated_before, sampling)
+                    .await?;
 
-        // Try to get jobs from cache first
-        let jobs = if let Some(cached_jobs) = self.cache.get(&node.id) {
-            cached_jobs
-        } else {
-            // Cache miss - fetch from API
-            let job_nodes = self
-                .client
-                .fetch_pipeline_jobs(&self.project_path, &node.id)
-                .await?;
+                // Stratified samples are weighted back to the real share of each status
+                let population = if sampling == Sampling::Stratified {
+                    let population = self
+                        .client
+                        .fetch_pipeline_counts(
+                            &self.project_path,
+                            ref_,
+                            updated_after,
+                            updated_before,
+                        )
+                        .await?;
+                    apply_stratified_weights(&mut pipelines, &population);
+                    population
+                } else {
+                    BTreeMap::new()
+                };
 
-            Self::transform_job_nodes(job_nodes)
-        };
+                // Derive cache from fetched pipelines and save to disk
+                if let Err(e) = self
+                    .cache
+                    .save_pipelines(&pipelines, high_water_mark.as_ref())
+                {
+                    warn!("Failed to save cache: {e}");
+                }
+                if let Some(test_cache) = &self.test_cache {
+                    if let Err(e) = test_cache.save_pipelines(&pipelines, &without_test_reports) {
+                        warn!("Failed to save test report cache: {e}");
+                    }
+                }
+
+                Ok((pipelines, population))
+            }
+            PipelineSource::Cache => {
+                let mut cached = self.cache.pipelines();
+                cached.retain(|pipeline| scope.contains(pipeline));
+                if cached.is_empty() {
+                    return Err(CILensError::NoPipelineData(self.project_path.clone()));
+                }
+
+                let mut pipelines = select_sample(cached, limit, sampling);
+                info!("Loaded {} pipelines from cache (offline)", pipelines.len());
+
+                if let Some(test_cache) = &self.test_cache {
+                    for pipeline in &mut pipelines {
+                        if let Some(reports) = test_cache.get(&pipeline.id) {
+                            assign_test_cases(&mut pipeline.jobs, reports);
+                        }
+                    }
+                }
+
+                Ok((pipelines, BTreeMap::new()))
+            }
+            PipelineSource::Raw(path) => {
+                let mut pipelines = read_raw_pipelines(path)?;
+                let exported = pipelines.len();
+                pipelines.retain(|pipeline| scope.contains(pipeline));
+
+                let pipelines = select_sample(pipelines, limit, sampling);
+                info!(
+                    "Loaded {} of {exported} pipelines from raw data: {}",
+                    pipelines.len(),
+                    path.display()
+                );
+
+                Ok((pipelines, BTreeMap::new()))
+            }
+        }
+    }
+
+    /// Fetches the jobs of pipelines and converts them, dropping pipelines without a
+    /// duration.
+    ///
+    /// Jobs are taken from the `known` pipeline with the same ID, then from the job cache
+    /// when `use_job_cache` is set, and are otherwise fetched in batches.
+    async fn pipelines_with_jobs(
+        &self,
+        nodes: Vec<fetch_pipelines::FetchPipelinesProjectPipelinesNodes>,
+        known: &[GitLabPipeline],
+        use_job_cache: bool,
+    ) -> Result<Vec<GitLabPipeline>> {
+        let known: HashMap<&str, &GitLabPipeline> = known
+            .iter()
+            .map(|pipeline| (pipeline.id.as_str(), pipeline))
+            .collect();
+        let mut jobs = HashMap::new();
+        let mut uncached_ids = Vec::new();
+        for node in nodes.iter().filter(|node| node.duration.is_some()) {
+            let known_jobs = known
+                .get(node.id.as_str())
+                .map(|pipeline| pipeline.jobs.clone())
+                .or_else(|| use_job_cache.then(|| self.cache.get(&node.id)).flatten());
+            match known_jobs {
+                Some(known_jobs) => {
+                    jobs.insert(node.id.clone(), known_jobs);
+                }
+                None => uncached_ids.push(node.id.as_str()),
+            }
+        }
+
+        info!(
+            "Fetching jobs for {} pipelines in batches...",
+            uncached_ids.len()
+        );
+        let job_nodes = self
+            .client
+            .fetch_jobs_of_pipelines(&self.project_path, &uncached_ids)
+            .await?;
+        jobs.extend(
+            job_nodes
+                .into_iter()
+                .map(|(pipeline_id, nodes)| (pipeline_id, Self::transform_job_nodes(nodes))),
+        );
+
+        Ok(nodes
+            .into_iter()
+            .filter_map(|node| {
+                let pipeline_jobs = jobs.remove(&node.id).unwrap_or_default();
+                Self::transform_pipeline(node, pipeline_jobs)
+            })
+            .collect())
+    }
+
+    /// Converts a pipeline node with its jobs, or returns `None` if it has no duration.
+    fn transform_pipeline(
+        node: fetch_pipelines::FetchPipelinesProjectPipelinesNodes,
+        jobs: Vec<GitLabJob>,
+    ) -> Option<GitLabPipeline> {
+        let duration = node.duration?;
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let duration = duration as usize;
 
         let pipeline_status = format!("{:?}", node.status).to_lowercase();
 
@@ -136,20 +408,431 @@ impl GitLabProvider {
             })
             .unwrap_or_default();
 
-        Ok(Some(GitLabPipeline {
+        Some(GitLabPipeline {
             id: node.id,
             ref_: node.ref_.unwrap_or_default(),
+            sha: node.sha.unwrap_or_default(),
+            weight: 1.0,
             source: node.source.unwrap_or_default(),
             status: pipeline_status,
             duration,
+            created_at: Some(node.created_at),
+            finished_at: node.finished_at,
+            merge_request: node.merge_request.map(|mr| GitLabMergeRequest {
+                iid: mr.iid,
+                merged_at: mr.merged_at,
+            }),
             stages,
             jobs,
-        }))
+        })
+    }
+
+    /// Attaches JUnit test case results to the jobs of each pipeline when test reports
+    /// are fetched.
+    ///
+    /// Test reports are optional: if those of a pipeline cannot be fetched, a warning is
+    /// logged and the pipeline is analyzed without test cases.
+    ///
+    /// # Returns
+    ///
+    /// IDs of the pipelines left without test cases, which must not be cached as fetched.
+    async fn attach_all_test_cases(&self, pipelines: &mut [GitLabPipeline]) -> HashSet<String> {
+        let Some(test_cache) = &self.test_cache else {
+            return HashSet::new();
+        };
+
+        let futures: Vec<_> = pipelines
+            .iter_mut()
+            .map(|pipeline| async move {
+                let result = self
+                    .attach_test_cases(&pipeline.id, &mut pipeline.jobs, test_cache)
+                    .await;
+                result.err().map(|e| {
+                    warn!(
+                        "Failed to fetch test reports of pipeline {}: {e}",
+                        pipeline.id
+                    );
+                    pipeline.id.clone()
+                })
+            })
+            .collect();
+
+        futures::future::join_all(futures)
+            .await
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// Attaches JUnit test case results to the jobs of a pipeline.
+    ///
+    /// Reports are fetched for every job that uploaded one, and for retried runs of the
+    /// same jobs so failures that passed on retry can be detected.
+    async fn attach_test_cases(
+        &self,
+        pipeline_id: &str,
+        jobs: &mut [GitLabJob],
+        test_cache: &TestReportCache,
+    ) -> Result<()> {
+        let reports = if let Some(cached) = test_cache.get(pipeline_id) {
+            cached
+        } else {
+            let build_ids: HashSet<String> = self
+                .client
+                .fetch_test_report_build_ids(&self.project_path, pipeline_id)
+                .await?
+                .into_iter()
+                .collect();
+            let reported_jobs: HashSet<&str> = jobs
+                .iter()
+                .filter(|job| build_ids.contains(extract_numeric_id(&job.id)))
+                .map(|job| job.name.as_str())
+                .collect();
+
+            let futures: Vec<_> = jobs
+                .iter()
+                .filter(|job| reported_jobs.contains(job.name.as_str()))
+                .map(|job| async move {
+                    let test_cases = self
+                        .client
+                        .fetch_test_cases(
+                            &self.project_path,
+                            pipeline_id,
+                            extract_numeric_id(&job.id),
+                        )
+                        .await?;
+                    Ok((job.id.clone(), Self::transform_test_case_nodes(test_cases)))
+                })
+                .collect();
+
+            futures::future::join_all(futures)
+                .await
+                .into_iter()
+                .collect::<Result<HashMap<_, _>>>()?
+        };
+
+        assign_test_cases(jobs, reports);
+
+        Ok(())
+    }
+
+    fn transform_test_case_nodes(
+        nodes: Vec<fetch_test_cases::FetchTestCasesProjectPipelineTestSuiteTestCasesNodes>,
+    ) -> Vec<GitLabTestCase> {
+        nodes
+            .into_iter()
+            .map(|node| {
+                let name = node.name.unwrap_or_default();
+                GitLabTestCase {
+                    name: match node.classname {
+                        Some(classname) if !classname.is_empty() => format!("{classname} {name}"),
+                        _ => name,
+                    },
+                    status: node
+                        .status
+                        .map(|s| format!("{s:?}").to_lowercase())
+                        .unwrap_or_default(),
+                    duration: node.execution_time.unwrap_or(0.0),
+                }
+            })
+            .collect()
+    }
+
+    /// Fetches deployments to each environment and calculates their delivery metrics.
+    ///
+    /// All deployments created between `since` and `until` are analyzed, independent of
+    /// the pipeline limit. Without `since`, the latest [`MAX_DEPLOYMENTS`] are, and the
+    /// period starts with the oldest of them.
+    ///
+    /// Deployment data is optional: environments that are missing, have no finished
+    /// deployments or cannot be fetched are skipped with a warning.
+    async fn fetch_deployment_metrics(
+        &self,
+        environments: &[String],
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Vec<DeploymentMetrics> {
+        let limit = if since.is_some() {
+            usize::MAX
+        } else {
+            MAX_DEPLOYMENTS
+        };
+        let futures: Vec<_> = environments
+            .iter()
+            .map(|environment| {
+                self.client
+                    .fetch_deployments(&self.project_path, environment, limit, since, until)
+            })
+            .collect();
+
+        let results = futures::future::join_all(futures).await;
+        let period_end = until.unwrap_or_else(Utc::now);
+
+        environments
+            .iter()
+            .zip(results)
+            .filter_map(|(environment, result)| {
+                let nodes = result
+                    .inspect_err(|e| warn!("Failed to fetch deployments to {environment}: {e}"))
+                    .ok()?;
+                let deployments: Vec<_> = nodes
+                    .into_iter()
+                    .filter_map(|node| {
+                        Some(GitLabDeployment {
+                            sha: node.sha.unwrap_or_default(),
+                            status: node
+                                .status
+                                .map(|s| format!("{s:?}").to_lowercase())
+                                .unwrap_or_default(),
+                            created_at: node.created_at?,
+                            finished_at: node.finished_at,
+                            committed_at: node.commit.and_then(|c| c.committed_date),
+                        })
+                    })
+                    .collect();
+
+                let metrics = super::deployments::calculate_deployment_metrics(
+                    environment,
+                    &deployments,
+                    since,
+                    period_end,
+                );
+                match &metrics {
+                    Some(metrics) => info!(
+                        "Analyzed {} deployments to {environment} from {} to {}",
+                        metrics.total_deployments,
+                        metrics.period_start.format("%Y-%m-%d"),
+                        metrics.period_end.format("%Y-%m-%d")
+                    ),
+                    None => warn!("No finished deployments found for environment: {environment}"),
+                }
+                metrics
+            })
+            .collect()
+    }
+
+    /// Fetches the most recent merge request pipelines with their jobs.
+    ///
+    /// The sample only holds the merge request pipelines its statuses and ref happen to
+    /// cover (none with `--ref main`), so they are fetched separately, reusing the jobs of
+    /// sampled pipelines. If they cannot be fetched, a warning is logged and `None`
+    /// returned.
+    async fn fetch_merge_request_pipelines(
+        &self,
+        limit: usize,
+        updated_after: Option<DateTime<Utc>>,
+        updated_before: Option<DateTime<Utc>>,
+        sampled: &[GitLabPipeline],
+    ) -> Option<Vec<GitLabPipeline>> {
+        let nodes = self
+            .client
+            .fetch_merge_request_pipelines(&self.project_path, limit, updated_after, updated_before)
+            .await
+            .inspect_err(|e| warn!("Failed to fetch merge request pipelines: {e}"))
+            .ok()?;
+        info!("Fetched {} merge request pipelines", nodes.len());
+
+        self.pipelines_with_jobs(nodes, sampled, true)
+            .await
+            .inspect_err(|e| warn!("Failed to fetch merge request pipeline jobs: {e}"))
+            .ok()
+    }
+
+    /// Fetches canceled pipelines with their jobs, and the number of finished pipelines
+    /// per status in the range, for the canceled pipelines report.
+    ///
+    /// Most sampling strategies fetch few or no canceled pipelines, so they are fetched
+    /// separately, reusing the jobs of sampled pipelines and the status counts of
+    /// stratified sampling. If they cannot be fetched, a warning is logged and `None`
+    /// returned.
+    async fn fetch_canceled_pipelines(
+        &self,
+        limit: usize,
+        ref_: Option<&str>,
+        updated_after: Option<DateTime<Utc>>,
+        updated_before: Option<DateTime<Utc>>,
+        sampled: &[GitLabPipeline],
+        population: &BTreeMap<String, usize>,
+    ) -> Option<(Vec<GitLabPipeline>, BTreeMap<String, usize>)> {
+        let counts = if population.is_empty() {
+            self.client
+                .fetch_pipeline_counts(&self.project_path, ref_, updated_after, updated_before)
+                .await
+                .inspect_err(|e| warn!("Failed to count pipelines by status: {e}"))
+                .ok()?
+        } else {
+            population.clone()
+        };
+        if counts.get("canceled").is_none_or(|&count| count == 0) {
+            return Some((Vec::new(), counts));
+        }
+
+        let nodes = self
+            .client
+            .fetch_canceled_pipelines(
+                &self.project_path,
+                limit,
+                ref_,
+                updated_after,
+                updated_before,
+            )
+            .await
+            .inspect_err(|e| warn!("Failed to fetch canceled pipelines: {e}"))
+            .ok()?;
+        let pipelines = self
+            .pipelines_with_jobs(nodes, sampled, true)
+            .await
+            .inspect_err(|e| warn!("Failed to fetch canceled pipeline jobs: {e}"))
+            .ok()?;
+
+        Some((pipelines, counts))
+    }
+
+    /// Fetches the most recent pipelines of a ref in chronological order, for branch health.
+    ///
+    /// Balanced and stratified samples interleave the latest pipelines of each status,
+    /// which is no contiguous timeline to detect incidents in. Only failed pipelines need
+    /// their jobs (for the culprit job), reusing those of sampled pipelines. If the
+    /// pipelines cannot be fetched, a warning is logged and `None` returned.
+    async fn fetch_branch_timeline(
+        &self,
+        limit: usize,
+        ref_: &str,
+        updated_after: Option<DateTime<Utc>>,
+        updated_before: Option<DateTime<Utc>>,
+        sampled: &[GitLabPipeline],
+    ) -> Option<Vec<GitLabPipeline>> {
+        let nodes = self
+            .client
+            .fetch_pipelines(
+                &self.project_path,
+                limit,
+                Some(ref_),
+                updated_after,
+                updated_before,
+                Sampling::Chronological,
+            )
+            .await
+            .inspect_err(|e| warn!("Failed to fetch the pipeline timeline of {ref_}: {e}"))
+            .ok()?;
+
+        let (failed, others): (Vec<_>, Vec<_>) = nodes
+            .into_iter()
+            .partition(|node| node.status == fetch_pipelines::PipelineStatusEnum::FAILED);
+        let mut pipelines = self
+            .pipelines_with_jobs(failed, sampled, true)
+            .await
+            .inspect_err(|e| warn!("Failed to fetch failed pipeline jobs of {ref_}: {e}"))
+            .ok()?;
+        pipelines.extend(
+            others
+                .into_iter()
+                .filter_map(|node| Self::transform_pipeline(node, Vec::new())),
+        );
+
+        Some(pipelines)
+    }
+
+    /// Fetches the pipelines that are running or pending right now.
+    ///
+    /// The live view is optional: if it cannot be fetched, it is skipped with a warning.
+    async fn fetch_live_pipelines(
+        &self,
+        limit: usize,
+        ref_: Option<&str>,
+        base_url: &str,
+    ) -> Option<LivePipelines> {
+        let nodes = self
+            .client
+            .fetch_active_pipelines(&self.project_path, limit, ref_)
+            .await
+            .inspect_err(|e| warn!("Failed to fetch running and pending pipelines: {e}"))
+            .ok()?;
+
+        let pipelines: Vec<GitLabActivePipeline> = nodes
+            .into_iter()
+            .map(|node| GitLabActivePipeline {
+                id: node.id,
+                ref_: node.ref_.unwrap_or_default(),
+                source: node.source.unwrap_or_default(),
+                status: format!("{:?}", node.status).to_lowercase(),
+                created_at: node.created_at,
+                started_at: node.started_at,
+            })
+            .collect();
+
+        Some(super::live::summarize_live_pipelines(
+            &pipelines,
+            Utc::now(),
+            base_url,
+            &self.project_path,
+        ))
+    }
+
+    /// Downloads the logs of all failed job executions and clusters them by signature.
+    ///
+    /// Logs are read from the job log cache when possible. Logs that cannot be
+    /// downloaded, e.g. because they expired or were erased, are skipped with a warning.
+    async fn mine_failure_signatures(
+        &self,
+        pipelines: &[GitLabPipeline],
+        signature_rules: &SignatureRules,
+        base_url: &str,
+    ) -> Vec<FailureSignature> {
+        let failed_jobs: Vec<&GitLabJob> = pipelines
+            .iter()
+            .flat_map(|pipeline| &pipeline.jobs)
+            .filter(|job| job.status == "FAILED")
+            .collect();
+
+        info!("Mining logs of {} failed jobs...", failed_jobs.len());
+
+        let futures: Vec<_> = failed_jobs
+            .into_iter()
+            .map(|job| async move {
+                let trace = if let Some(trace) = self.trace_cache.get(&job.id) {
+                    trace
+                } else {
+                    let trace = self
+                        .client
+                        .fetch_job_trace(&self.project_path, extract_numeric_id(&job.id))
+                        .await?;
+                    self.trace_cache.put(&job.id, &trace);
+                    trace
+                };
+
+                let (signature, error_line) = signature_rules.classify(&trace);
+                Ok(ClassifiedFailure {
+                    job_name: job.name.clone(),
+                    job_url: job_id_to_url(base_url, &self.project_path, &job.id),
+                    signature,
+                    error_line,
+                })
+            })
+            .collect();
+
+        let results: Vec<Result<ClassifiedFailure>> = futures::future::join_all(futures).await;
+
+        let mut skipped = 0;
+        let failures = results
+            .into_iter()
+            .filter_map(|result| {
+                result
+                    .inspect_err(|e| {
+                        skipped += 1;
+                        debug!("Failed to download job log: {e}");
+                    })
+                    .ok()
+            })
+            .collect();
+        if skipped > 0 {
+            warn!("Skipped {skipped} failed jobs whose logs could not be downloaded");
+        }
+
+        cluster_failures(failures)
     }
 
-    fn transform_job_nodes(
-        job_nodes: Vec<fetch_pipeline_jobs::FetchPipelineJobsProjectPipelineJobsNodes>,
-    ) -> Vec<GitLabJob> {
+    fn transform_job_nodes(job_nodes: Vec<PipelineJobNode>) -> Vec<GitLabJob> {
         job_nodes
             .into_iter()
             .map(|job_node| {
@@ -173,6 +856,15 @@ impl GitLabProvider {
                             .filter_map(|need| need.name)
                             .collect()
                     }),
+                    tags: job_node.tags.unwrap_or_default(),
+                    runner: job_node.runner.map(|runner| GitLabRunner {
+                        runner_type: format!("{:?}", runner.runner_type)
+                            .to_lowercase()
+                            .trim_end_matches("_type")
+                            .to_string(),
+                        description: runner.description,
+                    }),
+                    test_cases: Vec::new(),
                 }
             })
             .collect()
@@ -185,17 +877,34 @@ impl GitLabProvider {
     /// time-to-feedback, and job dependencies.
     ///
     /// Progress is displayed in three phases:
-    /// 1. Fetching pipelines (SUCCESS and FAILED statuses)
+    /// 1. Fetching pipelines (statuses chosen by the sampling strategy)
     /// 2. Fetching jobs for each pipeline
     /// 3. Processing insights (grouping, calculating metrics)
     ///
     /// # Arguments
     ///
-    /// * `limit` - Maximum number of pipelines to fetch (fetches SUCCESS and FAILED concurrently until limit reached)
+    /// * `limit` - Maximum number of pipelines to fetch, split across statuses by `sampling`
     /// * `ref_` - Optional git ref filter (e.g., "main", "develop")
     /// * `updated_after` - Optional start date for pipeline filtering
     /// * `updated_before` - Optional end date for pipeline filtering
     /// * `min_type_percentage` - Minimum percentage (0-100) for pipeline type inclusion
+    /// * `cost_rates` - Optional per-minute rates by runner tag and runner type
+    /// * `clustering` - Exact or similarity-based pipeline type clustering
+    /// * `label_rules` - Configured pipeline type labelling rules
+    /// * `environments` - Deployment environments to report delivery metrics for
+    /// * `signature_rules` - Failure signatures to mine failed job logs with, `None` to skip
+    /// * `sampling` - How pipelines are chosen within `limit`
+    /// * `live` - Whether to also report the pipelines that are running or pending now
+    /// * `merge_requests` - Whether to fetch merge request pipelines beyond the sample for
+    ///   the merge request report
+    /// * `canceled_pipelines` - Whether to fetch canceled pipelines beyond the sample for the
+    ///   canceled pipelines report
+    /// * `branch_timeline` - Whether to fetch the timeline of `ref_` for branch health when
+    ///   the sample isn't chronological
+    /// * `source` - Where the pipelines come from; offline sources skip everything that
+    ///   needs the API (deployments, live pipelines, failure signatures, merge request
+    ///   pipelines outside the sample)
+    /// * `export_raw` - Optional file to export the analyzed pipelines to as raw data
     ///
     /// # Returns
     ///
@@ -205,9 +914,10 @@ impl GitLabProvider {
     /// # Errors
     ///
     /// Returns an error if:
-    /// - GraphQL API requests fail after 30 retries
+    /// - GraphQL API requests fail after [`RateLimits::max_retries`] retries
     /// - Project or pipeline data is not found
     /// - Network or parsing errors occur
+    #[allow(clippy::too_many_arguments)]
     pub async fn collect_insights(
         &self,
         limit: usize,
@@ -215,7 +925,18 @@ impl GitLabProvider {
         updated_after: Option<DateTime<Utc>>,
         updated_before: Option<DateTime<Utc>>,
         min_type_percentage: u8,
-        cost_per_minute: Option<f64>,
+        cost_rates: Option<&CostRates>,
+        clustering: Clustering,
+        label_rules: &LabelRules,
+        environments: &[String],
+        signature_rules: Option<&SignatureRules>,
+        sampling: Sampling,
+        live: bool,
+        merge_requests: bool,
+        canceled_pipelines: bool,
+        branch_timeline: bool,
+        source: &PipelineSource,
+        export_raw: Option<&Path>,
     ) -> Result<CIInsights> {
         info!(
             "Starting insights collection for project: {}",
@@ -225,31 +946,124 @@ impl GitLabProvider {
         // Phase 1: Fetching pipelines
         let progress = PhaseProgress::start_phase_1();
 
-        let pipelines = self
-            .fetch_pipelines(limit, ref_, updated_after, updated_before)
+        let (pipelines, population) = self
+            .load_pipelines(source, limit, ref_, updated_after, updated_before, sampling)
             .await?;
 
-        // Derive cache from fetched pipelines and save to disk
-        if let Err(e) = self.cache.save_pipelines(&pipelines) {
-            warn!("Failed to save cache: {e}");
+        if let Some(path) = export_raw {
+            write_raw_pipelines(&pipelines, path)?;
         }
 
         if pipelines.is_empty() {
             warn!("No pipelines found for project: {}", self.project_path);
         }
 
+        // Deployments, live pipelines and job logs are only available from the API
+        let online = matches!(source, PipelineSource::Api);
+        if !online {
+            info!("Offline analysis: skipping deployments, live pipelines and failure signatures");
+        }
+
+        let deployments = if online {
+            self.fetch_deployment_metrics(environments, updated_after, updated_before)
+                .await
+        } else {
+            Vec::new()
+        };
+
         // Phase 2: Fetching jobs
         let progress = progress.finish_phase_1_start_phase_2();
 
         // Extract base URL from graphql_url (e.g., https://gitlab.com/api/graphql -> https://gitlab.com)
         let base_url = self.client.graphql_url.origin().ascii_serialization();
 
-        let pipeline_types = super::pipeline_types::group_pipeline_types(
+        let live = if live && online {
+            self.fetch_live_pipelines(limit, ref_, &base_url).await
+        } else {
+            None
+        };
+
+        // A chronological sample already is the timeline of the ref
+        let fetched_timeline = match ref_ {
+            Some(ref_) if branch_timeline && online && sampling != Sampling::Chronological => {
+                self.fetch_branch_timeline(limit, ref_, updated_after, updated_before, &pipelines)
+                    .await
+            }
+            _ => None,
+        };
+
+        // Offline or unless opted in, the canceled and merge request reports fall back to
+        // the sampled pipelines, as fetching beyond the sample is uncached
+        let fetched_canceled = if canceled_pipelines && online {
+            self.fetch_canceled_pipelines(
+                limit,
+                ref_,
+                updated_after,
+                updated_before,
+                &pipelines,
+                &population,
+            )
+            .await
+        } else {
+            None
+        };
+
+        let merge_request_pipelines = if merge_requests && online {
+            self.fetch_merge_request_pipelines(limit, updated_after, updated_before, &pipelines)
+                .await
+        } else {
+            None
+        };
+
+        let failure_signatures = match signature_rules {
+            Some(rules) if online => {
+                self.mine_failure_signatures(&pipelines, rules, &base_url)
+                    .await
+            }
+            _ => Vec::new(),
+        };
+
+        let grouping = super::pipeline_types::group_pipeline_types(
             &pipelines,
             min_type_percentage,
             &base_url,
             &self.project_path,
-            cost_per_minute,
+            cost_rates,
+            clustering,
+            label_rules,
+        );
+        let merge_requests = super::merge_requests::calculate_merge_request_metrics(
+            merge_request_pipelines.as_deref().unwrap_or(&pipelines),
+        );
+        let branch_health = ref_.and_then(|ref_| {
+            let timeline = match &fetched_timeline {
+                Some(timeline) => timeline,
+                None if sampling == Sampling::Chronological => &pipelines,
+                None => {
+                    warn!(
+                        "Skipping branch health of {ref_}: {sampling} samples are no \
+                         contiguous timeline (use --sampling chronological or --branch-timeline)"
+                    );
+                    return None;
+                }
+            };
+            super::branch_health::calculate_branch_health(
+                timeline,
+                ref_,
+                &base_url,
+                &self.project_path,
+            )
+        });
+        let (canceled_pipelines, status_counts) = match &fetched_canceled {
+            Some((canceled, counts)) => (canceled.as_slice(), Some(counts)),
+            None => (pipelines.as_slice(), None),
+        };
+        let canceled = super::canceled::summarize_canceled_pipelines(
+            canceled_pipelines,
+            status_counts,
+            &base_url,
+            &self.project_path,
+            cost_rates,
         );
 
         // Phase 3: Processing data
@@ -260,8 +1074,17 @@ impl GitLabProvider {
             project: self.project_path.clone(),
             collected_at: Utc::now(),
             total_pipelines: pipelines.len(),
-            total_pipeline_types: pipeline_types.len(),
-            pipeline_types,
+            total_pipeline_types: grouping.pipeline_types.len(),
+            pipeline_types: grouping.pipeline_types,
+            sampling: Some(summarize_sampling(sampling, &pipelines, population)),
+            clustering: Some(grouping.clustering),
+            excluded: grouping.excluded,
+            merge_requests,
+            branch_health,
+            deployments,
+            failure_signatures,
+            canceled,
+            live,
         };
 
         progress.finish_phase_3();
@@ -269,3 +1092,12 @@ impl GitLabProvider {
         Ok(insights)
     }
 }
+
+/// Moves JUnit test case results, keyed by job ID, onto their jobs.
+fn assign_test_cases(jobs: &mut [GitLabJob], mut reports: HashMap<String, Vec<GitLabTestCase>>) {
+    for job in jobs {
+        if let Some(test_cases) = reports.remove(&job.id) {
+            job.test_cases = test_cases;
+        }
+    }
+}
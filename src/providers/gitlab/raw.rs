@@ -0,0 +1,175 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use log::info;
+
+use crate::error::Result;
+
+use super::types::GitLabPipeline;
+
+/// Serializes pipelines with their jobs to a raw data file for offline analysis.
+///
+/// Files ending in `.ndjson` or `.jsonl` get one pipeline per line, any other file a
+/// JSON array. JUnit test case results are not included.
+///
+/// # Arguments
+///
+/// * `pipelines` - Pipelines to export, including their sampling weights
+/// * `path` - File to write, replaced if it exists
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be written.
+pub(super) fn write_raw_pipelines(pipelines: &[GitLabPipeline], path: &Path) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    if is_ndjson(path) {
+        for pipeline in pipelines {
+            serde_json::to_writer(&mut writer, pipeline)?;
+            writer.write_all(b"\n")?;
+        }
+    } else {
+        serde_json::to_writer(&mut writer, pipelines)?;
+    }
+    writer.flush()?;
+
+    info!(
+        "Exported {} pipelines to: {}",
+        pipelines.len(),
+        path.display()
+    );
+
+    Ok(())
+}
+
+/// Reads pipelines from a raw data file written by [`write_raw_pipelines`].
+///
+/// The layout is detected from the content, so JSON arrays and NDJSON are both read
+/// regardless of the file name.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or holds no valid pipelines.
+pub(super) fn read_raw_pipelines(path: &Path) -> Result<Vec<GitLabPipeline>> {
+    let content = fs::read_to_string(path)?;
+
+    let pipelines = if content.trim_start().starts_with('[') {
+        serde_json::from_str(&content)?
+    } else {
+        serde_json::Deserializer::from_str(&content)
+            .into_iter()
+            .collect::<std::result::Result<_, _>>()?
+    };
+
+    Ok(pipelines)
+}
+
+fn is_ndjson(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            extension.eq_ignore_ascii_case("ndjson") || extension.eq_ignore_ascii_case("jsonl")
+        })
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use crate::providers::gitlab::types::GitLabJob;
+    use tempfile::TempDir;
+
+    fn create_pipeline(id: u32, status: &str) -> GitLabPipeline {
+        GitLabPipeline {
+            id: format!("gid://gitlab/Ci::Pipeline/{id}"),
+            ref_: "main".to_string(),
+            sha: format!("sha{id}"),
+            weight: 2.5,
+            source: "push".to_string(),
+            status: status.to_string(),
+            duration: 60,
+            created_at: None,
+            finished_at: None,
+            merge_request: None,
+            stages: vec!["test".to_string()],
+            jobs: vec![GitLabJob {
+                id: format!("gid://gitlab/Ci::Job/{id}"),
+                name: "test".to_string(),
+                stage: "test".to_string(),
+                duration: 30.0,
+                status: "SUCCESS".to_string(),
+                retried: false,
+                needs: None,
+                tags: vec![],
+                runner: None,
+                test_cases: vec![],
+            }],
+        }
+    }
+
+    mod write_raw_pipelines {
+        use super::*;
+
+        #[test]
+        fn roundtrips_json_and_ndjson() {
+            let temp_dir = TempDir::new().unwrap();
+            let pipelines = vec![create_pipeline(1, "success"), create_pipeline(2, "failed")];
+
+            for file_name in ["pipelines.json", "pipelines.ndjson"] {
+                let path = temp_dir.path().join(file_name);
+                write_raw_pipelines(&pipelines, &path).unwrap();
+
+                let read = read_raw_pipelines(&path).unwrap();
+
+                assert_eq!(read.len(), 2, "{file_name}");
+                assert_eq!(read[1].status, "failed");
+                assert_eq!(read[1].weight, 2.5);
+                assert_eq!(read[1].jobs[0].name, "test");
+            }
+        }
+
+        #[test]
+        fn writes_one_pipeline_per_line_for_ndjson() {
+            let temp_dir = TempDir::new().unwrap();
+            let path = temp_dir.path().join("pipelines.jsonl");
+
+            write_raw_pipelines(
+                &[create_pipeline(1, "success"), create_pipeline(2, "failed")],
+                &path,
+            )
+            .unwrap();
+
+            let content = fs::read_to_string(&path).unwrap();
+            assert_eq!(content.lines().count(), 2);
+            assert!(content.starts_with('{'));
+        }
+    }
+
+    mod read_raw_pipelines {
+        use super::*;
+
+        #[test]
+        fn defaults_missing_weight_to_one() {
+            let temp_dir = TempDir::new().unwrap();
+            let path = temp_dir.path().join("fixture.json");
+            let mut pipeline = serde_json::to_value(create_pipeline(1, "success")).unwrap();
+            pipeline.as_object_mut().unwrap().remove("weight");
+            fs::write(&path, serde_json::json!([pipeline]).to_string()).unwrap();
+
+            let read = read_raw_pipelines(&path).unwrap();
+
+            assert_eq!(read[0].weight, 1.0);
+            assert_eq!(read[0].ref_, "main");
+        }
+
+        #[test]
+        fn rejects_invalid_data() {
+            let temp_dir = TempDir::new().unwrap();
+            let path = temp_dir.path().join("broken.ndjson");
+            fs::write(&path, "{\"id\": 1}\n").unwrap();
+
+            assert!(read_raw_pipelines(&path).is_err());
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::links::extract_numeric_id;
+use super::types::GitLabPipeline;
+use crate::insights::SamplingSummary;
+
+/// How the pipelines analyzed within `--limit` are chosen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sampling {
+    /// Half successful and half failed pipelines. Deterministic, but rates are biased
+    /// whenever the real failure rate is not 50%.
+    #[default]
+    Balanced,
+    /// The most recent finished pipelines of any status, so rates are unbiased.
+    Chronological,
+    /// An equal share of successful, failed and canceled pipelines, weighted by the
+    /// number of pipelines of each status in the queried range when calculating rates.
+    Stratified,
+}
+
+impl Sampling {
+    /// Returns the strategy's name as used on the command line.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Sampling::Balanced => "balanced",
+            Sampling::Chronological => "chronological",
+            Sampling::Stratified => "stratified",
+        }
+    }
+}
+
+impl fmt::Display for Sampling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Sampling {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "balanced" => Ok(Sampling::Balanced),
+            "chronological" => Ok(Sampling::Chronological),
+            "stratified" => Ok(Sampling::Stratified),
+            _ => Err(format!(
+                "`{value}` is not a sampling strategy (balanced, chronological, stratified)"
+            )),
+        }
+    }
+}
+
+/// Chooses the pipelines that fetching with the strategy would return from a larger set.
+///
+/// Like the GitLab API, the most recent (highest ID) pipelines of each status are kept.
+///
+/// # Arguments
+///
+/// * `pipelines` - Finished pipelines, e.g. cached ones merged with newly fetched ones
+/// * `limit` - Maximum number of pipelines, split across statuses like `fetch_pipelines`
+/// * `sampling` - Sampling strategy
+pub(super) fn select_sample(
+    mut pipelines: Vec<GitLabPipeline>,
+    limit: usize,
+    sampling: Sampling,
+) -> Vec<GitLabPipeline> {
+    pipelines.sort_by_key(|p| Reverse(extract_numeric_id(&p.id).parse::<u64>().unwrap_or(0)));
+
+    let statuses: &[&str] = match sampling {
+        Sampling::Chronological => {
+            pipelines.truncate(limit);
+            return pipelines;
+        }
+        Sampling::Balanced => &["success", "failed"],
+        Sampling::Stratified => &["success", "failed", "canceled"],
+    };
+
+    let quota = limit / statuses.len();
+    let mut taken: HashMap<String, usize> = HashMap::new();
+    pipelines
+        .into_iter()
+        .filter(|p| {
+            if !statuses.contains(&p.status.as_str()) {
+                return false;
+            }
+            let count = taken.entry(p.status.clone()).or_insert(0);
+            *count += 1;
+            *count <= quota
+        })
+        .collect()
+}
+
+/// Weights each pipeline by how many pipelines of its status it stands for.
+///
+/// # Arguments
+///
+/// * `pipelines` - Sampled pipelines
+/// * `population` - Number of pipelines per status in the queried range
+pub(super) fn apply_stratified_weights(
+    pipelines: &mut [GitLabPipeline],
+    population: &BTreeMap<String, usize>,
+) {
+    let sampled = count_by_status(pipelines);
+
+    for pipeline in pipelines {
+        let sampled = sampled.get(&pipeline.status).copied().unwrap_or(0);
+        if let Some(&population) = population.get(&pipeline.status) {
+            #[allow(clippy::cast_precision_loss)]
+            let weight = population as f64 / sampled.max(1) as f64;
+            // A status counted as empty still has the pipelines we fetched
+            pipeline.weight = weight.max(1.0);
+        }
+    }
+}
+
+/// Summarizes the sampling strategy and the number of pipelines sampled per status.
+pub(super) fn summarize_sampling(
+    sampling: Sampling,
+    pipelines: &[GitLabPipeline],
+    population: BTreeMap<String, usize>,
+) -> SamplingSummary {
+    SamplingSummary {
+        strategy: sampling.as_str().to_string(),
+        sampled: count_by_status(pipelines),
+        population,
+    }
+}
+
+fn count_by_status(pipelines: &[GitLabPipeline]) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for pipeline in pipelines {
+        *counts.entry(pipeline.status.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    fn create_pipeline(id: u32, status: &str) -> GitLabPipeline {
+        GitLabPipeline {
+            id: format!("gid://gitlab/Ci::Pipeline/{id}"),
+            ref_: "main".to_string(),
+            sha: format!("sha{id}"),
+            weight: 1.0,
+            source: "push".to_string(),
+            status: status.to_string(),
+            duration: 60,
+            created_at: None,
+            finished_at: None,
+            merge_request: None,
+            stages: vec![],
+            jobs: vec![],
+        }
+    }
+
+    mod from_str {
+        use super::*;
+
+        #[test]
+        fn parses_strategy_names() {
+            assert_eq!("balanced".parse(), Ok(Sampling::Balanced));
+            assert_eq!("Chronological".parse(), Ok(Sampling::Chronological));
+            assert_eq!("stratified".parse(), Ok(Sampling::Stratified));
+            assert!("random".parse::<Sampling>().is_err());
+        }
+    }
+
+    mod select_sample {
+        use super::*;
+
+        fn ids(pipelines: &[GitLabPipeline]) -> Vec<&str> {
+            pipelines
+                .iter()
+                .map(|p| extract_numeric_id(&p.id))
+                .collect()
+        }
+
+        #[test]
+        fn keeps_most_recent_pipelines_of_any_status_for_chronological() {
+            let pipelines = vec![
+                create_pipeline(1, "success"),
+                create_pipeline(10, "failed"),
+                create_pipeline(5, "canceled"),
+            ];
+
+            let sample = select_sample(pipelines, 2, Sampling::Chronological);
+
+            assert_eq!(ids(&sample), vec!["10", "5"]);
+        }
+
+        #[test]
+        fn keeps_most_recent_pipelines_per_status_for_balanced() {
+            let pipelines = vec![
+                create_pipeline(1, "success"),
+                create_pipeline(2, "failed"),
+                create_pipeline(3, "success"),
+                create_pipeline(4, "canceled"),
+                create_pipeline(5, "failed"),
+                create_pipeline(6, "success"),
+            ];
+
+            let sample = select_sample(pipelines, 4, Sampling::Balanced);
+
+            assert_eq!(ids(&sample), vec!["6", "5", "3", "2"]);
+        }
+    }
+
+    mod apply_stratified_weights {
+        use super::*;
+
+        #[test]
+        fn weights_pipelines_by_population_ratio() {
+            let mut pipelines = vec![
+                create_pipeline(1, "success"),
+                create_pipeline(2, "success"),
+                create_pipeline(3, "failed"),
+                create_pipeline(4, "failed"),
+            ];
+            let population =
+                BTreeMap::from([("success".to_string(), 90), ("failed".to_string(), 10)]);
+
+            apply_stratified_weights(&mut pipelines, &population);
+
+            assert_eq!(pipelines[0].weight, 45.0);
+            assert_eq!(pipelines[2].weight, 5.0);
+        }
+
+        #[test]
+        fn keeps_weight_for_statuses_without_population() {
+            let mut pipelines = vec![create_pipeline(1, "canceled")];
+
+            apply_stratified_weights(&mut pipelines, &BTreeMap::new());
+
+            assert_eq!(pipelines[0].weight, 1.0);
+        }
+    }
+
+    mod summarize_sampling {
+        use super::*;
+
+        #[test]
+        fn counts_sampled_pipelines_per_status() {
+            let pipelines = vec![
+                create_pipeline(1, "success"),
+                create_pipeline(2, "failed"),
+                create_pipeline(3, "success"),
+            ];
+
+            let summary = summarize_sampling(Sampling::Chronological, &pipelines, BTreeMap::new());
+
+            assert_eq!(summary.strategy, "chronological");
+            assert_eq!(summary.sampled["success"], 2);
+            assert_eq!(summary.sampled["failed"], 1);
+            assert!(summary.population.is_empty());
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::{BTreeSet, HashMap};
+
+use super::job_metrics::job_finish_times;
+use super::types::GitLabPipeline;
+use crate::insights::StageMetrics;
+use crate::providers::stats::{calculate_percentiles, cmp_f64};
+
+#[derive(Default)]
+struct StageAccumulator {
+    /// Lowest position of the stage in any pipeline, used for ordering
+    order: usize,
+    spans: Vec<f64>,
+    pipeline_duration: f64,
+    jobs: BTreeSet<String>,
+    job_durations: HashMap<String, Vec<f64>>,
+    executions: usize,
+    failures: usize,
+}
+
+/// Calculates per-stage metrics for a pipeline type.
+///
+/// Stage spans use the same dependency model as time-to-feedback: a stage spans from
+/// the earliest start to the latest finish of its jobs. Spans, durations and the share
+/// of pipeline duration come from successful pipelines; the failure rate covers all
+/// pipelines in which the stage ran. Retried job executions are ignored.
+///
+/// # Arguments
+///
+/// * `pipelines` - All pipelines of a type, successful and failed
+///
+/// # Returns
+///
+/// Stage metrics in pipeline stage order.
+pub(super) fn calculate_stage_metrics(pipelines: &[&GitLabPipeline]) -> Vec<StageMetrics> {
+    let mut stages: HashMap<&str, StageAccumulator> = HashMap::new();
+
+    for pipeline in pipelines {
+        let succeeded = pipeline.status == "success";
+        let finish_times = succeeded.then(|| job_finish_times(pipeline));
+
+        for (position, stage) in pipeline.stages.iter().enumerate() {
+            let jobs: Vec<_> = pipeline
+                .jobs
+                .iter()
+                .filter(|job| job.stage == *stage && !job.retried)
+                .collect();
+            if jobs.is_empty() {
+                continue;
+            }
+
+            let acc = stages
+                .entry(stage.as_str())
+                .or_insert_with(|| StageAccumulator {
+                    order: position,
+                    ..StageAccumulator::default()
+                });
+            acc.order = acc.order.min(position);
+            acc.executions += 1;
+            if jobs.iter().any(|job| job.status == "FAILED") {
+                acc.failures += 1;
+            }
+
+            let Some(finish_times) = &finish_times else {
+                continue;
+            };
+
+            let mut start = f64::MAX;
+            let mut end = 0.0_f64;
+            for job in &jobs {
+                let finish = finish_times.get(job.name.as_str()).copied().unwrap_or(0.0);
+                start = start.min(finish - job.duration);
+                end = end.max(finish);
+
+                acc.jobs.insert(job.name.clone());
+                acc.job_durations
+                    .entry(job.name.clone())
+                    .or_default()
+                    .push(job.duration);
+            }
+
+            acc.spans.push((end - start).max(0.0));
+            #[allow(clippy::cast_precision_loss)]
+            {
+                acc.pipeline_duration += pipeline.duration as f64;
+            }
+        }
+    }
+
+    let mut stages: Vec<(&str, StageAccumulator)> = stages.into_iter().collect();
+    stages.sort_by(|a, b| a.1.order.cmp(&b.1.order).then_with(|| a.0.cmp(b.0)));
+
+    stages
+        .into_iter()
+        .map(|(name, acc)| build_stage_metrics(name, acc))
+        .collect()
+}
+
+fn build_stage_metrics(name: &str, acc: StageAccumulator) -> StageMetrics {
+    let (span_p50, span_p95, _) = calculate_percentiles(&acc.spans);
+
+    let total_span: f64 = acc.spans.iter().sum();
+    let duration_share = if acc.pipeline_duration > 0.0 {
+        (total_span / acc.pipeline_duration * 100.0).min(100.0)
+    } else {
+        0.0
+    };
+
+    let slowest = acc
+        .job_durations
+        .iter()
+        .map(|(job, durations)| (job, calculate_percentiles(durations).1))
+        .max_by(|a, b| cmp_f64(a.1, b.1).then_with(|| b.0.cmp(a.0)));
+
+    #[allow(clippy::cast_precision_loss)]
+    let failure_rate = if acc.executions > 0 {
+        (acc.failures as f64 / acc.executions as f64) * 100.0
+    } else {
+        0.0
+    };
+
+    StageMetrics {
+        name: name.to_string(),
+        span_p50,
+        span_p95,
+        duration_share,
+        jobs: acc.jobs.into_iter().collect(),
+        slowest_job: slowest.map(|(job, _)| job.clone()),
+        slowest_job_duration_p95: slowest.map_or(0.0, |(_, duration)| duration),
+        failure_rate,
+        total_executions: acc.executions,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use crate::providers::gitlab::types::GitLabJob;
+
+    fn create_job(name: &str, stage: &str, duration: f64, status: &str) -> GitLabJob {
+        GitLabJob {
+            id: format!("gid://gitlab/Ci::Job/{name}"),
+            name: name.to_string(),
+            stage: stage.to_string(),
+            duration,
+            status: status.to_string(),
+            retried: false,
+            needs: None,
+            tags: vec![],
+            runner: None,
+            test_cases: vec![],
+        }
+    }
+
+    fn create_pipeline(status: &str, duration: usize, jobs: Vec<GitLabJob>) -> GitLabPipeline {
+        GitLabPipeline {
+            id: "gid://gitlab/Ci::Pipeline/1".to_string(),
+            ref_: "main".to_string(),
+            sha: "abc123".to_string(),
+            weight: 1.0,
+            source: "push".to_string(),
+            status: status.to_string(),
+            duration,
+            created_at: None,
+            finished_at: None,
+            merge_request: None,
+            stages: vec!["build".to_string(), "test".to_string(), "scan".to_string()],
+            jobs,
+        }
+    }
+
+    mod calculate_stage_metrics {
+        use super::*;
+
+        #[test]
+        fn returns_stages_in_pipeline_order() {
+            let pipeline = create_pipeline(
+                "success",
+                100,
+                vec![
+                    create_job("sast", "scan", 10.0, "SUCCESS"),
+                    create_job("compile", "build", 30.0, "SUCCESS"),
+                    create_job("unit", "test", 60.0, "SUCCESS"),
+                ],
+            );
+
+            let stages = calculate_stage_metrics(&[&pipeline]);
+
+            let names: Vec<_> = stages.iter().map(|s| s.name.as_str()).collect();
+            assert_eq!(names, vec!["build", "test", "scan"]);
+        }
+
+        #[test]
+        fn measures_stage_span_and_share() {
+            // build: 0-30s, test: parallel jobs 30-90s and 30-50s
+            let pipeline = create_pipeline(
+                "success",
+                100,
+                vec![
+                    create_job("compile", "build", 30.0, "SUCCESS"),
+                    create_job("unit", "test", 60.0, "SUCCESS"),
+                    create_job("lint", "test", 20.0, "SUCCESS"),
+                ],
+            );
+
+            let stages = calculate_stage_metrics(&[&pipeline]);
+            let test = stages.iter().find(|s| s.name == "test").unwrap();
+
+            assert_eq!(test.span_p50, 60.0, "Parallel jobs should not add up");
+            assert_eq!(test.duration_share, 60.0);
+            assert_eq!(test.jobs, vec!["lint", "unit"]);
+            assert_eq!(test.slowest_job.as_deref(), Some("unit"));
+            assert_eq!(test.slowest_job_duration_p95, 60.0);
+        }
+
+        #[test]
+        fn counts_stage_failures_across_all_pipelines() {
+            let passed = create_pipeline(
+                "success",
+                40,
+                vec![
+                    create_job("compile", "build", 10.0, "SUCCESS"),
+                    create_job("sast", "scan", 30.0, "SUCCESS"),
+                ],
+            );
+            let failed = create_pipeline(
+                "failed",
+                40,
+                vec![
+                    create_job("compile", "build", 10.0, "SUCCESS"),
+                    create_job("sast", "scan", 30.0, "FAILED"),
+                ],
+            );
+
+            let stages = calculate_stage_metrics(&[&passed, &failed]);
+            let scan = stages.iter().find(|s| s.name == "scan").unwrap();
+            let build = stages.iter().find(|s| s.name == "build").unwrap();
+
+            assert_eq!(scan.failure_rate, 50.0);
+            assert_eq!(scan.total_executions, 2);
+            assert_eq!(build.failure_rate, 0.0);
+            assert_eq!(
+                scan.span_p95, 30.0,
+                "Spans should only come from successful pipelines"
+            );
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::debug;
+
+use crate::error::Result;
+
+/// First bytes of every gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Exclusive lock on a cache file, shared by all cilens processes on the machine.
+///
+/// The lock lives in a separate `.lock` file next to the cache file, because the cache
+/// file itself is replaced on every write. It is released when dropped.
+pub(super) struct FileLock {
+    file: File,
+    lock_file: PathBuf,
+}
+
+impl FileLock {
+    /// Blocks until no other process holds the lock for `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lock file cannot be opened or locked.
+    pub(super) fn acquire(path: &Path) -> Result<Self> {
+        let lock_file = lock_file(path);
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_file)?;
+
+        if file.try_lock().is_err() {
+            debug!(
+                "Waiting for another cilens run to release {}",
+                lock_file.display()
+            );
+            file.lock()?;
+        }
+
+        Ok(Self { file, lock_file })
+    }
+
+    /// Releases the lock and removes its lock file, once the file it guards is removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lock file cannot be removed.
+    pub(super) fn remove(self) -> Result<()> {
+        let Self { file, lock_file } = self;
+        drop(file);
+
+        match fs::remove_file(&lock_file) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => Ok(result?),
+        }
+    }
+}
+
+/// Returns the lock file guarding `path` (e.g., `group-project.json.gz` → `group-project.lock`).
+pub(super) fn lock_file(path: &Path) -> PathBuf {
+    path.with_file_name(format!("{}.lock", file_stem(path)))
+}
+
+/// Returns a cache file name without its `.json` or `.json.gz` extension.
+pub(super) fn file_stem(path: &Path) -> String {
+    let file_name = path
+        .file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+    let file_name = file_name.strip_suffix(".gz").unwrap_or(&file_name);
+
+    file_name
+        .strip_suffix(".json")
+        .unwrap_or(file_name)
+        .to_string()
+}
+
+/// Replaces a file without readers ever seeing it half-written.
+///
+/// The content goes to a temporary file in the same directory, which is synced and
+/// then renamed over `path`. A run that is interrupted leaves the old file in place.
+///
+/// # Errors
+///
+/// Returns an error if the temporary file cannot be written or renamed.
+pub(super) fn write_atomically(path: &Path, content: &[u8]) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+    let temp_file = path.with_file_name(format!(".{file_name}.{}.tmp", process::id()));
+
+    let result = File::create(&temp_file)
+        .and_then(|mut file| {
+            file.write_all(content)?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp_file, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_file);
+    }
+
+    Ok(result?)
+}
+
+/// Gzip-compresses data.
+///
+/// # Errors
+///
+/// Returns an error if compression fails.
+pub(super) fn compress(content: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(content)?;
+
+    Ok(encoder.finish()?)
+}
+
+/// Reads a file as text, decompressing it if it is gzip-compressed.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or decompressed, or is not UTF-8.
+pub(super) fn read_to_string(path: &Path) -> Result<String> {
+    let content = fs::read(path)?;
+
+    if !content.starts_with(&GZIP_MAGIC) {
+        return String::from_utf8(content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e).into());
+    }
+
+    let mut decompressed = String::new();
+    GzDecoder::new(content.as_slice()).read_to_string(&mut decompressed)?;
+
+    Ok(decompressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_compressed_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("group-project.json.gz");
+        let content = "{\"pipelines\":{}}".repeat(100);
+
+        write_atomically(&path, &compress(content.as_bytes()).unwrap()).unwrap();
+
+        assert!(fs::metadata(&path).unwrap().len() < content.len() as u64);
+        assert_eq!(read_to_string(&path).unwrap(), content);
+    }
+
+    #[test]
+    fn test_reads_uncompressed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("group-project.json");
+        fs::write(&path, "{}").unwrap();
+
+        assert_eq!(read_to_string(&path).unwrap(), "{}");
+    }
+
+    #[test]
+    fn test_write_atomically_replaces_file_without_leftovers() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("group-project.json");
+        fs::write(&path, "old").unwrap();
+
+        write_atomically(&path, b"new").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_lock_file_is_shared_by_cache_layouts() {
+        let dir = Path::new("/cache");
+
+        assert_eq!(
+            lock_file(&dir.join("group-project.json.gz")),
+            dir.join("group-project.lock")
+        );
+        assert_eq!(
+            lock_file(&dir.join("group-project.json")),
+            dir.join("group-project.lock")
+        );
+        assert_eq!(
+            lock_file(&dir.join("group-my.project.json.gz")),
+            dir.join("group-my.project.lock")
+        );
+    }
+
+    #[test]
+    fn test_lock_is_released_on_drop() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("group-project.json.gz");
+
+        let lock = FileLock::acquire(&path).unwrap();
+        let other = File::options().write(true).open(lock_file(&path)).unwrap();
+        assert!(other.try_lock().is_err());
+
+        drop(lock);
+        assert!(other.try_lock().is_ok());
+    }
+}
@@ -0,0 +1,358 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::{debug, info, warn};
+
+use crate::error::Result;
+
+use super::cache;
+use super::storage::{self, FileLock};
+use super::types::{GitLabPipeline, GitLabTestCase};
+
+/// Test cases per job ID for one pipeline.
+type CachedTestReports = HashMap<String, Vec<GitLabTestCase>>;
+
+/// Test report cache for GitLab pipelines.
+///
+/// Caches JUnit test case results of completed pipelines alongside the job cache, so
+/// test reports are only fetched once per pipeline:
+/// - Linux: `~/.cache/cilens/gitlab/{project-slug}@{instance-slug}-tests.json.gz`
+/// - macOS: `~/Library/Caches/cilens/gitlab/{project-slug}@{instance-slug}-tests.json.gz`
+///
+/// Like `JobCache`, it is loaded into memory at startup, derived from the final
+/// pipeline data when saved, and written gzip-compressed while holding the file's lock.
+pub struct TestReportCache {
+    cache_file: PathBuf,
+    pipelines: HashMap<String, CachedTestReports>,
+    enabled: bool,
+}
+
+impl TestReportCache {
+    /// Creates a new test report cache instance, loading existing data from disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_url` - GitLab instance base URL the test reports are fetched from
+    /// * `project_path` - GitLab project path (e.g., "group/project")
+    /// * `enabled` - Whether caching is enabled
+    ///
+    /// # Errors
+    ///
+    /// Returns error if cache directory cannot be determined or created.
+    pub fn new(base_url: &str, project_path: &str, enabled: bool) -> Result<Self> {
+        if !enabled {
+            debug!("Test report cache disabled");
+            return Ok(Self {
+                cache_file: PathBuf::new(),
+                pipelines: HashMap::new(),
+                enabled: false,
+            });
+        }
+
+        let cache_dir = cache::cache_dir()?;
+        fs::create_dir_all(&cache_dir)?;
+
+        Ok(Self::open(cache_dir.join(cache_filename(
+            &cache::cache_stem(base_url, project_path),
+        ))))
+    }
+
+    /// Loads the cache from `cache_file`, or from the uncompressed file an older cilens
+    /// wrote instead, which is replaced on the next save.
+    fn open(cache_file: PathBuf) -> Self {
+        let existing = [cache_file.clone(), legacy_cache_file(&cache_file)]
+            .into_iter()
+            .find(|path| path.exists());
+
+        let pipelines = match existing {
+            Some(existing) => storage::read_to_string(&existing)
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok())
+                .inspect(|_| debug!("Loaded test report cache from: {}", existing.display()))
+                .unwrap_or_else(|| {
+                    warn!("Failed to load test report cache, starting with empty cache");
+                    HashMap::new()
+                }),
+            None => HashMap::new(),
+        };
+
+        Self {
+            cache_file,
+            pipelines,
+            enabled: true,
+        }
+    }
+
+    /// Retrieves cached test cases per job ID for a pipeline.
+    ///
+    /// Returns `None` if caching is disabled or the pipeline's reports were never fetched.
+    pub fn get(&self, pipeline_id: &str) -> Option<CachedTestReports> {
+        if !self.enabled {
+            return None;
+        }
+
+        self.pipelines.get(pipeline_id).cloned()
+    }
+
+    /// Derives the cache from pipelines with fetched test reports and saves it to disk
+    /// gzip-compressed, replacing the file atomically while holding its lock.
+    ///
+    /// # Arguments
+    ///
+    /// * `pipelines` - Pipelines whose jobs carry their test cases
+    /// * `without_test_reports` - IDs of pipelines whose test reports could not be
+    ///   fetched, left out so the next run fetches them again
+    pub fn save_pipelines(
+        &self,
+        pipelines: &[GitLabPipeline],
+        without_test_reports: &HashSet<String>,
+    ) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let cache: HashMap<&str, HashMap<&str, &Vec<GitLabTestCase>>> = pipelines
+            .iter()
+            .filter(|pipeline| !without_test_reports.contains(&pipeline.id))
+            .map(|pipeline| {
+                let reports = pipeline
+                    .jobs
+                    .iter()
+                    .filter(|job| !job.test_cases.is_empty())
+                    .map(|job| (job.id.as_str(), &job.test_cases))
+                    .collect();
+                (pipeline.id.as_str(), reports)
+            })
+            .collect();
+
+        let _lock = FileLock::acquire(&self.cache_file)?;
+        let content = storage::compress(&serde_json::to_vec(&cache)?)?;
+        storage::write_atomically(&self.cache_file, &content)?;
+        let legacy_file = legacy_cache_file(&self.cache_file);
+        if legacy_file.exists() {
+            fs::remove_file(&legacy_file)?;
+        }
+
+        debug!(
+            "Saved test reports of {} pipelines to cache: {}",
+            cache.len(),
+            self.cache_file.display()
+        );
+
+        Ok(())
+    }
+
+    /// Removes the project's test report cache files of all GitLab instances from disk,
+    /// including uncompressed ones and those named after the project only, written by
+    /// an older cilens, and their lock files.
+    ///
+    /// # Returns
+    ///
+    /// The removed cache files.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache directory cannot be listed or a cache file cannot
+    /// be locked or removed.
+    pub fn clear_project_cache(project_path: &str) -> Result<Vec<PathBuf>> {
+        clear_cache_files(&cache::cache_dir()?, project_path)
+    }
+
+    /// Returns the size of the test report cache file stored next to the job cache named
+    /// `cache_stem` in bytes (0 if absent).
+    pub(super) fn disk_usage(cache_stem: &str) -> u64 {
+        cache::cache_dir()
+            .and_then(|cache_dir| {
+                Ok(fs::metadata(cache_dir.join(cache_filename(cache_stem)))?.len())
+            })
+            .unwrap_or(0)
+    }
+}
+
+/// Returns the test report cache file name for a [`cache::cache_stem`].
+fn cache_filename(cache_stem: &str) -> String {
+    format!("{cache_stem}-tests.json.gz")
+}
+
+fn clear_cache_files(cache_dir: &Path, project_path: &str) -> Result<Vec<PathBuf>> {
+    if !cache_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    // Both the compressed file and an uncompressed one map to the compressed name
+    let cache_files: BTreeSet<PathBuf> = fs::read_dir(cache_dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            let file_name = path.to_string_lossy();
+            path.is_file() && (file_name.ends_with(".json.gz") || file_name.ends_with(".json"))
+        })
+        .filter_map(|path| {
+            let stem = storage::file_stem(&path);
+            let owner = stem.strip_suffix("-tests")?;
+            cache::is_project_cache_stem(owner, project_path)
+                .then(|| cache_dir.join(cache_filename(owner)))
+        })
+        .collect();
+
+    let mut removed = Vec::new();
+    for cache_file in cache_files {
+        let lock = FileLock::acquire(&cache_file)?;
+        for cache_file in [legacy_cache_file(&cache_file), cache_file] {
+            if cache_file.exists() {
+                fs::remove_file(&cache_file)?;
+                info!("Test report cache cleared: {}", cache_file.display());
+                removed.push(cache_file);
+            }
+        }
+        lock.remove()?;
+    }
+
+    Ok(removed)
+}
+
+/// Returns the uncompressed cache file older cilens versions wrote instead of `cache_file`.
+fn legacy_cache_file(cache_file: &Path) -> PathBuf {
+    cache_file.with_extension("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::gitlab::types::GitLabJob;
+    use tempfile::TempDir;
+
+    fn create_test_pipeline(id: &str, test_cases: Vec<GitLabTestCase>) -> GitLabPipeline {
+        GitLabPipeline {
+            id: id.to_string(),
+            ref_: "main".to_string(),
+            sha: "abc123".to_string(),
+            weight: 1.0,
+            source: "push".to_string(),
+            status: "success".to_string(),
+            duration: 100,
+            created_at: None,
+            finished_at: None,
+            merge_request: None,
+            stages: vec![],
+            jobs: vec![GitLabJob {
+                id: "gid://gitlab/Ci::Build/1".to_string(),
+                name: "rspec".to_string(),
+                stage: "test".to_string(),
+                duration: 10.0,
+                status: "SUCCESS".to_string(),
+                retried: false,
+                needs: None,
+                tags: vec![],
+                runner: None,
+                test_cases,
+            }],
+        }
+    }
+
+    fn load_cache(dir: &std::path::Path) -> TestReportCache {
+        TestReportCache::open(dir.join("group-project-tests.json.gz"))
+    }
+
+    #[test]
+    fn test_cache_disabled() {
+        let cache = TestReportCache::new("https://gitlab.com", "group/project", false).unwrap();
+
+        assert!(cache.get("pipeline-1").is_none());
+        assert!(cache.save_pipelines(&[], &HashSet::new()).is_ok());
+    }
+
+    #[test]
+    fn test_cache_save_and_load_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = load_cache(temp_dir.path());
+
+        let test_case = GitLabTestCase {
+            name: "User#valid?".to_string(),
+            status: "failed".to_string(),
+            duration: 0.5,
+        };
+        let pipelines = vec![
+            create_test_pipeline("pipeline-1", vec![test_case]),
+            create_test_pipeline("pipeline-2", vec![]),
+            create_test_pipeline("pipeline-3", vec![]),
+        ];
+        let without_test_reports = HashSet::from(["pipeline-3".to_string()]);
+        cache
+            .save_pipelines(&pipelines, &without_test_reports)
+            .unwrap();
+
+        let reloaded = load_cache(temp_dir.path());
+
+        let reports = reloaded.get("pipeline-1").unwrap();
+        assert_eq!(reports["gid://gitlab/Ci::Build/1"][0].name, "User#valid?");
+        assert!(
+            reloaded.get("pipeline-2").unwrap().is_empty(),
+            "Pipelines without reports should be cached as fetched"
+        );
+        assert!(
+            reloaded.get("pipeline-3").is_none(),
+            "Pipelines whose reports could not be fetched should be fetched again"
+        );
+    }
+
+    #[test]
+    fn test_cache_compresses_uncompressed_file_on_save() {
+        let temp_dir = TempDir::new().unwrap();
+        let legacy_file = temp_dir.path().join("group-project-tests.json");
+        fs::write(
+            &legacy_file,
+            r#"{"pipeline-1": {"gid://gitlab/Ci::Build/1": []}}"#,
+        )
+        .unwrap();
+
+        let cache = load_cache(temp_dir.path());
+        assert!(cache.get("pipeline-1").is_some());
+        cache
+            .save_pipelines(
+                &[create_test_pipeline("pipeline-2", vec![])],
+                &HashSet::new(),
+            )
+            .unwrap();
+
+        let cache_file = temp_dir.path().join("group-project-tests.json.gz");
+        assert!(!legacy_file.exists());
+        assert!(fs::read(&cache_file).unwrap().starts_with(&[0x1f, 0x8b]));
+        assert!(load_cache(temp_dir.path()).get("pipeline-2").is_some());
+    }
+
+    #[test]
+    fn test_cache_filename_is_scoped_by_instance() {
+        assert_eq!(
+            cache_filename(&cache::cache_stem(
+                "https://gitlab.example.com:8443",
+                "group/project"
+            )),
+            "group-project@gitlab.example.com-8443-tests.json.gz"
+        );
+    }
+
+    #[test]
+    fn test_clear_cache_files_removes_all_instances_of_project() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        for file in [
+            "group-project@gitlab.com-tests.json.gz",
+            "group-project@gitlab.example.com-tests.json",
+            "group-project-tests.json.gz",
+            "group-project-other@gitlab.com-tests.json.gz",
+            "group-project@gitlab.com.json.gz",
+        ] {
+            fs::write(dir.join(file), "{}").unwrap();
+        }
+
+        let removed = clear_cache_files(dir, "group/project").unwrap();
+
+        assert_eq!(removed.len(), 3);
+        assert!(dir
+            .join("group-project-other@gitlab.com-tests.json.gz")
+            .exists());
+        assert!(dir.join("group-project@gitlab.com.json.gz").exists());
+        assert!(!dir.join("group-project@gitlab.com-tests.lock").exists());
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::{HashMap, HashSet};
+
+use super::types::{GitLabPipeline, GitLabTestCase};
+use crate::insights::TestMetrics;
+use crate::providers::stats::{calculate_percentiles, cmp_f64};
+
+#[derive(Default)]
+struct TestData {
+    durations: Vec<f64>,
+    flaky: usize,
+    failed: usize,
+}
+
+/// Calculates per-test reliability and duration for each job from JUnit test cases.
+///
+/// Every execution of a test that was not skipped counts, including executions in retried
+/// jobs. A failure is flaky if the same test of the same job passed in any execution for
+/// the same commit and ref, whether on retry or in another pipeline; other failures stay
+/// failed. Only tests that failed or were flaky at least once are returned.
+///
+/// # Arguments
+///
+/// * `pipelines` - Pipelines whose jobs carry their test cases
+///
+/// # Returns
+///
+/// Unhealthy tests per job name, most flaky first.
+pub(super) fn calculate_test_metrics(
+    pipelines: &[&GitLabPipeline],
+) -> HashMap<String, Vec<TestMetrics>> {
+    // Tests that passed at least once per commit, ref and job
+    let mut passed: HashSet<(&str, &str, &str, &str)> = HashSet::new();
+    for pipeline in pipelines {
+        for job in &pipeline.jobs {
+            for test_case in job.test_cases.iter().filter(|t| is_passed(t)) {
+                passed.insert((
+                    commit_key(pipeline),
+                    pipeline.ref_.as_str(),
+                    job.name.as_str(),
+                    test_case.name.as_str(),
+                ));
+            }
+        }
+    }
+
+    let mut tests: HashMap<&str, HashMap<&str, TestData>> = HashMap::new();
+    for pipeline in pipelines {
+        for job in &pipeline.jobs {
+            for test_case in &job.test_cases {
+                if !is_passed(test_case) && !is_failed(test_case) {
+                    continue;
+                }
+
+                let data = tests
+                    .entry(job.name.as_str())
+                    .or_default()
+                    .entry(test_case.name.as_str())
+                    .or_default();
+                data.durations.push(test_case.duration);
+
+                if is_failed(test_case) {
+                    let key = (
+                        commit_key(pipeline),
+                        pipeline.ref_.as_str(),
+                        job.name.as_str(),
+                        test_case.name.as_str(),
+                    );
+                    if passed.contains(&key) {
+                        data.flaky += 1;
+                    } else {
+                        data.failed += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    tests
+        .into_iter()
+        .filter_map(|(job_name, job_tests)| {
+            let mut metrics: Vec<TestMetrics> = job_tests
+                .into_iter()
+                .filter(|(_, data)| data.flaky > 0 || data.failed > 0)
+                .map(|(name, data)| build_test_metrics(name, &data))
+                .collect();
+            if metrics.is_empty() {
+                return None;
+            }
+
+            metrics.sort_by(|a, b| {
+                cmp_f64(b.flakiness_rate, a.flakiness_rate)
+                    .then_with(|| cmp_f64(b.failure_rate, a.failure_rate))
+                    .then_with(|| a.name.cmp(&b.name))
+            });
+            Some((job_name.to_string(), metrics))
+        })
+        .collect()
+}
+
+fn build_test_metrics(name: &str, data: &TestData) -> TestMetrics {
+    let total_executions = data.durations.len();
+    let (duration_p50, duration_p95, _) = calculate_percentiles(&data.durations);
+
+    #[allow(clippy::cast_precision_loss)]
+    let rate = |count: usize| (count as f64 / total_executions.max(1) as f64) * 100.0;
+
+    TestMetrics {
+        name: name.to_string(),
+        total_executions,
+        flaky_executions: data.flaky,
+        flakiness_rate: rate(data.flaky),
+        failed_executions: data.failed,
+        failure_rate: rate(data.failed),
+        duration_p50,
+        duration_p95,
+    }
+}
+
+/// Identifies the commit a pipeline ran for, falling back to the pipeline itself.
+fn commit_key(pipeline: &GitLabPipeline) -> &str {
+    if pipeline.sha.is_empty() {
+        &pipeline.id
+    } else {
+        &pipeline.sha
+    }
+}
+
+fn is_passed(test_case: &GitLabTestCase) -> bool {
+    test_case.status == "success"
+}
+
+fn is_failed(test_case: &GitLabTestCase) -> bool {
+    test_case.status == "failed" || test_case.status == "error"
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use crate::providers::gitlab::types::GitLabJob;
+
+    fn test_case(name: &str, status: &str, duration: f64) -> GitLabTestCase {
+        GitLabTestCase {
+            name: name.to_string(),
+            status: status.to_string(),
+            duration,
+        }
+    }
+
+    fn create_job(retried: bool, test_cases: Vec<GitLabTestCase>) -> GitLabJob {
+        GitLabJob {
+            id: "gid://gitlab/Ci::Build/1".to_string(),
+            name: "rspec".to_string(),
+            stage: "test".to_string(),
+            duration: 60.0,
+            status: "SUCCESS".to_string(),
+            retried,
+            needs: None,
+            tags: vec![],
+            runner: None,
+            test_cases,
+        }
+    }
+
+    fn create_pipeline(id: &str, sha: &str, jobs: Vec<GitLabJob>) -> GitLabPipeline {
+        GitLabPipeline {
+            id: id.to_string(),
+            ref_: "main".to_string(),
+            sha: sha.to_string(),
+            weight: 1.0,
+            source: "push".to_string(),
+            status: "success".to_string(),
+            duration: 60,
+            created_at: None,
+            finished_at: None,
+            merge_request: None,
+            stages: vec!["test".to_string()],
+            jobs,
+        }
+    }
+
+    mod calculate_test_metrics {
+        use super::*;
+
+        #[test]
+        fn counts_failure_passing_on_retry_as_flaky() {
+            let pipeline = create_pipeline(
+                "1",
+                "abc",
+                vec![
+                    create_job(true, vec![test_case("login", "failed", 1.0)]),
+                    create_job(false, vec![test_case("login", "success", 3.0)]),
+                ],
+            );
+
+            let metrics = calculate_test_metrics(&[&pipeline]);
+            let login = &metrics["rspec"][0];
+
+            assert_eq!(login.name, "login");
+            assert_eq!(login.total_executions, 2);
+            assert_eq!(login.flaky_executions, 1);
+            assert_eq!(login.flakiness_rate, 50.0);
+            assert_eq!(login.failed_executions, 0);
+            assert_eq!(login.duration_p95, 3.0);
+        }
+
+        #[test]
+        fn counts_failure_passing_in_rerun_of_same_commit_as_flaky() {
+            let failed = create_pipeline(
+                "1",
+                "abc",
+                vec![create_job(false, vec![test_case("login", "error", 1.0)])],
+            );
+            let rerun = create_pipeline(
+                "2",
+                "abc",
+                vec![create_job(false, vec![test_case("login", "success", 1.0)])],
+            );
+            let other_commit = create_pipeline(
+                "3",
+                "def",
+                vec![create_job(false, vec![test_case("login", "failed", 1.0)])],
+            );
+
+            let metrics = calculate_test_metrics(&[&failed, &rerun, &other_commit]);
+            let login = &metrics["rspec"][0];
+
+            assert_eq!(login.total_executions, 3);
+            assert_eq!(login.flaky_executions, 1);
+            assert_eq!(login.failed_executions, 1);
+        }
+
+        #[test]
+        fn omits_healthy_and_skipped_tests() {
+            let pipeline = create_pipeline(
+                "1",
+                "abc",
+                vec![create_job(
+                    false,
+                    vec![
+                        test_case("login", "success", 1.0),
+                        test_case("logout", "skipped", 0.0),
+                    ],
+                )],
+            );
+
+            assert!(calculate_test_metrics(&[&pipeline]).is_empty());
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::{debug, info, warn};
+
+use crate::error::Result;
+
+use super::cache;
+use super::links::extract_numeric_id;
+use super::storage;
+
+/// Job log cache for GitLab failure signature mining.
+///
+/// Logs of finished jobs never change, so each downloaded log is stored once in its own
+/// file, keyed by job ID:
+/// - Linux: `~/.cache/cilens/gitlab/{project-slug}@{instance-slug}-traces/{job-id}.log`
+/// - macOS: `~/Library/Caches/cilens/gitlab/{project-slug}@{instance-slug}-traces/{job-id}.log`
+///
+/// Unlike `JobCache`, logs are read lazily because they can be large.
+pub struct TraceCache {
+    cache_dir: PathBuf,
+    enabled: bool,
+}
+
+impl TraceCache {
+    /// Creates a new job log cache instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_url` - GitLab instance base URL the job logs are downloaded from
+    /// * `project_path` - GitLab project path (e.g., "group/project")
+    /// * `enabled` - Whether caching is enabled
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the cache directory cannot be determined.
+    pub fn new(base_url: &str, project_path: &str, enabled: bool) -> Result<Self> {
+        if !enabled {
+            debug!("Job log cache disabled");
+            return Ok(Self {
+                cache_dir: PathBuf::new(),
+                enabled: false,
+            });
+        }
+
+        Ok(Self {
+            cache_dir: cache::cache_dir()?
+                .join(traces_dir_name(&cache::cache_stem(base_url, project_path))),
+            enabled: true,
+        })
+    }
+
+    /// Retrieves the cached log of a job.
+    ///
+    /// Returns `None` if caching is disabled or the log was never downloaded.
+    pub fn get(&self, job_id: &str) -> Option<String> {
+        if !self.enabled {
+            return None;
+        }
+
+        fs::read_to_string(self.trace_file(job_id)).ok()
+    }
+
+    /// Stores the log of a finished job.
+    ///
+    /// The file is replaced atomically, so a parallel run never reads a partial log.
+    /// Failures are logged and otherwise ignored, since the log can be downloaded again.
+    pub fn put(&self, job_id: &str, trace: &str) {
+        if !self.enabled {
+            return;
+        }
+
+        let result = fs::create_dir_all(&self.cache_dir)
+            .map_err(Into::into)
+            .and_then(|()| storage::write_atomically(&self.trace_file(job_id), trace.as_bytes()));
+        if let Err(e) = result {
+            warn!("Failed to cache job log {job_id}: {e}");
+        }
+    }
+
+    /// Removes the project's job log cache directories of all GitLab instances from
+    /// disk, including the one named after the project only, written by an older cilens.
+    ///
+    /// # Returns
+    ///
+    /// The removed directories.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache directory cannot be listed or a job log directory
+    /// cannot be removed.
+    pub fn clear_project_cache(project_path: &str) -> Result<Vec<PathBuf>> {
+        clear_traces_dirs(&cache::cache_dir()?, project_path)
+    }
+
+    /// Returns the total size of the cached job logs stored next to the job cache named
+    /// `cache_stem` in bytes.
+    pub(super) fn disk_usage(cache_stem: &str) -> u64 {
+        let Ok(entries) = cache::cache_dir()
+            .and_then(|dir| Ok(fs::read_dir(dir.join(traces_dir_name(cache_stem)))?))
+        else {
+            return 0;
+        };
+
+        entries
+            .filter_map(|entry| entry.ok()?.metadata().ok())
+            .map(|metadata| metadata.len())
+            .sum()
+    }
+
+    fn trace_file(&self, job_id: &str) -> PathBuf {
+        self.cache_dir
+            .join(format!("{}.log", extract_numeric_id(job_id)))
+    }
+}
+
+/// Returns the job log directory name for a [`cache::cache_stem`].
+fn traces_dir_name(cache_stem: &str) -> String {
+    format!("{cache_stem}-traces")
+}
+
+fn clear_traces_dirs(cache_dir: &Path, project_path: &str) -> Result<Vec<PathBuf>> {
+    if !cache_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut traces_dirs: Vec<PathBuf> = fs::read_dir(cache_dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_dir()
+                && storage::file_stem(path)
+                    .strip_suffix("-traces")
+                    .is_some_and(|owner| cache::is_project_cache_stem(owner, project_path))
+        })
+        .collect();
+    traces_dirs.sort();
+
+    for traces_dir in &traces_dirs {
+        fs::remove_dir_all(traces_dir)?;
+        info!("Job log cache cleared: {}", traces_dir.display());
+    }
+
+    Ok(traces_dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_cache_disabled() {
+        let cache = TraceCache::new("https://gitlab.com", "group/project", false).unwrap();
+
+        cache.put("gid://gitlab/Ci::Build/1", "log");
+        assert!(cache.get("gid://gitlab/Ci::Build/1").is_none());
+    }
+
+    #[test]
+    fn test_cache_put_and_get() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = TraceCache {
+            cache_dir: temp_dir.path().join("group-project-traces"),
+            enabled: true,
+        };
+
+        cache.put("gid://gitlab/Ci::Build/42", "ERROR: Job failed");
+
+        assert_eq!(
+            cache.get("gid://gitlab/Ci::Build/42").as_deref(),
+            Some("ERROR: Job failed")
+        );
+        assert!(temp_dir.path().join("group-project-traces/42.log").exists());
+        assert!(cache.get("gid://gitlab/Ci::Build/43").is_none());
+    }
+
+    #[test]
+    fn test_traces_dir_is_scoped_by_instance() {
+        assert_eq!(
+            traces_dir_name(&cache::cache_stem(
+                "https://gitlab.example.com",
+                "group/project"
+            )),
+            "group-project@gitlab.example.com-traces"
+        );
+    }
+
+    #[test]
+    fn test_clear_traces_dirs_removes_all_instances_of_project() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        for traces_dir in [
+            "group-project@gitlab.com-traces",
+            "group-project@gitlab.example.com-traces",
+            "group-project-traces",
+            "group-project-other@gitlab.com-traces",
+        ] {
+            fs::create_dir_all(dir.join(traces_dir)).unwrap();
+            fs::write(dir.join(traces_dir).join("1.log"), "log").unwrap();
+        }
+
+        let removed = clear_traces_dirs(dir, "group/project").unwrap();
+
+        assert_eq!(removed.len(), 3);
+        assert!(dir.join("group-project-other@gitlab.com-traces").exists());
+        assert!(!dir.join("group-project@gitlab.com-traces").exists());
+    }
+}
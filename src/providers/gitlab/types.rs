@@ -1,23 +1,84 @@
+use chrono::{DateTime, Utc};
+
 /// A GitLab CI/CD pipeline execution.
 ///
 /// Represents a single pipeline run with its metadata, jobs, and execution details.
-/// Used internally to analyze pipeline patterns and calculate metrics.
-#[derive(Debug)]
+/// Used internally to analyze pipeline patterns and calculate metrics, and serialized
+/// as is for raw data exports.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct GitLabPipeline {
     /// GraphQL Global ID (e.g., <gid://gitlab/Ci::Pipeline/123>)
     pub id: String,
     /// Git reference that triggered the pipeline (e.g., "main", "develop")
+    #[serde(rename = "ref")]
     pub ref_: String,
     /// Trigger source (e.g., "push", "schedule", "web")
     pub source: String,
+    /// Commit SHA the pipeline ran for
+    pub sha: String,
     /// Final pipeline status (e.g., "success", "failed")
     pub status: String,
     /// Total pipeline duration in seconds
     pub duration: usize,
+    /// When the pipeline was created
+    pub created_at: Option<DateTime<Utc>>,
+    /// When the pipeline finished
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Merge request the pipeline ran for (merge request pipelines only)
+    pub merge_request: Option<GitLabMergeRequest>,
     /// Ordered list of stage names
     pub stages: Vec<String>,
     /// All jobs in this pipeline
     pub jobs: Vec<GitLabJob>,
+    /// Number of pipelines in the population this one stands for when calculating rates
+    /// (1.0 unless pipelines were sampled with `Sampling::Stratified`)
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+/// A GitLab pipeline that is still running or waiting to run.
+#[derive(Debug)]
+pub struct GitLabActivePipeline {
+    /// GraphQL Global ID (e.g., <gid://gitlab/Ci::Pipeline/123>)
+    pub id: String,
+    /// Git reference the pipeline runs on
+    pub ref_: String,
+    /// Trigger source (e.g., "push", "schedule", "web")
+    pub source: String,
+    /// Current pipeline status (e.g., "running", "pending")
+    pub status: String,
+    /// When the pipeline was created
+    pub created_at: DateTime<Utc>,
+    /// When the first job started, if one has
+    pub started_at: Option<DateTime<Utc>>,
+}
+
+/// The merge request a pipeline ran for.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct GitLabMergeRequest {
+    /// Project-scoped merge request number
+    pub iid: String,
+    /// When the merge request was merged, if it was
+    pub merged_at: Option<DateTime<Utc>>,
+}
+
+/// A deployment to a GitLab environment.
+#[derive(Debug, Clone)]
+pub struct GitLabDeployment {
+    /// Commit SHA that was deployed
+    pub sha: String,
+    /// Deployment status (e.g., "success", "failed")
+    pub status: String,
+    /// When the deployment was created
+    pub created_at: DateTime<Utc>,
+    /// When the deployment finished
+    pub finished_at: Option<DateTime<Utc>>,
+    /// When the deployed commit was committed
+    pub committed_at: Option<DateTime<Utc>>,
 }
 
 /// A job within a GitLab CI/CD pipeline.
@@ -39,4 +100,34 @@ pub struct GitLabJob {
     pub retried: bool,
     /// Explicit job dependencies via `needs` keyword
     pub needs: Option<Vec<String>>,
+    /// Runner tags requested by the job
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Runner that executed the job (absent if not visible to the token)
+    #[serde(default)]
+    pub runner: Option<GitLabRunner>,
+    /// JUnit test case results (only with `--test-reports`; cached separately)
+    #[serde(skip)]
+    pub test_cases: Vec<GitLabTestCase>,
+}
+
+/// A test case result from a job's JUnit report.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct GitLabTestCase {
+    /// Test name, prefixed with its class name when reported
+    pub name: String,
+    /// Result (e.g., "success", "failed", "error", "skipped")
+    pub status: String,
+    /// Execution time in seconds
+    pub duration: f64,
+}
+
+/// The runner that executed a job.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct GitLabRunner {
+    /// Runner type (e.g., "instance", "group", "project")
+    pub runner_type: String,
+    /// Runner description; on GitLab.com this names the machine type
+    /// (e.g., "saas-linux-small-amd64")
+    pub description: Option<String>,
 }
@@ -0,0 +1,12 @@
+use crate::insights::{ClusteringSummary, ExcludedPipelines, PipelineType};
+
+/// Pipeline types found in one grouping pass, with the pipelines left out of them.
+#[derive(Debug)]
+pub struct PipelineGrouping {
+    /// Types that reach `min_type_percentage`, most common first
+    pub pipeline_types: Vec<PipelineType>,
+    /// Pipelines of all types below `min_type_percentage`, or `None` if none was excluded
+    pub excluded: Option<ExcludedPipelines>,
+    /// How many pipelines `min_type_percentage` drops under each clustering mode
+    pub clustering: ClusteringSummary,
+}
@@ -0,0 +1,179 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use log::debug;
+use reqwest::{Certificate, Client, ClientBuilder, Identity, Proxy};
+
+use crate::error::{CILensError, Result};
+use crate::output::print_warning;
+
+/// HTTP client settings shared by the GitLab and GitHub clients.
+///
+/// Unset values keep reqwest's defaults: no timeout, proxies from the `HTTPS_PROXY` /
+/// `HTTP_PROXY` / `NO_PROXY` environment variables and the bundled root certificates.
+#[derive(Debug, Clone, Default)]
+pub struct HttpSettings {
+    /// Time allowed for a whole request, including reading the response
+    pub timeout: Option<Duration>,
+    /// Time allowed to establish a connection
+    pub connect_timeout: Option<Duration>,
+    /// Proxy for all requests (e.g., `http://proxy.internal:3128`)
+    pub proxy: Option<String>,
+    /// PEM files with CA certificates to trust in addition to the bundled ones
+    pub ca_certs: Vec<PathBuf>,
+    /// PEM file with the client certificate for mutual TLS, and its key unless
+    /// `client_key` is set
+    pub client_cert: Option<PathBuf>,
+    /// PEM file with the private key of `client_cert`
+    pub client_key: Option<PathBuf>,
+    /// Accept any TLS certificate, including self-signed and expired ones
+    pub insecure: bool,
+}
+
+impl HttpSettings {
+    /// Applies the settings to a client builder and builds the client.
+    ///
+    /// Prints a warning to stderr when certificate verification is disabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `builder` - Builder with the provider's own headers and user agent
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error if the proxy URL is invalid, a certificate or key
+    /// file cannot be read or parsed, or the client cannot be built.
+    pub(crate) fn build_client(&self, mut builder: ClientBuilder) -> Result<Client> {
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(connect_timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(proxy) = &self.proxy {
+            let proxy = Proxy::all(proxy)
+                .map_err(|e| CILensError::Config(format!("Invalid proxy URL '{proxy}': {e}")))?;
+            builder = builder.proxy(proxy);
+        }
+
+        for ca_cert in &self.ca_certs {
+            let certificates = Certificate::from_pem_bundle(&read_pem(ca_cert)?).map_err(|e| {
+                CILensError::Config(format!(
+                    "Invalid CA certificate file {}: {e}",
+                    ca_cert.display()
+                ))
+            })?;
+            if certificates.is_empty() {
+                return Err(CILensError::Config(format!(
+                    "CA certificate file {} contains no certificates",
+                    ca_cert.display()
+                )));
+            }
+            debug!(
+                "Trusting {} CA certificates from {}",
+                certificates.len(),
+                ca_cert.display()
+            );
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        if let Some(client_cert) = &self.client_cert {
+            let mut pem = read_pem(client_cert)?;
+            if let Some(client_key) = &self.client_key {
+                pem.push(b'\n');
+                pem.extend(read_pem(client_key)?);
+            }
+            let identity = Identity::from_pem(&pem).map_err(|e| {
+                CILensError::Config(format!(
+                    "Invalid client certificate {}: {e}",
+                    client_cert.display()
+                ))
+            })?;
+            builder = builder.identity(identity);
+        } else if self.client_key.is_some() {
+            return Err(CILensError::Config(
+                "A client key requires a client certificate".into(),
+            ));
+        }
+
+        if self.insecure {
+            print_warning(
+                "TLS certificate verification is disabled (--insecure). Responses can be \
+                 intercepted or forged; only use this against hosts you trust.",
+            );
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+
+        builder
+            .build()
+            .map_err(|e| CILensError::Config(format!("Failed to create HTTP client: {e}")))
+    }
+}
+
+fn read_pem(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path)
+        .map_err(|e| CILensError::Config(format!("Failed to read {}: {e}", path.display())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_default_settings_build_a_client() {
+        assert!(HttpSettings::default()
+            .build_client(Client::builder())
+            .is_ok());
+    }
+
+    #[test]
+    fn test_timeouts_and_proxy_build_a_client() {
+        let settings = HttpSettings {
+            timeout: Some(Duration::from_secs(30)),
+            connect_timeout: Some(Duration::from_secs(5)),
+            proxy: Some("http://proxy.internal:3128".to_string()),
+            ..HttpSettings::default()
+        };
+
+        assert!(settings.build_client(Client::builder()).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_missing_and_invalid_certificates() {
+        let temp_dir = TempDir::new().unwrap();
+        let invalid = temp_dir.path().join("invalid.pem");
+        fs::write(&invalid, "not a certificate").unwrap();
+
+        let missing = HttpSettings {
+            ca_certs: vec![temp_dir.path().join("missing.pem")],
+            ..HttpSettings::default()
+        };
+        let invalid_ca = HttpSettings {
+            ca_certs: vec![invalid.clone()],
+            ..HttpSettings::default()
+        };
+        let invalid_identity = HttpSettings {
+            client_cert: Some(invalid),
+            ..HttpSettings::default()
+        };
+
+        let error = missing.build_client(Client::builder()).unwrap_err();
+        assert!(error.to_string().contains("missing.pem"));
+        assert!(invalid_ca.build_client(Client::builder()).is_err());
+        assert!(invalid_identity.build_client(Client::builder()).is_err());
+    }
+
+    #[test]
+    fn test_rejects_client_key_without_certificate() {
+        let settings = HttpSettings {
+            client_key: Some(PathBuf::from("client.key")),
+            ..HttpSettings::default()
+        };
+
+        assert!(settings.build_client(Client::builder()).is_err());
+    }
+}
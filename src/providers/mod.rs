@@ -1,5 +1,13 @@
 mod github;
 mod gitlab;
+mod grouping;
+mod http;
+mod stats;
 
-pub use github::GitHubProvider;
-pub use gitlab::{GitLabProvider, JobCache};
+pub use github::{BillingModel, GitHubProvider};
+pub use gitlab::{
+    CacheRetention, CacheStats, CacheVerification, Clustering, CostRates, GitLabProvider,
+    GitLabProviderOptions, JobCache, LabelRules, PipelineSource, PruneResult, RateLimits, Sampling,
+    SignatureRules, TestReportCache, TraceCache,
+};
+pub use http::HttpSettings;
@@ -0,0 +1,342 @@
+use std::cmp::Ordering;
+
+pub(crate) fn cmp_f64(a: f64, b: f64) -> Ordering {
+    a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+}
+
+/// Calculate P50, P95, P99 percentiles from a list of values
+/// Returns (p50, p95, p99). If insufficient data, returns same value for all.
+pub(crate) fn calculate_percentiles(values: &[f64]) -> (f64, f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0, 0.0);
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| cmp_f64(*a, *b));
+
+    let len = sorted.len();
+
+    // For small datasets, return the same value (best we can do)
+    if len == 1 {
+        let val = sorted[0];
+        return (val, val, val);
+    }
+
+    // Calculate percentile indices using integer arithmetic
+    let p50_idx = (len / 2).min(len - 1);
+    let p95_idx = (len * 95 / 100).min(len - 1);
+    let p99_idx = (len * 99 / 100).min(len - 1);
+
+    let p50 = sorted[p50_idx];
+    let p95 = sorted[p95_idx];
+    let p99 = sorted[p99_idx];
+
+    (p50, p95, p99)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(clippy::float_cmp)]
+    mod calculate_percentiles {
+        use super::*;
+
+        #[test]
+        fn returns_zeros_for_empty_dataset() {
+            let values: Vec<f64> = vec![];
+            let (p50, p95, p99) = calculate_percentiles(&values);
+            assert_eq!(p50, 0.0);
+            assert_eq!(p95, 0.0);
+            assert_eq!(p99, 0.0);
+        }
+
+        #[test]
+        fn returns_same_value_for_single_element() {
+            let values = vec![42.5];
+            let (p50, p95, p99) = calculate_percentiles(&values);
+            assert_eq!(p50, 42.5);
+            assert_eq!(p95, 42.5);
+            assert_eq!(p99, 42.5);
+        }
+
+        #[test]
+        fn handles_two_element_dataset() {
+            let values = vec![10.0, 20.0];
+            let (p50, p95, p99) = calculate_percentiles(&values);
+            // With 2 elements: p50_idx=1, p95_idx=1, p99_idx=1
+            assert_eq!(p50, 20.0);
+            assert_eq!(p95, 20.0);
+            assert_eq!(p99, 20.0);
+        }
+
+        #[test]
+        fn calculates_percentiles_for_small_sorted_dataset() {
+            let values = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+            let (p50, p95, p99) = calculate_percentiles(&values);
+            // len=5: p50_idx=2 (3.0), p95_idx=4 (5.0), p99_idx=4 (5.0)
+            assert_eq!(p50, 3.0);
+            assert_eq!(p95, 5.0);
+            assert_eq!(p99, 5.0);
+        }
+
+        #[test]
+        fn calculates_percentiles_for_small_unsorted_dataset() {
+            let values = vec![5.0, 2.0, 4.0, 1.0, 3.0];
+            let (p50, p95, p99) = calculate_percentiles(&values);
+            // Should sort to [1.0, 2.0, 3.0, 4.0, 5.0]
+            // len=5: p50_idx=2 (3.0), p95_idx=4 (5.0), p99_idx=4 (5.0)
+            assert_eq!(p50, 3.0);
+            assert_eq!(p95, 5.0);
+            assert_eq!(p99, 5.0);
+        }
+
+        #[test]
+        fn calculates_percentiles_for_medium_dataset() {
+            // 100 elements from 1.0 to 100.0
+            let values: Vec<f64> = (1..=100).map(|i| i as f64).collect();
+            let (p50, p95, p99) = calculate_percentiles(&values);
+            // len=100: p50_idx=50, p95_idx=95, p99_idx=99
+            assert_eq!(p50, 51.0); // Index 50 is value 51
+            assert_eq!(p95, 96.0); // Index 95 is value 96
+            assert_eq!(p99, 100.0); // Index 99 is value 100
+        }
+
+        #[test]
+        fn calculates_percentiles_for_large_dataset() {
+            // 1000 elements from 0.0 to 999.0
+            let values: Vec<f64> = (0..1000).map(|i| i as f64).collect();
+            let (p50, p95, p99) = calculate_percentiles(&values);
+            // len=1000: p50_idx=500, p95_idx=950, p99_idx=990
+            assert_eq!(p50, 500.0);
+            assert_eq!(p95, 950.0);
+            assert_eq!(p99, 990.0);
+        }
+
+        #[test]
+        fn handles_dataset_with_duplicate_values() {
+            let values = vec![5.0, 5.0, 5.0, 5.0, 5.0];
+            let (p50, p95, p99) = calculate_percentiles(&values);
+            assert_eq!(p50, 5.0);
+            assert_eq!(p95, 5.0);
+            assert_eq!(p99, 5.0);
+        }
+
+        #[test]
+        fn handles_dataset_with_negative_values() {
+            let values = vec![-10.0, -5.0, 0.0, 5.0, 10.0];
+            let (p50, p95, p99) = calculate_percentiles(&values);
+            // len=5: p50_idx=2 (0.0), p95_idx=4 (10.0), p99_idx=4 (10.0)
+            assert_eq!(p50, 0.0);
+            assert_eq!(p95, 10.0);
+            assert_eq!(p99, 10.0);
+        }
+
+        #[test]
+        fn handles_dataset_with_very_large_values() {
+            let values = vec![1e10, 2e10, 3e10, 4e10, 5e10];
+            let (p50, p95, p99) = calculate_percentiles(&values);
+            assert_eq!(p50, 3e10);
+            assert_eq!(p95, 5e10);
+            assert_eq!(p99, 5e10);
+        }
+
+        #[test]
+        fn handles_dataset_with_very_small_values() {
+            let values = vec![1e-10, 2e-10, 3e-10, 4e-10, 5e-10];
+            let (p50, p95, p99) = calculate_percentiles(&values);
+            assert_eq!(p50, 3e-10);
+            assert_eq!(p95, 5e-10);
+            assert_eq!(p99, 5e-10);
+        }
+
+        #[test]
+        fn handles_dataset_with_mixed_magnitude_values() {
+            let values = vec![0.001, 1.0, 100.0, 10000.0, 1_000_000.0];
+            let (p50, p95, p99) = calculate_percentiles(&values);
+            assert_eq!(p50, 100.0);
+            assert_eq!(p95, 1_000_000.0);
+            assert_eq!(p99, 1_000_000.0);
+        }
+
+        #[test]
+        fn calculates_correct_indices_for_boundary_sizes() {
+            // Test with 10 elements to verify index calculation
+            let values: Vec<f64> = (1..=10).map(|i| i as f64).collect();
+            let (p50, p95, p99) = calculate_percentiles(&values);
+            // len=10: p50_idx=5, p95_idx=9, p99_idx=9
+            assert_eq!(p50, 6.0); // Index 5 is value 6
+            assert_eq!(p95, 10.0); // Index 9 is value 10
+            assert_eq!(p99, 10.0); // Index 9 is value 10
+        }
+
+        #[test]
+        fn handles_dataset_with_three_elements() {
+            let values = vec![1.0, 2.0, 3.0];
+            let (p50, p95, p99) = calculate_percentiles(&values);
+            // len=3: p50_idx=1 (2.0), p95_idx=2 (3.0), p99_idx=2 (3.0)
+            assert_eq!(p50, 2.0);
+            assert_eq!(p95, 3.0);
+            assert_eq!(p99, 3.0);
+        }
+
+        #[test]
+        fn preserves_precision_for_decimal_values() {
+            let values = vec![1.123, 2.456, 3.789, 4.012, 5.345];
+            let (p50, p95, p99) = calculate_percentiles(&values);
+            assert_eq!(p50, 3.789);
+            assert_eq!(p95, 5.345);
+            assert_eq!(p99, 5.345);
+        }
+
+        #[test]
+        fn handles_zero_values() {
+            let values = vec![0.0, 0.0, 0.0, 0.0, 0.0];
+            let (p50, p95, p99) = calculate_percentiles(&values);
+            assert_eq!(p50, 0.0);
+            assert_eq!(p95, 0.0);
+            assert_eq!(p99, 0.0);
+        }
+
+        #[test]
+        fn handles_dataset_with_infinity() {
+            let values = vec![1.0, 2.0, f64::INFINITY];
+            let (p50, p95, p99) = calculate_percentiles(&values);
+            // len=3: p50_idx=1 (2.0), p95_idx=2 (infinity), p99_idx=2 (infinity)
+            assert_eq!(p50, 2.0);
+            assert_eq!(p95, f64::INFINITY);
+            assert_eq!(p99, f64::INFINITY);
+        }
+
+        #[test]
+        fn handles_dataset_with_negative_infinity() {
+            let values = vec![f64::NEG_INFINITY, 1.0, 2.0];
+            let (p50, p95, p99) = calculate_percentiles(&values);
+            // After sorting: [NEG_INFINITY, 1.0, 2.0]
+            // len=3: p50_idx=1 (1.0), p95_idx=2 (2.0), p99_idx=2 (2.0)
+            assert_eq!(p50, 1.0);
+            assert_eq!(p95, 2.0);
+            assert_eq!(p99, 2.0);
+        }
+    }
+
+    #[allow(clippy::float_cmp)]
+    mod cmp_f64 {
+        use super::*;
+        use std::cmp::Ordering;
+
+        #[test]
+        fn returns_less_when_first_is_smaller() {
+            assert_eq!(cmp_f64(1.0, 2.0), Ordering::Less);
+        }
+
+        #[test]
+        fn returns_greater_when_first_is_larger() {
+            assert_eq!(cmp_f64(2.0, 1.0), Ordering::Greater);
+        }
+
+        #[test]
+        fn returns_equal_when_values_are_equal() {
+            assert_eq!(cmp_f64(1.0, 1.0), Ordering::Equal);
+        }
+
+        #[test]
+        fn returns_equal_for_zero_values() {
+            assert_eq!(cmp_f64(0.0, 0.0), Ordering::Equal);
+        }
+
+        #[test]
+        fn handles_negative_values() {
+            assert_eq!(cmp_f64(-2.0, -1.0), Ordering::Less);
+            assert_eq!(cmp_f64(-1.0, -2.0), Ordering::Greater);
+        }
+
+        #[test]
+        fn handles_negative_and_positive() {
+            assert_eq!(cmp_f64(-1.0, 1.0), Ordering::Less);
+            assert_eq!(cmp_f64(1.0, -1.0), Ordering::Greater);
+        }
+
+        #[test]
+        fn handles_very_small_differences() {
+            let a = 1.0;
+            let b = 1.0 + f64::EPSILON;
+            assert_eq!(cmp_f64(a, b), Ordering::Less);
+        }
+
+        #[test]
+        fn handles_infinity() {
+            assert_eq!(cmp_f64(1.0, f64::INFINITY), Ordering::Less);
+            assert_eq!(cmp_f64(f64::INFINITY, 1.0), Ordering::Greater);
+        }
+
+        #[test]
+        fn handles_negative_infinity() {
+            assert_eq!(cmp_f64(f64::NEG_INFINITY, 1.0), Ordering::Less);
+            assert_eq!(cmp_f64(1.0, f64::NEG_INFINITY), Ordering::Greater);
+        }
+
+        #[test]
+        fn handles_both_infinity() {
+            assert_eq!(cmp_f64(f64::INFINITY, f64::INFINITY), Ordering::Equal);
+            assert_eq!(
+                cmp_f64(f64::NEG_INFINITY, f64::NEG_INFINITY),
+                Ordering::Equal
+            );
+        }
+
+        #[test]
+        fn handles_negative_and_positive_infinity() {
+            assert_eq!(cmp_f64(f64::NEG_INFINITY, f64::INFINITY), Ordering::Less);
+            assert_eq!(cmp_f64(f64::INFINITY, f64::NEG_INFINITY), Ordering::Greater);
+        }
+
+        #[test]
+        fn handles_nan_with_normal_value() {
+            // NaN comparisons return None, so our function returns Equal
+            assert_eq!(cmp_f64(f64::NAN, 1.0), Ordering::Equal);
+            assert_eq!(cmp_f64(1.0, f64::NAN), Ordering::Equal);
+        }
+
+        #[test]
+        fn handles_both_nan() {
+            // NaN == NaN is false, partial_cmp returns None
+            assert_eq!(cmp_f64(f64::NAN, f64::NAN), Ordering::Equal);
+        }
+
+        #[test]
+        fn handles_nan_with_infinity() {
+            assert_eq!(cmp_f64(f64::NAN, f64::INFINITY), Ordering::Equal);
+            assert_eq!(cmp_f64(f64::INFINITY, f64::NAN), Ordering::Equal);
+        }
+
+        #[test]
+        fn handles_very_large_values() {
+            assert_eq!(cmp_f64(f64::MAX, f64::MAX), Ordering::Equal);
+            assert_eq!(cmp_f64(f64::MIN, f64::MAX), Ordering::Less);
+        }
+
+        #[test]
+        fn handles_very_small_positive_values() {
+            assert_eq!(
+                cmp_f64(f64::MIN_POSITIVE, f64::MIN_POSITIVE),
+                Ordering::Equal
+            );
+            assert_eq!(cmp_f64(0.0, f64::MIN_POSITIVE), Ordering::Less);
+        }
+
+        #[test]
+        fn handles_positive_and_negative_zero() {
+            // In IEEE 754, +0.0 and -0.0 compare as equal
+            assert_eq!(cmp_f64(0.0, -0.0), Ordering::Equal);
+            assert_eq!(cmp_f64(-0.0, 0.0), Ordering::Equal);
+        }
+
+        #[test]
+        fn handles_decimal_precision() {
+            assert_eq!(cmp_f64(1.123_456_789, 1.123_456_789), Ordering::Equal);
+            assert_eq!(cmp_f64(1.123_456_788, 1.123_456_789), Ordering::Less);
+            assert_eq!(cmp_f64(1.123_456_790, 1.123_456_789), Ordering::Greater);
+        }
+    }
+}